@@ -1,6 +1,7 @@
 import { useState } from 'react'
 import { TrendingUp, TrendingDown, DollarSign, Percent, BarChart3, ArrowUpRight, ArrowDownRight, ChevronLeft, ChevronRight } from 'lucide-react'
 import { formatTimestamp } from '@/utils/time'
+import { isBuyTrade, tradeLabel } from '@/utils/trade'
 import type { BacktestStatistic, MarketPrecision } from '@/types'
 
 interface BacktestResultProps {
@@ -234,6 +235,16 @@ export default function BacktestResult({ statistic, precision }: BacktestResultP
             <span className="text-sm text-gray-600">Gross Loss</span>
             <span className="text-sm font-medium text-red-600">{formatNumber(statistic.gross_loss)}</span>
           </div>
+          <div className="flex justify-between items-center py-3 border-b border-gray-100">
+            <span className="text-sm text-gray-600">Total Fees</span>
+            <span className="text-sm font-medium text-red-600">{formatNumber(statistic.total_fees)}</span>
+          </div>
+          <div className="flex justify-between items-center py-3 border-b border-gray-100">
+            <span className="text-sm text-gray-600">Return Before Fees / After Fees</span>
+            <span className="text-sm font-medium text-gray-900">
+              {formatPercent(statistic.gross_return_percent)} / {formatPercent(returnPercentValue)}
+            </span>
+          </div>
           <div className="flex justify-between items-center py-3 bg-gray-50 rounded-lg px-3">
             <span className="text-sm font-medium text-gray-700">Net Profit</span>
             <span className={`text-base font-semibold ${isProfit ? 'text-green-600' : 'text-red-600'}`}>
@@ -268,7 +279,7 @@ export default function BacktestResult({ statistic, precision }: BacktestResultP
                 {statistic.trades
                   .slice((currentPage - 1) * TRADES_PER_PAGE, currentPage * TRADES_PER_PAGE)
                   .map((trade, idx) => {
-                    const isBuy = trade.trade_type === 'market_buy' || trade.trade_type === 'limit_buy'
+                    const isBuy = isBuyTrade(trade)
                     const isLimit = trade.trade_type === 'limit_buy' || trade.trade_type === 'limit_sell'
                     const profitValue = Number(trade.profit)
 
@@ -280,7 +291,7 @@ export default function BacktestResult({ statistic, precision }: BacktestResultP
                         <td className="py-3 px-4">
                           <div className="flex items-center gap-1">
                             <span className={`text-xs font-medium px-2 py-1 rounded ${isBuy ? 'bg-green-100 text-green-700' : 'bg-red-100 text-red-700'}`}>
-                              {isBuy ? 'BUY' : 'SELL'}
+                              {tradeLabel(trade)}
                             </span>
                             {isLimit && (
                               <span className="text-xs text-gray-500 px-2 py-1 bg-gray-100 rounded">
@@ -3,6 +3,8 @@ import { Play, Clock, CheckCircle, XCircle, Loader, TrendingUp, TrendingDown, X
 import { api } from '@/services/api'
 import { useBacktestStream } from '@/hooks/useBacktestStream'
 import ComboBox from '@/components/ComboBox'
+import { formatDuration } from '@/utils/time'
+import { isBuyTrade, tradeLabel } from '@/utils/trade'
 import CandlestickChart from '@/components/CandlestickChart'
 import BacktestResult from '@/components/BacktestResult'
 import type { Timeframe, BacktestTask, AvailableCandleInfo, Candle, Trade } from '@/types'
@@ -80,7 +82,7 @@ export default function Backtest() {
 
   const convertTradesToMarkers = (trades: Trade[]): SeriesMarker<Time>[] => {
     return trades.map(trade => {
-      const isBuy = trade.trade_type === 'market_buy' || trade.trade_type === 'limit_buy'
+      const isBuy = isBuyTrade(trade)
       const isLimit = trade.trade_type === 'limit_buy' || trade.trade_type === 'limit_sell'
 
       return {
@@ -88,7 +90,7 @@ export default function Backtest() {
         position: isBuy ? 'belowBar' : 'aboveBar',
         color: isBuy ? '#26a69a' : '#ef5350',
         shape: isBuy ? 'arrowUp' : 'arrowDown',
-        text: `${isLimit ? 'LIMIT' : 'MARKET'} ${isBuy ? 'BUY' : 'SELL'} ${trade.amount} @ ${trade.price}`,
+        text: `${isLimit ? 'LIMIT' : 'MARKET'} ${tradeLabel(trade)} ${trade.amount} @ ${trade.price}`,
       }
     })
   }
@@ -331,6 +333,12 @@ export default function Backtest() {
                                 style={{ width: `${task.progress}%` }}
                               />
                             </div>
+                            {task.throughput !== undefined && task.eta_secs !== undefined && (
+                              <div className="flex items-center justify-between text-xs text-gray-500 mt-1">
+                                <span>{Math.round(task.throughput)} candles/s</span>
+                                <span>~{formatDuration(task.eta_secs * 1000)} remaining</span>
+                              </div>
+                            )}
                           </div>
                         )}
 
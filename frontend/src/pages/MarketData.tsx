@@ -3,6 +3,7 @@ import { Download, Search, Clock, CheckCircle, XCircle, Loader, BarChart3 } from
 import { api } from '@/services/api'
 import { useFetchCandlesStream } from '@/hooks/useFetchCandlesStream'
 import ComboBox from '@/components/ComboBox'
+import { formatDuration } from '@/utils/time'
 import CandlestickChart from '@/components/CandlestickChart'
 import type { Timeframe, FetchCandlesTask, AvailableCandleInfo, Candle } from '@/types'
 import type { CandlestickData } from 'lightweight-charts'
@@ -370,6 +371,12 @@ export default function MarketData() {
                               style={{ width: `${task.progress}%` }}
                             />
                           </div>
+                          {task.throughput !== undefined && task.eta_secs !== undefined && (
+                            <div className="flex justify-between text-xs text-gray-500 mt-1">
+                              <span>{Math.round(task.throughput)} candles/s</span>
+                              <span>~{formatDuration(task.eta_secs * 1000)} remaining</span>
+                            </div>
+                          )}
                         </div>
                       )}
 
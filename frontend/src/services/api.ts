@@ -8,15 +8,33 @@ import type {
   ErrorResponse,
   FetchCandlesTask,
   BacktestTask,
+  BacktestHistoryQuery,
+  BacktestResult,
+  ClearTasksResponse,
   Timeframe,
   GetSourceResponse,
   GetSourceQuery,
   SaveSourceQuery,
+  CreateSourceRequest,
   DeleteSourceQuery,
   MoveSourceQuery,
+  SearchSourceQuery,
+  SearchSourceResponse,
   AddStrategyRequest,
   AvailableCandleInfo,
-  ListStrategiesResponse
+  MarketInfo,
+  Ticker,
+  GetCandlesResponse,
+  LatestCandleQuery,
+  VerifyCandlesQuery,
+  VerifyCandlesResponse,
+  UpdateCandlesRequest,
+  ListStrategiesResponse,
+  StrategyMetadata,
+  ValidateStrategyRequest,
+  ValidateStrategyResponse,
+  ListTasksQuery,
+  ListTasksResponse
 } from '@/types'
 
 const API_BASE_URL = 'http://localhost:3001'
@@ -24,12 +42,14 @@ const API_BASE_URL = 'http://localhost:3001'
 class ApiError extends Error {
   public error: string
   public status: number
+  public requestId?: string
 
-  constructor(error: string, message: string, status: number) {
+  constructor(error: string, message: string, status: number, requestId?: string) {
     super(message)
     this.name = 'ApiError'
     this.error = error
     this.status = status
+    this.requestId = requestId
   }
 }
 
@@ -45,10 +65,12 @@ async function fetchAPI<T>(endpoint: string, options?: RequestInit): Promise<T>
 
     if (!response.ok) {
       const error: ErrorResponse = await response.json()
-      const apiError = new ApiError(error.error, error.message, response.status)
+      const apiError = new ApiError(error.error, error.message, response.status, error.request_id)
 
       toast.error('API Error', {
-        description: error.message,
+        description: error.request_id
+          ? `${error.message} (request ${error.request_id})`
+          : error.message,
         duration: 5000,
       })
 
@@ -77,6 +99,11 @@ export const api = {
 
   exchanges: {
     list: () => fetchAPI<string[]>('/exchanges'),
+
+    markets: (exchange: string, symbol?: string) =>
+      fetchAPI<MarketInfo[]>(
+        `/exchanges/${encodeURIComponent(exchange)}/markets${symbol ? `?symbol=${encodeURIComponent(symbol)}` : ''}`
+      ),
   },
 
   symbols: {
@@ -84,11 +111,44 @@ export const api = {
       fetchAPI<string[]>(`/symbols?exchange=${encodeURIComponent(exchange)}`),
   },
 
+  ticker: {
+    get: (exchange: string, symbol: string) =>
+      fetchAPI<Ticker>(
+        `/ticker?exchange=${encodeURIComponent(exchange)}&symbol=${encodeURIComponent(symbol)}`
+      ),
+  },
+
   timeframes: {
     list: (exchange: string) =>
       fetchAPI<Timeframe[]>(`/timeframes?exchange=${encodeURIComponent(exchange)}`),
   },
 
+  tasks: {
+    list: (query: ListTasksQuery = {}) => {
+      const params = new URLSearchParams({
+        ...(query.status && { status: query.status }),
+        ...(query.task_type && { task_type: query.task_type }),
+        ...(query.start && { start: query.start.toString() }),
+        ...(query.end && { end: query.end.toString() }),
+        ...(query.sort && { sort: query.sort }),
+        ...(query.order && { order: query.order }),
+        ...(query.offset !== undefined && { offset: query.offset.toString() }),
+        ...(query.limit !== undefined && { limit: query.limit.toString() }),
+      })
+      return fetchAPI<ListTasksResponse>(`/tasks?${params}`)
+    },
+
+    delete: (id: string) =>
+      fetchAPI<void>(`/tasks/${id}`, {
+        method: 'DELETE',
+      }),
+
+    clearCompleted: () =>
+      fetchAPI<ClearTasksResponse>('/tasks/completed', {
+        method: 'DELETE',
+      }),
+  },
+
   fetchCandles: {
     getAll: () => fetchAPI<FetchCandlesTask[]>('/tasks/fetch'),
 
@@ -134,6 +194,11 @@ export const api = {
 
     getById: (id: string) => fetchAPI<BacktestTask>(`/tasks/backtest/${id}`),
 
+    history: (query: BacktestHistoryQuery) =>
+      fetchAPI<BacktestResult[]>(
+        `/strategy/backtest/history?name=${encodeURIComponent(query.name)}${query.limit ? `&limit=${query.limit}` : ''}`
+      ),
+
     create: (request: CreateBacktestTaskRequest) =>
       fetchAPI<CreateBacktestTaskResponse>('/tasks/backtest', {
         method: 'POST',
@@ -170,23 +235,56 @@ export const api = {
   },
 
   candles: {
-    get: (params: {
+    get: async (params: {
       exchange: string
       symbol: string
       timeframe: Timeframe
       start?: number
       end?: number
     }) => {
-      const query = new URLSearchParams({
-        exchange: params.exchange,
-        symbol: params.symbol,
-        timeframe: params.timeframe,
-        ...(params.start && { start: params.start.toString() }),
-        ...(params.end && { end: params.end.toString() }),
+      const candles: Candle[] = []
+      let start = params.start
+      for (;;) {
+        const query = new URLSearchParams({
+          exchange: params.exchange,
+          symbol: params.symbol,
+          timeframe: params.timeframe,
+          ...(start && { start: start.toString() }),
+          ...(params.end && { end: params.end.toString() }),
+        })
+        const page = await fetchAPI<GetCandlesResponse>(`/candles?${query}`)
+        candles.push(...page.candles)
+        if (page.next_cursor == null) {
+          return candles
+        }
+        start = page.next_cursor
+      }
+    },
+
+    latest: (query: LatestCandleQuery) =>
+      fetchAPI<Candle>(
+        `/candles/latest?exchange=${encodeURIComponent(query.exchange)}&symbol=${encodeURIComponent(query.symbol)}&timeframe=${query.timeframe}`
+      ),
+
+    verify: (query: VerifyCandlesQuery) => {
+      const params = new URLSearchParams({
+        exchange: query.exchange,
+        symbol: query.symbol,
+        timeframe: query.timeframe,
+        ...(query.start && { start: query.start.toString() }),
+        ...(query.end && { end: query.end.toString() }),
+        ...(query.samples && { samples: query.samples.toString() }),
+        ...(query.tolerance !== undefined && { tolerance: query.tolerance.toString() }),
       })
-      return fetchAPI<Candle[]>(`/candles?${query}`)
+      return fetchAPI<VerifyCandlesResponse>(`/candles/verify?${params}`)
     },
 
+    update: (request: UpdateCandlesRequest) =>
+      fetchAPI<CreateFetchCandlesTaskResponse>('/candles/update', {
+        method: 'POST',
+        body: JSON.stringify(request),
+      }),
+
     available: () => fetchAPI<AvailableCandleInfo[]>('/candles/available'),
   },
 
@@ -200,21 +298,39 @@ export const api = {
         body: JSON.stringify(content),
       }),
 
+    create: (request: CreateSourceRequest) =>
+      fetchAPI<void>('/strategy/source/create', {
+        method: 'POST',
+        body: JSON.stringify(request),
+      }),
+
     delete: (query: DeleteSourceQuery) =>
       fetchAPI<void>(`/strategy/source/delete?path=${encodeURIComponent(query.path)}`),
 
     move: (query: MoveSourceQuery) =>
       fetchAPI<void>(`/strategy/source/move?old_path=${encodeURIComponent(query.old_path)}&new_path=${encodeURIComponent(query.new_path)}`),
+
+    search: (query: SearchSourceQuery) =>
+      fetchAPI<SearchSourceResponse>(`/strategy/source/search?q=${encodeURIComponent(query.q)}&regex=${query.regex ?? false}`),
   },
 
   strategy: {
     list: () => fetchAPI<ListStrategiesResponse>('/strategy/list'),
 
+    meta: (name: string) =>
+      fetchAPI<StrategyMetadata>(`/strategy/${encodeURIComponent(name)}/meta`),
+
     add: (request: AddStrategyRequest) =>
       fetchAPI<void>('/strategy/add', {
         method: 'POST',
         body: JSON.stringify(request),
       }),
+
+    validate: (request: ValidateStrategyRequest) =>
+      fetchAPI<ValidateStrategyResponse>('/strategy/validate', {
+        method: 'POST',
+        body: JSON.stringify(request),
+      }),
   },
 }
 
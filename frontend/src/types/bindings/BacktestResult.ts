@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BacktestStatistic } from "./BacktestStatistic";
+import type { Timeframe } from "./Timeframe";
+
+export type BacktestResult = { id: string, name: string, exchange: string, symbol: string, timeframe: Timeframe, params?: Record<string, unknown>, statistic: BacktestStatistic, completed_at: number, };
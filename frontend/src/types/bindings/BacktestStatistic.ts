@@ -1,4 +1,8 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FundingPayment } from "./FundingPayment";
+import type { PlotMarker } from "./PlotMarker";
+import type { PlotPoint } from "./PlotPoint";
+import type { StrategyLog } from "./StrategyLog";
 import type { Trade } from "./Trade";
 
-export type BacktestStatistic = { trades: Array<Trade>, initial_capital: string, total_cost: string, net_profit: string, return_percent: number, max_equity: string, max_drawdown: string, max_drawdown_percent: number, gross_profit: string, gross_loss: string, profit_factor: number, sharpe_ratio: number, total_trades: number, buy_trades: number, sell_trades: number, winning_trades: number, losing_trades: number, win_rate: number, avg_win: string, avg_loss: string, largest_win: string, largest_loss: string, };
+export type BacktestStatistic = { trades: Array<Trade>, logs: Array<StrategyLog>, plots: { [key in string]?: Array<PlotPoint> }, markers: Array<PlotMarker>, funding_payments: Array<FundingPayment>, initial_capital: string, total_cost: string, funding_cost: string, total_fees: string, net_profit: string, return_percent: number, gross_return_percent: number, max_equity: string, max_drawdown: string, max_drawdown_percent: number, gross_profit: string, gross_loss: string, profit_factor: number, sharpe_ratio: number, sortino_ratio?: number, calmar_ratio?: number, benchmark_return_percent: number, benchmark_curve: Array<PlotPoint>, candles_processed: number, time_in_market_percent: number, round_trips: number, avg_holding_candles: number, avg_holding_ms: number, total_trades: number, buy_trades: number, sell_trades: number, winning_trades: number, losing_trades: number, win_rate: number, avg_win: string, avg_loss: string, largest_win: string, largest_loss: string, };
@@ -1,7 +1,27 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 import type { BacktestStatistic } from "./BacktestStatistic";
 import type { BacktestStatus } from "./BacktestStatus";
+import type { ExecutionTiming } from "./ExecutionTiming";
+import type { FeeRounding } from "./FeeRounding";
 import type { MarketPrecision } from "./MarketPrecision";
+import type { MarketType } from "./MarketType";
+import type { SlippageModel } from "./SlippageModel";
 import type { Timeframe } from "./Timeframe";
 
-export type BacktestTask = { id: string, status: BacktestStatus, progress: number, name: string, exchange: string, symbol: string, timeframe: Timeframe, precision: MarketPrecision, statistic?: BacktestStatistic, error_message?: string, created_at: number, started_at?: number, completed_at?: number, updated_at: number, };
+export type BacktestTask = { id: string, status: BacktestStatus, progress: number, 
+/**
+ * Candles processed per second.
+ */
+throughput?: number, 
+/**
+ * Estimated seconds until the task completes at the current throughput.
+ */
+eta_secs?: number, name: string, exchange: string, symbol: string, timeframe: Timeframe, precision: MarketPrecision, extra_symbols?: Array<string>, extra_timeframes?: Array<Timeframe>, params?: Record<string, unknown>, 
+/**
+ * First candle of the backtest, including warmup candles.
+ */
+start?: number, 
+/**
+ * Last candle of the backtest.
+ */
+end?: number, warmup: number, execution_timing: ExecutionTiming, slippage?: SlippageModel, limit_fill_ratio?: number, market_volume_ratio?: number, market_type: MarketType, fee_rounding: FeeRounding, statistic?: BacktestStatistic, error_message?: string, created_at: number, started_at?: number, completed_at?: number, updated_at: number, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Holdings of one currency in an exchange account. Exchanges that do not
+ * report a field leave it empty.
+ */
+export type Balance = { currency: string, free?: string, used?: string, total?: string, };
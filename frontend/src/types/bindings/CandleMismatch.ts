@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Candle } from "./Candle";
+
+export type CandleMismatch = { stored: Candle, 
+/**
+ * Missing when the exchange returned no candle at the stored timestamp.
+ */
+exchange?: Candle, fields: Array<string>, };
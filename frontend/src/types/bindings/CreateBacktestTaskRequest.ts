@@ -1,4 +1,8 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ExecutionTiming } from "./ExecutionTiming";
+import type { FeeRounding } from "./FeeRounding";
+import type { MarketType } from "./MarketType";
+import type { SlippageModel } from "./SlippageModel";
 import type { Timeframe } from "./Timeframe";
 
-export type CreateBacktestTaskRequest = { name: string, exchange: string, symbol: string, timeframe: Timeframe, };
+export type CreateBacktestTaskRequest = { name: string, exchange: string, symbol: string, timeframe: Timeframe, extra_symbols?: Array<string>, extra_timeframes?: Array<Timeframe>, force_rebuild?: boolean, params?: Record<string, unknown>, start?: number, end?: number, warmup?: number, execution_timing?: ExecutionTiming, slippage?: SlippageModel, limit_fill_ratio?: number, market_volume_ratio?: number, market_type?: MarketType, fee_rounding?: FeeRounding, };
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Timeframe } from "./Timeframe";
+
+export type CreateFetchAllCandlesTaskRequest = { exchange: string, timeframe: Timeframe, symbols?: Array<string>, 
+/**
+ * Overrides the delay between exchange requests, in milliseconds.
+ */
+delay_ms?: number, batch_limit?: number, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { OptimizeMetric } from "./OptimizeMetric";
+import type { ParameterRange } from "./ParameterRange";
+import type { Timeframe } from "./Timeframe";
+
+export type CreateOptimizeTaskRequest = { name: string, exchange: string, symbol: string, timeframe: Timeframe, grid: { [key in string]?: ParameterRange }, metric: OptimizeMetric, };
@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { OptimizeMetric } from "./OptimizeMetric";
+import type { ParameterRange } from "./ParameterRange";
+import type { Timeframe } from "./Timeframe";
+
+export type CreateWalkForwardTaskRequest = { name: string, exchange: string, symbol: string, timeframe: Timeframe, grid: { [key in string]?: ParameterRange }, metric: OptimizeMetric, 
+/**
+ * Candles in each in-sample window.
+ */
+in_sample: number, 
+/**
+ * Candles in each out-of-sample window.
+ */
+out_of_sample: number, 
+/**
+ * Candles the windows move forward between folds, the out-of-sample
+ * size by default.
+ */
+step?: number, };
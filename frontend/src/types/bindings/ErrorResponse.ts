@@ -1,3 +1,3 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 
-export type ErrorResponse = { error: string, message: string, };
+export type ErrorResponse = { error: string, message: string, request_id?: string, };
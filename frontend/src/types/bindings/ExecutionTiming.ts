@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * When the strategy acts relative to the candle it sees. With `Close`, `tick`
+ * sees the current candle's close and market orders fill at it, which assumes
+ * the order could be placed at the very price that closed the candle. With
+ * `NextOpen`, `tick` sees only closed candles and orders fill from the open of
+ * the following candle.
+ */
+export type ExecutionTiming = "close" | "next_open";
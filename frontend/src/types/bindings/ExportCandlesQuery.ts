@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ExportFormat } from "./ExportFormat";
+import type { Timeframe } from "./Timeframe";
+
+export type ExportCandlesQuery = { exchange: string, symbol: string, timeframe: Timeframe, start?: number, end?: number, format?: ExportFormat, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Direction fees are rounded in to `fee_precision`. `Up` charges any
+ * fraction of a step to the trader.
+ */
+export type FeeRounding = "up" | "down" | "half_even";
@@ -0,0 +1,17 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FetchAllCandlesFailure } from "./FetchAllCandlesFailure";
+import type { FetchCandlesResult } from "./FetchCandlesResult";
+import type { FetchCandlesStatus } from "./FetchCandlesStatus";
+import type { Timeframe } from "./Timeframe";
+
+export type FetchAllCandlesTask = { id: string, status: FetchCandlesStatus, progress: number, exchange: string, timeframe: Timeframe, symbols: Array<string>, 
+/**
+ * Milliseconds to wait between consecutive exchange requests, defaulting
+ * to the exchange's advertised rate limit.
+ */
+delay_ms: number, 
+/**
+ * Candles requested per exchange call, capped at the exchange's maximum.
+ * The exchange picks its own page size when unset.
+ */
+batch_limit?: number, completed_symbols: number, current_symbol?: string, results: Array<FetchCandlesResult>, failures: Array<FetchAllCandlesFailure>, error_message?: string, created_at: number, started_at?: number, completed_at?: number, updated_at: number, };
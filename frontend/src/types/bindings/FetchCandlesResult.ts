@@ -1,4 +1,12 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 import type { Timeframe } from "./Timeframe";
 
-export type FetchCandlesResult = { symbol: string, exchange: string, timeframe: Timeframe, records: bigint, };
+export type FetchCandlesResult = { symbol: string, exchange: string, timeframe: Timeframe, records: bigint, 
+/**
+ * Candles that were already stored.
+ */
+skipped: number, 
+/**
+ * Candles dropped by validation, e.g. with a high below the low.
+ */
+rejected: number, };
@@ -3,4 +3,22 @@ import type { FetchCandlesResult } from "./FetchCandlesResult";
 import type { FetchCandlesStatus } from "./FetchCandlesStatus";
 import type { Timeframe } from "./Timeframe";
 
-export type FetchCandlesTask = { id: string, status: FetchCandlesStatus, progress: number, symbol: string, exchange: string, timeframe: Timeframe, result?: FetchCandlesResult, error_message?: string, created_at: number, started_at?: number, completed_at?: number, updated_at: number, };
+export type FetchCandlesTask = { id: string, status: FetchCandlesStatus, progress: number, 
+/**
+ * Candles fetched per second.
+ */
+throughput?: number, 
+/**
+ * Estimated seconds until the task completes at the current throughput.
+ */
+eta_secs?: number, symbol: string, exchange: string, timeframe: Timeframe, start?: number, end?: number, 
+/**
+ * Milliseconds to wait between consecutive exchange requests, defaulting
+ * to the exchange's advertised rate limit.
+ */
+delay_ms: number, 
+/**
+ * Candles requested per exchange call, capped at the exchange's maximum.
+ * The exchange picks its own page size when unset.
+ */
+batch_limit?: number, result?: FetchCandlesResult, error_message?: string, created_at: number, started_at?: number, completed_at?: number, updated_at: number, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A funding charge on a perpetual swap position. Positive amounts are paid,
+ * negative amounts are received.
+ */
+export type FundingPayment = { timestamp: number, symbol: string, rate: string, amount: string, };
@@ -1,4 +1,8 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 import type { Timeframe } from "./Timeframe";
 
-export type GetCandlesQuery = { exchange: string, symbol: string, timeframe: Timeframe, start?: number, end?: number, };
+export type GetCandlesQuery = { exchange: string, symbol: string, timeframe: Timeframe, start?: number, end?: number, source?: Timeframe, 
+/**
+ * Candles per page; with `source`, counts the resampled candles.
+ */
+limit?: number, max_points?: number, };
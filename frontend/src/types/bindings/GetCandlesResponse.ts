@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Candle } from "./Candle";
+
+export type GetCandlesResponse = { candles: Array<Candle>, next_cursor?: number, };
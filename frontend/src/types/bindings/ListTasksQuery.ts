@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SortOrder } from "./SortOrder";
+import type { TaskSortField } from "./TaskSortField";
+import type { TaskStatus } from "./TaskStatus";
+import type { TaskType } from "./TaskType";
+
+/**
+ * Filters on `start` and `end` apply to the task's creation time, both
+ * inclusive.
+ */
+export type ListTasksQuery = { status?: TaskStatus, task_type?: TaskType, start?: number, end?: number, sort?: TaskSortField, order?: SortOrder, offset?: number, limit?: number, };
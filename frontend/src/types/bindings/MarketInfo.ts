@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { MarketLimits } from "./MarketLimits";
+import type { MarketPrecision } from "./MarketPrecision";
+import type { TradingFees } from "./TradingFees";
+
+export type MarketInfo = { symbol: string, precision: MarketPrecision, fees: TradingFees, limits: MarketLimits, };
@@ -1,3 +1,8 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FeeRounding } from "./FeeRounding";
 
-export type MarketPrecision = { price_precision: string, amount_precision: string, };
+export type MarketPrecision = { price_precision: string, amount_precision: string, 
+/**
+ * Step of the quote currency fees are charged in. Zero disables rounding.
+ */
+fee_precision: string, fee_rounding: FeeRounding, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Swap markets are charged funding on held positions during the backtest.
+ */
+export type MarketType = "spot" | "swap";
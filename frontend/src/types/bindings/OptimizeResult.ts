@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type OptimizeResult = { params: Record<string, unknown>, score: number, net_profit: string, return_percent: number, sharpe_ratio: number, max_drawdown_percent: number, total_trades: number, win_rate: number, };
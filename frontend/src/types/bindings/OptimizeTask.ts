@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { OptimizeMetric } from "./OptimizeMetric";
+import type { OptimizeResult } from "./OptimizeResult";
+import type { OptimizeStatus } from "./OptimizeStatus";
+import type { ParameterRange } from "./ParameterRange";
+import type { Timeframe } from "./Timeframe";
+
+export type OptimizeTask = { id: string, status: OptimizeStatus, progress: number, name: string, exchange: string, symbol: string, timeframe: Timeframe, grid: { [key in string]?: ParameterRange }, metric: OptimizeMetric, total_combinations: number, completed_combinations: number, failed_combinations: number, results: Array<OptimizeResult>, error_message?: string, created_at: number, started_at?: number, completed_at?: number, updated_at: number, };
@@ -1,3 +1,3 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 
-export type OrderType = "limit_buy" | "limit_sell";
+export type OrderType = "limit_buy" | "limit_sell" | "trailing_stop_sell";
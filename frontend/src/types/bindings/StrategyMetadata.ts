@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A copy of a plugin's metadata that outlives the loaded library.
+ */
+export type StrategyMetadata = { name: string, description: string, author: string, version: string, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A client receives the events of every task until its first `subscribe`,
+ * and from then on only those of the tasks it is subscribed to, even once
+ * it has unsubscribed from all of them. `unsubscribe` before any `subscribe`
+ * has no effect. `cancel` stops pending or running tasks, which then fail
+ * with a cancellation error; ids of unknown or finished tasks are ignored.
+ */
+export type TaskClientMessage = { "action": "subscribe", task_ids: Array<string>, } | { "action": "unsubscribe", task_ids: Array<string>, } | { "action": "cancel", task_ids: Array<string>, };
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BacktestTask } from "./BacktestTask";
+import type { FetchAllCandlesTask } from "./FetchAllCandlesTask";
+import type { FetchCandlesTask } from "./FetchCandlesTask";
+import type { OptimizeTask } from "./OptimizeTask";
+import type { WalkForwardTask } from "./WalkForwardTask";
+
+export type TaskEvent = { "type": "snapshot", fetch_candles: Array<FetchCandlesTask>, fetch_all_candles: Array<FetchAllCandlesTask>, backtest: Array<BacktestTask>, optimize: Array<OptimizeTask>, walk_forward: Array<WalkForwardTask>, } | { "type": "fetch_candles", task: FetchCandlesTask, } | { "type": "fetch_all_candles", task: FetchAllCandlesTask, } | { "type": "backtest", task: BacktestTask, } | { "type": "optimize", task: OptimizeTask, } | { "type": "walk_forward", task: WalkForwardTask, } | { "type": "removed", task_id: string, };
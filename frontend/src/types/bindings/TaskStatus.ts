@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Status shared by every task kind; fetch tasks never report `Compiling`.
+ */
+export type TaskStatus = "pending" | "compiling" | "running" | "completed" | "failed";
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TaskStatus } from "./TaskStatus";
+import type { TaskType } from "./TaskType";
+
+/**
+ * The fields every task kind has, for listing tasks without their results.
+ */
+export type TaskSummary = { id: string, task_type: TaskType, status: TaskStatus, progress: number, error_message?: string, created_at: number, started_at?: number, completed_at?: number, updated_at: number, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * What a trade did to the position: buys open or add to a long and sells
+ * close it, while a short is opened by selling and closed by buying to cover.
+ */
+export type TradeIntent = "open_long" | "close_long" | "open_short" | "close_short";
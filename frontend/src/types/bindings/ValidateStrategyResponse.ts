@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { StrategyValidationStage } from "./StrategyValidationStage";
+
+export type ValidateStrategyResponse = { valid: boolean, stage?: StrategyValidationStage, message?: string, };
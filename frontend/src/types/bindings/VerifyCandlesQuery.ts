@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Timeframe } from "./Timeframe";
+
+export type VerifyCandlesQuery = { exchange: string, symbol: string, timeframe: Timeframe, start?: number, end?: number, samples?: number, 
+/**
+ * Largest accepted difference relative to the stored value, 0 by default.
+ */
+tolerance?: number, };
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CandleMismatch } from "./CandleMismatch";
+
+export type VerifyCandlesResponse = { sampled: number, matched: number, mismatched: number, missing: number, 
+/**
+ * Sampled candles left unchecked because verification ran out of time.
+ */
+skipped: number, examples: Array<CandleMismatch>, };
@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { OptimizeResult } from "./OptimizeResult";
+
+/**
+ * One in-sample optimization and the out-of-sample backtest that follows it.
+ */
+export type WalkForwardFold = { index: number, in_sample_start: number, in_sample_end: number, out_of_sample_start: number, out_of_sample_end: number, 
+/**
+ * Best scoring params on the in-sample window.
+ */
+in_sample?: OptimizeResult, 
+/**
+ * Those params backtested on the out-of-sample window.
+ */
+out_of_sample?: OptimizeResult, failed_combinations: number, error_message?: string, };
@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type WalkForwardSummary = { 
+/**
+ * Folds with an out-of-sample result; the averages cover only these.
+ */
+completed_folds: number, profitable_folds: number, total_net_profit: string, average_return_percent: number, average_in_sample_score: number, average_out_of_sample_score: number, 
+/**
+ * Average out-of-sample score over average in-sample score. Values far
+ * below 1 suggest the params were fitted to noise.
+ */
+efficiency?: number, };
@@ -0,0 +1,21 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { OptimizeMetric } from "./OptimizeMetric";
+import type { ParameterRange } from "./ParameterRange";
+import type { Timeframe } from "./Timeframe";
+import type { WalkForwardFold } from "./WalkForwardFold";
+import type { WalkForwardStatus } from "./WalkForwardStatus";
+import type { WalkForwardSummary } from "./WalkForwardSummary";
+
+export type WalkForwardTask = { id: string, status: WalkForwardStatus, progress: number, name: string, exchange: string, symbol: string, timeframe: Timeframe, grid: { [key in string]?: ParameterRange }, metric: OptimizeMetric, 
+/**
+ * Candles in each in-sample window.
+ */
+in_sample: number, 
+/**
+ * Candles in each out-of-sample window.
+ */
+out_of_sample: number, 
+/**
+ * Candles the windows move forward between folds.
+ */
+step: number, total_folds: number, folds: Array<WalkForwardFold>, summary?: WalkForwardSummary, error_message?: string, created_at: number, started_at?: number, completed_at?: number, updated_at: number, };
@@ -1,29 +1,99 @@
 export * from './bindings/AddStrategyRequest'
 export * from './bindings/AvailableCandleInfo'
+export * from './bindings/BacktestHistoryQuery'
+export * from './bindings/BacktestProgress'
+export * from './bindings/BacktestResult'
 export * from './bindings/BacktestStatistic'
 export * from './bindings/BacktestStatus'
 export * from './bindings/BacktestTask'
+export * from './bindings/Balance'
 export * from './bindings/Candle'
+export * from './bindings/CandleMismatch'
+export * from './bindings/ClearTasksResponse'
 export * from './bindings/CreateBacktestTaskRequest'
 export * from './bindings/CreateBacktestTaskResponse'
+export * from './bindings/CreateFetchAllCandlesTaskRequest'
+export * from './bindings/CreateFetchAllCandlesTaskResponse'
 export * from './bindings/CreateFetchCandlesTaskRequest'
 export * from './bindings/CreateFetchCandlesTaskResponse'
+export * from './bindings/CreateOptimizeTaskRequest'
+export * from './bindings/CreateOptimizeTaskResponse'
+export * from './bindings/CreateSourceRequest'
+export * from './bindings/CreateWalkForwardTaskRequest'
+export * from './bindings/CreateWalkForwardTaskResponse'
+export * from './bindings/DeleteCandlesQuery'
+export * from './bindings/DeleteCandlesResponse'
 export * from './bindings/DeleteSourceQuery'
 export * from './bindings/ErrorResponse'
 export * from './bindings/ExchangeQuery'
+export * from './bindings/ExecutionTiming'
+export * from './bindings/ExportBacktestQuery'
+export * from './bindings/ExportCandlesQuery'
+export * from './bindings/ExportFormat'
+export * from './bindings/FeeRounding'
+export * from './bindings/FetchAllCandlesFailure'
+export * from './bindings/FetchAllCandlesTask'
 export * from './bindings/FetchCandlesResult'
 export * from './bindings/FetchCandlesStatus'
 export * from './bindings/FetchCandlesTask'
 export * from './bindings/FileNode'
 export * from './bindings/FileNodeType'
+export * from './bindings/FundingPayment'
+export * from './bindings/FundingRate'
 export * from './bindings/GetCandlesQuery'
+export * from './bindings/GetCandlesResponse'
 export * from './bindings/GetSourceQuery'
 export * from './bindings/GetSourceResponse'
+export * from './bindings/LatestCandleQuery'
 export * from './bindings/ListStrategiesResponse'
+export * from './bindings/ListTasksQuery'
+export * from './bindings/ListTasksResponse'
+export * from './bindings/LogLevel'
+export * from './bindings/MarketInfo'
+export * from './bindings/MarketLimits'
 export * from './bindings/MarketPrecision'
+export * from './bindings/MarketType'
+export * from './bindings/MarketsQuery'
 export * from './bindings/MoveSourceQuery'
+export * from './bindings/OptimizeMetric'
+export * from './bindings/OptimizeResult'
+export * from './bindings/OptimizeStatus'
+export * from './bindings/OptimizeTask'
 export * from './bindings/OrderType'
+export * from './bindings/ParameterRange'
+export * from './bindings/PlotMarker'
+export * from './bindings/PlotPoint'
+export * from './bindings/ReadinessCheck'
+export * from './bindings/ReadinessResponse'
+export * from './bindings/RemoveStrategyRequest'
 export * from './bindings/SaveSourceQuery'
+export * from './bindings/SearchSourceMatch'
+export * from './bindings/SearchSourceQuery'
+export * from './bindings/SearchSourceResponse'
+export * from './bindings/SlippageModel'
+export * from './bindings/SortOrder'
+export * from './bindings/StrategyLog'
+export * from './bindings/StrategyMetadata'
+export * from './bindings/StrategyValidationStage'
+export * from './bindings/TaskClientMessage'
+export * from './bindings/TaskEvent'
+export * from './bindings/TaskSortField'
+export * from './bindings/TaskStatus'
+export * from './bindings/TaskSummary'
+export * from './bindings/TaskType'
+export * from './bindings/Ticker'
+export * from './bindings/TickerQuery'
 export * from './bindings/Timeframe'
 export * from './bindings/Trade'
+export * from './bindings/TradeIntent'
 export * from './bindings/TradeType'
+export * from './bindings/TradingFees'
+export * from './bindings/UpdateCandlesRequest'
+export * from './bindings/ValidateStrategyRequest'
+export * from './bindings/ValidateStrategyResponse'
+export * from './bindings/VerifyCandlesQuery'
+export * from './bindings/VerifyCandlesResponse'
+export * from './bindings/WalkForwardFold'
+export * from './bindings/WalkForwardStatus'
+export * from './bindings/WalkForwardSummary'
+export * from './bindings/WalkForwardTask'
@@ -0,0 +1,27 @@
+import type { Trade, TradeIntent } from '@/types'
+
+const INTENT_LABELS: Record<TradeIntent, string> = {
+  open_long: 'BUY',
+  close_long: 'SELL',
+  open_short: 'SHORT',
+  close_short: 'COVER',
+}
+
+export function tradeIntent(trade: Trade): TradeIntent {
+  if (trade.intent) {
+    return trade.intent
+  }
+  // Trades recorded before intents were tracked are all long.
+  return trade.trade_type === 'market_buy' || trade.trade_type === 'limit_buy'
+    ? 'open_long'
+    : 'close_long'
+}
+
+export function isBuyTrade(trade: Trade): boolean {
+  const intent = tradeIntent(trade)
+  return intent === 'open_long' || intent === 'close_short'
+}
+
+export function tradeLabel(trade: Trade): string {
+  return INTENT_LABELS[tradeIntent(trade)]
+}
@@ -0,0 +1,5 @@
+CREATE TABLE optimize_tasks (
+    id UUID PRIMARY KEY,
+    data JSONB NOT NULL,
+    completed_at TIMESTAMPTZ NOT NULL
+);
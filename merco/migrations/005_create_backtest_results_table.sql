@@ -0,0 +1,12 @@
+CREATE TABLE backtest_results (
+    id UUID PRIMARY KEY,
+    name TEXT NOT NULL,
+    exchange TEXT NOT NULL,
+    symbol TEXT NOT NULL,
+    timeframe TEXT NOT NULL,
+    params JSONB,
+    statistic JSONB NOT NULL,
+    completed_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX idx_backtest_results_name ON backtest_results (name, completed_at DESC);
@@ -0,0 +1,8 @@
+CREATE TABLE funding_rates (
+    timestamp       TIMESTAMPTZ NOT NULL,
+    exchange        TEXT NOT NULL,
+    symbol          TEXT NOT NULL,
+    rate            DECIMAL(20,12) NOT NULL,
+
+    PRIMARY KEY (exchange, symbol, timestamp)
+);
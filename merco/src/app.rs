@@ -1,31 +1,150 @@
 use crate::AppResult;
-use crate::services::tasks::{load_backtest_tasks, load_fetch_candles_tasks};
-use crate::tasks::{BacktestTask, FetchCandlesTask};
-use crate::{handlers, strategy::StrategyManager};
+use crate::exchange::ccxt::ExchangeCredentials;
+use crate::handlers::source::SourceConfig;
+use crate::handlers::tasks::RetentionConfig;
+use crate::middleware::request_id;
+use crate::services::tasks::{
+    load_backtest_tasks, load_fetch_all_candles_tasks, load_fetch_candles_tasks,
+    load_optimize_tasks, load_walk_forward_tasks,
+};
+use crate::tasks::{
+    BacktestConfig, BacktestProgress, BacktestTask, FetchAllCandlesTask, FetchCandlesTask,
+    OptimizeTask, TaskSummary, WalkForwardTask,
+};
+use crate::{
+    handlers,
+    strategy::{StrategyConfig, StrategyManager},
+};
 use axum::{
     Router,
-    routing::{get, post},
+    extract::DefaultBodyLimit,
+    middleware,
+    routing::{delete, get, post},
 };
 use sqlx::PgPool;
 use std::collections::HashMap;
-use std::sync::Arc;
-use tokio::sync::{RwLock, broadcast};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{OwnedSemaphorePermit, RwLock, Semaphore, broadcast};
 use tokio_util::sync::CancellationToken;
 use tower_http::cors::{Any, CorsLayer};
+use tracing::Instrument;
 use uuid::Uuid;
 
 #[derive(Debug, Clone)]
 pub struct AppState {
     pub fetch_candles_event_tx: broadcast::Sender<FetchCandlesTask>,
     pub fetch_candles_tasks: Arc<RwLock<HashMap<Uuid, Arc<RwLock<FetchCandlesTask>>>>>,
+    pub fetch_all_candles_event_tx: broadcast::Sender<FetchAllCandlesTask>,
+    pub fetch_all_candles_tasks: Arc<RwLock<HashMap<Uuid, Arc<RwLock<FetchAllCandlesTask>>>>>,
     pub backtest_event_tx: broadcast::Sender<BacktestTask>,
+    pub backtest_progress_tx: broadcast::Sender<BacktestProgress>,
     pub backtest_tasks: Arc<RwLock<HashMap<Uuid, Arc<RwLock<BacktestTask>>>>>,
+    pub optimize_event_tx: broadcast::Sender<OptimizeTask>,
+    pub optimize_tasks: Arc<RwLock<HashMap<Uuid, Arc<RwLock<OptimizeTask>>>>>,
+    pub walk_forward_event_tx: broadcast::Sender<WalkForwardTask>,
+    pub walk_forward_tasks: Arc<RwLock<HashMap<Uuid, Arc<RwLock<WalkForwardTask>>>>>,
+    pub task_removed_tx: broadcast::Sender<Uuid>,
+    /// Last known summary of every task, for listing tasks that are running
+    /// and so hold their write lock.
+    pub task_summaries: Arc<Mutex<HashMap<Uuid, TaskSummary>>>,
+    /// Tokens of the tasks that are pending or running, for cancelling them.
+    pub task_cancellations: Arc<Mutex<HashMap<Uuid, CancellationToken>>>,
     pub strategy_manager: StrategyManager,
+    pub exchange_credentials: Arc<HashMap<String, ExchangeCredentials>>,
+    pub source_config: SourceConfig,
+    pub backtest_config: BacktestConfig,
+    pub task_semaphore: Arc<Semaphore>,
     pub db_pool: PgPool,
     pub shutdown_token: CancellationToken,
 }
 
-pub async fn create_app(db_pool: PgPool, shutdown_token: CancellationToken) -> AppResult<Router> {
+impl AppState {
+    pub fn exchange_credentials(&self, exchange: &str) -> Option<ExchangeCredentials> {
+        self.exchange_credentials
+            .get(exchange)
+            .cloned()
+            .or_else(|| ExchangeCredentials::from_env(exchange))
+    }
+
+    fn register_task(&self, task_id: Uuid) -> CancellationToken {
+        let token = CancellationToken::new();
+        self.task_cancellations
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(task_id, token.clone());
+        token
+    }
+
+    fn unregister_task(&self, task_id: Uuid) {
+        self.task_cancellations
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&task_id);
+    }
+
+    /// Cancels a pending or running task, returning false when there is no
+    /// such task. The task records the cancellation as its failure.
+    pub fn cancel_task(&self, task_id: Uuid) -> bool {
+        let token = self
+            .task_cancellations
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&task_id)
+            .cloned();
+        token.inspect(CancellationToken::cancel).is_some()
+    }
+
+    async fn acquire_task_permit(&self) -> Option<OwnedSemaphorePermit> {
+        tokio::select! {
+            _ = self.shutdown_token.cancelled() => None,
+            permit = self.task_semaphore.clone().acquire_owned() => permit.ok(),
+        }
+    }
+
+    /// Runs a task in the background once a task permit is free, handing it
+    /// the token `cancel_task` triggers. A task cancelled while waiting for a
+    /// permit runs without one, only to record the cancellation; on shutdown
+    /// it is not run at all.
+    pub fn spawn_task<F, Fut>(&self, task_id: Uuid, kind: &'static str, run: F)
+    where
+        F: FnOnce(AppState, CancellationToken) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send,
+    {
+        let cancel = self.register_task(task_id);
+        let state = self.clone();
+        tokio::spawn(
+            async move {
+                let permit = tokio::select! {
+                    permit = state.acquire_task_permit() => permit,
+                    _ = cancel.cancelled() => None,
+                };
+                if permit.is_some() || cancel.is_cancelled() {
+                    run(state.clone(), cancel).await;
+                }
+                state.unregister_task(task_id);
+            }
+            .instrument(tracing::info_span!("task", kind, task_id = %task_id)),
+        );
+    }
+}
+
+/// Settings `create_app` needs from the server configuration.
+#[derive(Debug, Clone)]
+pub struct AppConfig {
+    pub exchange_credentials: HashMap<String, ExchangeCredentials>,
+    pub max_concurrent_tasks: usize,
+    pub source: SourceConfig,
+    pub task_retention: RetentionConfig,
+    pub backtest: BacktestConfig,
+    pub strategy: StrategyConfig,
+}
+
+pub async fn create_app(
+    db_pool: PgPool,
+    config: AppConfig,
+    shutdown_token: CancellationToken,
+) -> AppResult<Router> {
     let (fetch_candles_event_tx, _) = broadcast::channel(1000);
     let mut fetch_candles_tasks = HashMap::new();
     let loaded_fetch_candles_tasks = load_fetch_candles_tasks(&db_pool).await?;
@@ -35,7 +154,17 @@ pub async fn create_app(db_pool: PgPool, shutdown_token: CancellationToken) -> A
         fetch_candles_tasks.insert(task_id, task);
     }
 
+    let (fetch_all_candles_event_tx, _) = broadcast::channel(1000);
+    let mut fetch_all_candles_tasks = HashMap::new();
+    let loaded_fetch_all_candles_tasks = load_fetch_all_candles_tasks(&db_pool).await?;
+    for task in loaded_fetch_all_candles_tasks {
+        let task_id = task.id;
+        let task = Arc::new(RwLock::new(task));
+        fetch_all_candles_tasks.insert(task_id, task);
+    }
+
     let (backtest_event_tx, _) = broadcast::channel(1000);
+    let (backtest_progress_tx, _) = broadcast::channel(1000);
     let mut backtest_tasks = HashMap::new();
     let loaded_backtest_tasks = load_backtest_tasks(&db_pool).await?;
     for task in loaded_backtest_tasks {
@@ -44,18 +173,56 @@ pub async fn create_app(db_pool: PgPool, shutdown_token: CancellationToken) -> A
         backtest_tasks.insert(task_id, task);
     }
 
-    let strategy_manager = StrategyManager::new().expect("Failed to create StrategyManager");
+    let (optimize_event_tx, _) = broadcast::channel(1000);
+    let mut optimize_tasks = HashMap::new();
+    let loaded_optimize_tasks = load_optimize_tasks(&db_pool).await?;
+    for task in loaded_optimize_tasks {
+        let task_id = task.id;
+        let task = Arc::new(RwLock::new(task));
+        optimize_tasks.insert(task_id, task);
+    }
+
+    let (walk_forward_event_tx, _) = broadcast::channel(1000);
+    let mut walk_forward_tasks = HashMap::new();
+    let loaded_walk_forward_tasks = load_walk_forward_tasks(&db_pool).await?;
+    for task in loaded_walk_forward_tasks {
+        let task_id = task.id;
+        let task = Arc::new(RwLock::new(task));
+        walk_forward_tasks.insert(task_id, task);
+    }
+
+    let (task_removed_tx, _) = broadcast::channel(1000);
+
+    let strategy_manager =
+        StrategyManager::new(&config.strategy).expect("Failed to create StrategyManager");
 
     let state = AppState {
         fetch_candles_event_tx,
         fetch_candles_tasks: Arc::new(RwLock::new(fetch_candles_tasks)),
+        fetch_all_candles_event_tx,
+        fetch_all_candles_tasks: Arc::new(RwLock::new(fetch_all_candles_tasks)),
         backtest_event_tx,
+        backtest_progress_tx,
         backtest_tasks: Arc::new(RwLock::new(backtest_tasks)),
+        optimize_event_tx,
+        optimize_tasks: Arc::new(RwLock::new(optimize_tasks)),
+        walk_forward_event_tx,
+        walk_forward_tasks: Arc::new(RwLock::new(walk_forward_tasks)),
+        task_removed_tx,
+        task_summaries: Arc::new(Mutex::new(HashMap::new())),
+        task_cancellations: Arc::new(Mutex::new(HashMap::new())),
         strategy_manager,
+        exchange_credentials: Arc::new(config.exchange_credentials),
+        source_config: config.source,
+        backtest_config: config.backtest,
+        task_semaphore: Arc::new(Semaphore::new(config.max_concurrent_tasks.max(1))),
         db_pool,
         shutdown_token,
     };
 
+    handlers::tasks::spawn_task_sweeper(state.clone(), config.task_retention);
+    handlers::tasks::spawn_summary_cache(state.clone());
+
     let cors = CorsLayer::new()
         .allow_origin(Any)
         .allow_methods(Any)
@@ -63,8 +230,18 @@ pub async fn create_app(db_pool: PgPool, shutdown_token: CancellationToken) -> A
 
     Ok(Router::new()
         .route("/health", get(handlers::info::check))
+        .route("/ready", get(handlers::info::ready))
         .route("/exchanges", get(handlers::info::list_exchanges))
+        .route(
+            "/exchanges/{exchange}/markets",
+            get(handlers::info::list_markets),
+        )
+        .route(
+            "/exchanges/{exchange}/balance",
+            get(handlers::info::get_balance),
+        )
         .route("/symbols", get(handlers::info::list_symbols))
+        .route("/ticker", get(handlers::info::get_ticker))
         .route("/timeframes", get(handlers::info::list_timeframes))
         .route("/tasks/fetch", get(handlers::fetch_candles::get_all_tasks))
         .route("/tasks/fetch", post(handlers::fetch_candles::create_task))
@@ -73,6 +250,22 @@ pub async fn create_app(db_pool: PgPool, shutdown_token: CancellationToken) -> A
             "/tasks/fetch/stream",
             get(handlers::fetch_candles::stream_tasks),
         )
+        .route(
+            "/tasks/fetch_all",
+            get(handlers::fetch_all_candles::get_all_tasks),
+        )
+        .route(
+            "/tasks/fetch_all",
+            post(handlers::fetch_all_candles::create_task),
+        )
+        .route(
+            "/tasks/fetch_all/{id}",
+            get(handlers::fetch_all_candles::get_task),
+        )
+        .route(
+            "/tasks/fetch_all/stream",
+            get(handlers::fetch_all_candles::stream_tasks),
+        )
         .route("/tasks/backtest", get(handlers::backtest::get_all_tasks))
         .route("/tasks/backtest", post(handlers::backtest::create_task))
         .route("/tasks/backtest/{id}", get(handlers::backtest::get_task))
@@ -80,20 +273,92 @@ pub async fn create_app(db_pool: PgPool, shutdown_token: CancellationToken) -> A
             "/tasks/backtest/stream",
             get(handlers::backtest::stream_tasks),
         )
+        .route(
+            "/tasks/backtest/{id}/stream",
+            get(handlers::backtest::stream_task),
+        )
+        .route(
+            "/tasks/backtest/{id}/export",
+            get(handlers::backtest::export_task),
+        )
+        .route("/tasks/optimize", get(handlers::optimize::get_all_tasks))
+        .route("/tasks/optimize", post(handlers::optimize::create_task))
+        .route("/tasks/optimize/{id}", get(handlers::optimize::get_task))
+        .route(
+            "/tasks/optimize/stream",
+            get(handlers::optimize::stream_tasks),
+        )
+        .route(
+            "/tasks/walk_forward",
+            get(handlers::walk_forward::get_all_tasks),
+        )
+        .route(
+            "/tasks/walk_forward",
+            post(handlers::walk_forward::create_task),
+        )
+        .route(
+            "/tasks/walk_forward/{id}",
+            get(handlers::walk_forward::get_task),
+        )
+        .route(
+            "/tasks/walk_forward/stream",
+            get(handlers::walk_forward::stream_tasks),
+        )
+        .route("/tasks", get(handlers::tasks::list_tasks))
+        .route("/tasks/ws", get(handlers::tasks::ws_tasks))
+        .route("/tasks/completed", delete(handlers::tasks::clear_completed))
+        .route("/tasks/{id}", delete(handlers::tasks::delete_task))
         .route("/candles", get(handlers::candles::get_candles))
+        .route("/candles", delete(handlers::candles::delete_candles))
+        .route("/candles/export", get(handlers::candles::export_candles))
+        .route("/candles/latest", get(handlers::candles::latest_candle))
+        .route("/candles/verify", get(handlers::candles::verify_candles))
+        .route(
+            "/candles/update",
+            post(handlers::fetch_candles::update_candles),
+        )
         .route(
             "/candles/available",
             get(handlers::candles::available_candles),
         )
         .route("/strategy/list", get(handlers::strategy::list_strategies))
+        .route(
+            "/strategy/backtest/history",
+            get(handlers::backtest::get_history),
+        )
+        .route(
+            "/strategy/{name}/meta",
+            get(handlers::strategy::strategy_metadata),
+        )
         .route("/strategy/add", post(handlers::strategy::add_strategy))
+        .route(
+            "/strategy/validate",
+            post(handlers::strategy::validate_strategy),
+        )
+        .route(
+            "/strategy/remove",
+            post(handlers::strategy::remove_strategy),
+        )
         .route("/strategy/source/get", get(handlers::source::get_source))
-        .route("/strategy/source/save", post(handlers::source::save_source))
+        .route(
+            "/strategy/source/save",
+            post(handlers::source::save_source)
+                .layer(DefaultBodyLimit::max(state.source_config.max_file_size)),
+        )
+        .route(
+            "/strategy/source/create",
+            post(handlers::source::create_source),
+        )
         .route(
             "/strategy/source/delete",
             get(handlers::source::delete_source),
         )
         .route("/strategy/source/move", get(handlers::source::move_source))
+        .route(
+            "/strategy/source/search",
+            get(handlers::source::search_source),
+        )
+        .layer(middleware::from_fn(request_id))
         .layer(cors)
         .with_state(state))
 }
@@ -1,3 +1,4 @@
+use crate::middleware::current_request_id;
 use axum::{
     Json,
     http::StatusCode,
@@ -8,12 +9,15 @@ use sqlx::migrate::MigrateError;
 use thiserror::Error;
 use toml_edit::TomlError;
 use ts_rs::TS;
+use uuid::Uuid;
 
 #[derive(Debug, Serialize, Deserialize, TS)]
 #[ts(export)]
 pub struct ErrorResponse {
     pub error: String,
     pub message: String,
+    #[ts(optional)]
+    pub request_id: Option<Uuid>,
 }
 
 #[derive(Debug, Error)]
@@ -36,8 +40,17 @@ pub enum AppError {
     #[error("Strategy Error: {0}")]
     Strategy(String),
 
+    #[error("Build Error: {0}")]
+    Build(String),
+
+    #[error("Order Rejected: {0}")]
+    OrderRejected(String),
+
     #[error("Internal Error: {0}")]
     Internal(String),
+
+    #[error("Task was cancelled")]
+    Cancelled,
 }
 
 impl IntoResponse for AppError {
@@ -101,6 +114,28 @@ impl IntoResponse for AppError {
                 );
                 (StatusCode::INTERNAL_SERVER_ERROR, "Strategy", msg)
             }
+            AppError::Build(msg) => {
+                tracing::warn!(
+                    error_type = %"Build",
+                    status_code = %StatusCode::UNPROCESSABLE_ENTITY,
+                    message = %msg,
+                    "Strategy build failed"
+                );
+                (StatusCode::UNPROCESSABLE_ENTITY, "Build", msg.clone())
+            }
+            AppError::OrderRejected(msg) => {
+                tracing::warn!(
+                    error_type = %"OrderRejected",
+                    status_code = %StatusCode::UNPROCESSABLE_ENTITY,
+                    message = %msg,
+                    "Order rejected"
+                );
+                (
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    "OrderRejected",
+                    msg.clone(),
+                )
+            }
             AppError::Internal(msg) => {
                 tracing::error!(
                     error_type = %"Internal",
@@ -110,11 +145,22 @@ impl IntoResponse for AppError {
                 );
                 (StatusCode::INTERNAL_SERVER_ERROR, "Internal", msg.clone())
             }
+            AppError::Cancelled => {
+                let msg = self.to_string();
+                tracing::warn!(
+                    error_type = %"Cancelled",
+                    status_code = %StatusCode::CONFLICT,
+                    message = %msg,
+                    "Task cancelled"
+                );
+                (StatusCode::CONFLICT, "Cancelled", msg)
+            }
         };
 
         let body = Json(ErrorResponse {
             error: error_type.to_string(),
             message,
+            request_id: current_request_id(),
         });
         (status, body).into_response()
     }
@@ -1,19 +1,108 @@
 use crate::errors::{AppError, AppResult};
-use crate::models::{Candle, MarketPrecision, Timeframe, TradingFees};
-use crate::utils::str_to_bigdecimal;
-use chrono::{TimeZone, Utc};
-use pyo3::types::PyList;
+use crate::models::{
+    Balance, Candle, FeeRounding, FundingRate, MarketInfo, MarketLimits, MarketPrecision,
+    OrderBook, OrderBookLevel, Ticker, Timeframe, TradingFees,
+};
+use crate::utils::{edit_distance, str_to_bigdecimal};
+use bigdecimal::{BigDecimal, Zero};
+use chrono::{DateTime, TimeDelta, TimeZone, Utc};
+use futures::Stream;
+use pyo3::types::{PyList, PyType};
 use pyo3::{prelude::*, types::PyDict};
-use std::str::FromStr;
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    fmt,
+    str::FromStr,
+    sync::{Arc, LazyLock, Mutex, OnceLock},
+    time::{Duration, Instant},
+};
+use tokio::sync::mpsc;
 
-#[derive(Debug)]
+static RETRY_CONFIG: OnceLock<RetryConfig> = OnceLock::new();
+static CANDLE_VALIDATION: OnceLock<CandleValidation> = OnceLock::new();
+static CACHE_CONFIG: OnceLock<CacheConfig> = OnceLock::new();
+static INSTANCES: LazyLock<Mutex<HashMap<String, (Instant, CCXT)>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RetryConfig {
+    pub max_retries: u32,
+    pub base_delay_ms: u64,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay_ms: 500,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CacheConfig {
+    pub ttl_secs: u64,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self { ttl_secs: 300 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CandleValidation {
+    Strict,
+    #[default]
+    Lenient,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct ExchangeCredentials {
+    pub api_key: String,
+    pub secret: String,
+    #[serde(default)]
+    pub password: Option<String>,
+}
+
+impl ExchangeCredentials {
+    pub fn from_env(exchange: &str) -> Option<Self> {
+        let prefix = format!("MERCO_{}", exchange.to_uppercase());
+        let api_key = std::env::var(format!("{}_API_KEY", prefix)).ok()?;
+        let secret = std::env::var(format!("{}_SECRET", prefix)).ok()?;
+        let password = std::env::var(format!("{}_PASSWORD", prefix)).ok();
+
+        Some(Self {
+            api_key,
+            secret,
+            password,
+        })
+    }
+}
+
+impl fmt::Debug for ExchangeCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExchangeCredentials")
+            .field("api_key", &"<redacted>")
+            .field("secret", &"<redacted>")
+            .field("password", &self.password.as_ref().map(|_| "<redacted>"))
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct CCXT {
     exchange_name: String,
-    instance: Py<PyAny>,
+    instance: Arc<Py<PyAny>>,
+    credentials: Option<Arc<ExchangeCredentials>>,
 }
 
 impl CCXT {
     const MODULE_NAME: &str = "ccxt";
+    const PRO_MODULE_NAME: &str = "ccxt.pro";
+    const MIN_POLL_DELAY: Duration = Duration::from_secs(1);
     const AVAILABLE_EXCHANGES: [&str; 42] = [
         "apex",
         "ascendex",
@@ -59,6 +148,205 @@ impl CCXT {
         "zonda",
     ];
 
+    pub fn set_retry_config(config: RetryConfig) {
+        let _ = RETRY_CONFIG.set(config);
+    }
+
+    fn retry_config() -> &'static RetryConfig {
+        RETRY_CONFIG.get_or_init(RetryConfig::default)
+    }
+
+    pub fn set_candle_validation(validation: CandleValidation) {
+        let _ = CANDLE_VALIDATION.set(validation);
+    }
+
+    fn candle_validation() -> CandleValidation {
+        *CANDLE_VALIDATION.get_or_init(CandleValidation::default)
+    }
+
+    pub fn set_cache_config(config: CacheConfig) {
+        let _ = CACHE_CONFIG.set(config);
+    }
+
+    fn cache_ttl() -> Duration {
+        Duration::from_secs(CACHE_CONFIG.get_or_init(CacheConfig::default).ttl_secs)
+    }
+
+    fn with_retry<T>(
+        &self,
+        py: Python<'_>,
+        operation: &str,
+        mut f: impl FnMut() -> PyResult<T>,
+    ) -> PyResult<T> {
+        let config = Self::retry_config();
+        let ccxt = py.import(Self::MODULE_NAME)?;
+        let network_error = ccxt.getattr("NetworkError")?;
+        let network_error = network_error.cast::<PyType>()?;
+
+        let mut attempt = 0;
+        loop {
+            match f() {
+                Ok(value) => return Ok(value),
+                Err(err) if attempt < config.max_retries && err.is_instance(py, network_error) => {
+                    let delay = Duration::from_millis(
+                        config
+                            .base_delay_ms
+                            .saturating_mul(2u64.saturating_pow(attempt)),
+                    );
+                    attempt += 1;
+                    tracing::warn!(
+                        "{} on {} failed (attempt {}/{}): {}, retrying in {:?}",
+                        operation,
+                        self.exchange_name,
+                        attempt,
+                        config.max_retries,
+                        err,
+                        delay
+                    );
+                    py.detach(|| std::thread::sleep(delay));
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    async fn spawn_blocking<T, F>(f: F) -> AppResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> AppResult<T> + Send + 'static,
+    {
+        tokio::task::spawn_blocking(f)
+            .await
+            .map_err(|e| AppError::Internal(format!("CCXT blocking task failed: {}", e)))?
+    }
+
+    pub async fn check_available_async() -> AppResult<()> {
+        Self::spawn_blocking(Self::check_available).await
+    }
+
+    pub async fn exchanges_async() -> AppResult<Vec<String>> {
+        Self::spawn_blocking(Self::exchanges).await
+    }
+
+    pub async fn with_exchange_async(exchange: &str) -> AppResult<Self> {
+        let exchange = exchange.to_string();
+        Self::spawn_blocking(move || Self::with_exchange(&exchange)).await
+    }
+
+    pub async fn with_credentials_async(
+        exchange: &str,
+        credentials: ExchangeCredentials,
+    ) -> AppResult<Self> {
+        let exchange = exchange.to_string();
+        Self::spawn_blocking(move || Self::with_credentials(&exchange, &credentials)).await
+    }
+
+    pub async fn symbols_async(&self) -> AppResult<Vec<String>> {
+        let client = self.clone();
+        Self::spawn_blocking(move || client.symbols()).await
+    }
+
+    pub async fn timeframes_async(&self) -> AppResult<Vec<Timeframe>> {
+        let client = self.clone();
+        Self::spawn_blocking(move || client.timeframes()).await
+    }
+
+    pub async fn rate_limit_ms_async(&self) -> AppResult<u64> {
+        let client = self.clone();
+        Self::spawn_blocking(move || client.rate_limit_ms()).await
+    }
+
+    pub async fn max_candles_limit_async(&self, symbol: &str) -> AppResult<Option<u64>> {
+        let client = self.clone();
+        let symbol = symbol.to_string();
+        Self::spawn_blocking(move || client.max_candles_limit(&symbol)).await
+    }
+
+    pub async fn fees_async(&self, symbol: &str) -> AppResult<TradingFees> {
+        let client = self.clone();
+        let symbol = symbol.to_string();
+        Self::spawn_blocking(move || client.fees(&symbol)).await
+    }
+
+    pub async fn precision_async(&self, symbol: &str) -> AppResult<MarketPrecision> {
+        let client = self.clone();
+        let symbol = symbol.to_string();
+        Self::spawn_blocking(move || client.precision(&symbol)).await
+    }
+
+    pub async fn limits_async(&self, symbol: &str) -> AppResult<MarketLimits> {
+        let client = self.clone();
+        let symbol = symbol.to_string();
+        Self::spawn_blocking(move || client.limits(&symbol)).await
+    }
+
+    pub async fn markets_async(&self, symbol: Option<&str>) -> AppResult<Vec<MarketInfo>> {
+        let client = self.clone();
+        let symbol = symbol.map(str::to_string);
+        Self::spawn_blocking(move || client.markets(symbol.as_deref())).await
+    }
+
+    pub async fn fetch_order_book_async(
+        &self,
+        symbol: &str,
+        limit: Option<i64>,
+    ) -> AppResult<OrderBook> {
+        let client = self.clone();
+        let symbol = symbol.to_string();
+        Self::spawn_blocking(move || client.fetch_order_book(&symbol, limit)).await
+    }
+
+    pub async fn fetch_balance_async(&self) -> AppResult<Vec<Balance>> {
+        let client = self.clone();
+        Self::spawn_blocking(move || client.fetch_balance()).await
+    }
+
+    pub async fn fetch_ticker_async(&self, symbol: &str) -> AppResult<Ticker> {
+        let client = self.clone();
+        let symbol = symbol.to_string();
+        Self::spawn_blocking(move || client.fetch_ticker(&symbol)).await
+    }
+
+    pub async fn fetch_candles_async(
+        &self,
+        symbol: &str,
+        timeframe: Timeframe,
+        since: Option<i64>,
+        limit: Option<i64>,
+    ) -> AppResult<(Vec<Candle>, u64)> {
+        let client = self.clone();
+        let symbol = symbol.to_string();
+        Self::spawn_blocking(move || client.fetch_candles(&symbol, timeframe, since, limit)).await
+    }
+
+    pub async fn fetch_funding_rates_async(
+        &self,
+        symbol: &str,
+        since: Option<i64>,
+        limit: Option<i64>,
+    ) -> AppResult<Vec<FundingRate>> {
+        let client = self.clone();
+        let symbol = symbol.to_string();
+        Self::spawn_blocking(move || client.fetch_funding_rates(&symbol, since, limit)).await
+    }
+
+    pub async fn first_candle_async(
+        &self,
+        symbol: &str,
+        timeframe: Timeframe,
+    ) -> AppResult<Option<Candle>> {
+        let client = self.clone();
+        let symbol = symbol.to_string();
+        Self::spawn_blocking(move || client.first_candle(&symbol, timeframe)).await
+    }
+
+    pub fn check_available() -> AppResult<()> {
+        Python::attach(|py| {
+            py.import(Self::MODULE_NAME)?;
+            Ok(())
+        })
+    }
+
     pub fn exchanges() -> AppResult<Vec<String>> {
         Python::attach(|py| {
             let ccxt = py.import(Self::MODULE_NAME)?;
@@ -74,27 +362,105 @@ impl CCXT {
         })
     }
 
+    // Unauthenticated instances are shared between requests until the TTL
+    // expires, so markets are not reloaded each time. The Python object is
+    // only touched while holding the GIL, which serializes concurrent use.
     pub fn with_exchange(exchange: &str) -> AppResult<Self> {
-        if !Self::AVAILABLE_EXCHANGES.contains(&exchange) {
-            return Err(AppError::BadRequest(format!(
-                "Invalid exchange: {}",
-                exchange
+        let ttl = Self::cache_ttl();
+        if ttl.is_zero() {
+            return Self::create(exchange, None);
+        }
+
+        let cached = INSTANCES
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(exchange)
+            .filter(|(loaded_at, _)| loaded_at.elapsed() < ttl)
+            .map(|(_, client)| client.clone());
+        if let Some(client) = cached {
+            return Ok(client);
+        }
+
+        let client = Self::create(exchange, None)?;
+        INSTANCES
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(exchange.to_string(), (Instant::now(), client.clone()));
+
+        Ok(client)
+    }
+
+    fn unknown_exchange_message(exchange: &str, exchanges: &[String]) -> String {
+        let input = exchange.to_lowercase();
+        let suggestion = exchanges
+            .iter()
+            .map(|name| (edit_distance(&input, name), name))
+            .filter(|(distance, name)| *distance <= name.len().div_ceil(3))
+            .min_by_key(|(distance, _)| *distance);
+
+        match suggestion {
+            Some((_, name)) => format!("Unknown exchange '{}'; did you mean '{}'?", exchange, name),
+            None => format!("Unknown exchange '{}'", exchange),
+        }
+    }
+
+    // Authenticated instances are never cached, so one account's client is
+    // not handed to another request.
+    pub fn with_credentials(exchange: &str, credentials: &ExchangeCredentials) -> AppResult<Self> {
+        Self::create(exchange, Some(credentials))
+    }
+
+    fn create(exchange: &str, credentials: Option<&ExchangeCredentials>) -> AppResult<Self> {
+        let exchanges = Self::exchanges()?;
+        if !exchanges.iter().any(|name| name == exchange) {
+            return Err(AppError::BadRequest(Self::unknown_exchange_message(
+                exchange, &exchanges,
             )));
         }
 
         Python::attach(|py| {
             let ccxt = py.import(Self::MODULE_NAME)?;
             let exchange_class = ccxt.getattr(exchange)?;
-            let exchange_instance = exchange_class.call0()?;
-            exchange_instance.call_method0("load_markets")?;
 
-            Ok(Self {
+            let credentials = credentials.cloned().map(Arc::new);
+            let exchange_config = Self::exchange_config(py, credentials.as_deref())?;
+            let exchange_instance = exchange_class.call1((exchange_config,))?;
+
+            let client = Self {
                 exchange_name: exchange.to_string(),
-                instance: exchange_instance.unbind(),
-            })
+                instance: Arc::new(exchange_instance.clone().unbind()),
+                credentials,
+            };
+
+            client.with_retry(py, "load_markets", || {
+                exchange_instance.call_method0("load_markets")
+            })?;
+
+            Ok(client)
         })
     }
 
+    // The constructor options shared by the ccxt instance and its ccxt.pro
+    // counterpart, so both talk to the exchange the same way.
+    fn exchange_config<'py>(
+        py: Python<'py>,
+        credentials: Option<&ExchangeCredentials>,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        let exchange_config = PyDict::new(py);
+        if let Some(credentials) = credentials {
+            exchange_config.set_item("apiKey", &credentials.api_key)?;
+            exchange_config.set_item("secret", &credentials.secret)?;
+            if let Some(password) = &credentials.password {
+                exchange_config.set_item("password", password)?;
+            }
+        }
+        Ok(exchange_config)
+    }
+
+    pub fn is_authenticated(&self) -> bool {
+        self.credentials.is_some()
+    }
+
     pub fn symbols(&self) -> AppResult<Vec<String>> {
         Python::attach(|py| {
             let exchange = self.instance.bind(py);
@@ -118,17 +484,91 @@ impl CCXT {
         })
     }
 
+    pub fn rate_limit_ms(&self) -> AppResult<u64> {
+        Python::attach(|py| {
+            let exchange = self.instance.bind(py);
+            let rate_limit: f64 = exchange.getattr("rateLimit")?.extract()?;
+            Ok(rate_limit.max(0.0).ceil() as u64)
+        })
+    }
+
+    /// Largest page size `fetch_ohlcv` accepts for the symbol's market type, if
+    /// the exchange documents one in its `features`.
+    pub fn max_candles_limit(&self, symbol: &str) -> AppResult<Option<u64>> {
+        Python::attach(|py| {
+            let exchange = self.instance.bind(py);
+            let Some(features) = exchange
+                .getattr("features")
+                .ok()
+                .filter(|features| !features.is_none())
+            else {
+                return Ok(None);
+            };
+
+            let market = exchange.getattr("markets")?.get_item(symbol)?;
+            let market_type: String = Self::optional_item(&market, "type")
+                .map(|value| value.extract())
+                .transpose()?
+                .unwrap_or_else(|| "spot".to_string());
+            let mut market_features = Self::optional_item(&features, &market_type);
+            if market_type != "spot" {
+                let contract = if Self::optional_item(&market, "inverse")
+                    .and_then(|value| value.extract::<bool>().ok())
+                    .unwrap_or(false)
+                {
+                    "inverse"
+                } else {
+                    "linear"
+                };
+                market_features =
+                    market_features.and_then(|features| Self::optional_item(&features, contract));
+            }
+
+            let limit = market_features
+                .or_else(|| Self::optional_item(&features, "spot"))
+                .and_then(|features| Self::optional_item(&features, "fetchOHLCV"))
+                .and_then(|fetch_ohlcv| Self::optional_item(&fetch_ohlcv, "limit"))
+                .and_then(|limit| limit.extract::<u64>().ok());
+
+            Ok(limit)
+        })
+    }
+
     pub fn fees(&self, symbol: &str) -> AppResult<TradingFees> {
         Python::attach(|py| {
             let exchange = self.instance.bind(py);
             let markets = exchange.getattr("markets")?;
             let market = markets.get_item(symbol)?;
+            let trading_fees = exchange
+                .getattr("fees")
+                .and_then(|fees| fees.get_item("trading"))
+                .ok();
 
-            let maker: String = market.get_item("maker")?.str()?.extract()?;
-            let taker: String = market.get_item("taker")?.str()?.extract()?;
+            let fee = |key: &str| -> AppResult<BigDecimal> {
+                let value = Self::optional_item(&market, key).or_else(|| {
+                    trading_fees
+                        .as_ref()
+                        .and_then(|trading| Self::optional_item(trading, key))
+                });
+                match value {
+                    Some(value) => {
+                        let value: String = value.str()?.extract()?;
+                        str_to_bigdecimal(&value, &format!("{} fee", key))
+                    }
+                    None => {
+                        tracing::warn!(
+                            "Exchange {} has no {} fee for {}, defaulting to zero",
+                            self.exchange_name,
+                            key,
+                            symbol
+                        );
+                        Ok(BigDecimal::zero())
+                    }
+                }
+            };
 
-            let maker = str_to_bigdecimal(&maker, "maker fee")?;
-            let taker = str_to_bigdecimal(&taker, "taker fee")?;
+            let maker = fee("maker")?;
+            let taker = fee("taker")?;
 
             Ok(TradingFees { maker, taker })
         })
@@ -139,79 +579,585 @@ impl CCXT {
             let exchange = self.instance.bind(py);
             let markets = exchange.getattr("markets")?;
             let market = markets.get_item(symbol)?;
-            let precision = market.get_item("precision")?;
+            let precision = Self::optional_item(&market, "precision");
+
+            let value = |key: &str| -> AppResult<BigDecimal> {
+                match precision
+                    .as_ref()
+                    .and_then(|precision| Self::optional_item(precision, key))
+                {
+                    Some(value) => {
+                        let value: String = value.str()?.extract()?;
+                        str_to_bigdecimal(&value, &format!("{} precision", key))
+                    }
+                    None => {
+                        tracing::warn!(
+                            "Exchange {} has no {} precision for {}, rounding disabled",
+                            self.exchange_name,
+                            key,
+                            symbol
+                        );
+                        Ok(BigDecimal::zero())
+                    }
+                }
+            };
 
-            let price_value: String = precision.get_item("price")?.str()?.extract()?;
-            let price_precision = str_to_bigdecimal(&price_value, "price precision")?;
+            let price_precision = value("price")?;
+            let amount_precision = value("amount")?;
 
-            let amount_value: String = precision.get_item("amount")?.str()?.extract()?;
-            let amount_precision = str_to_bigdecimal(&amount_value, "amount precision")?;
+            // Fees are charged in the quote currency, so prefer the market's
+            // cost precision and fall back to the quote currency's own.
+            let quote_precision = || {
+                let quote: String = Self::optional_item(&market, "quote")?.extract().ok()?;
+                let currencies = exchange.getattr("currencies").ok()?;
+                let currency = Self::optional_item(&currencies, &quote)?;
+                Self::optional_item(&currency, "precision")
+            };
+            let fee_precision = match precision
+                .as_ref()
+                .and_then(|precision| Self::optional_item(precision, "cost"))
+                .or_else(quote_precision)
+            {
+                Some(value) => {
+                    let value: String = value.str()?.extract()?;
+                    str_to_bigdecimal(&value, "fee precision")?
+                }
+                None => BigDecimal::zero(),
+            };
 
             Ok(MarketPrecision {
                 price_precision,
                 amount_precision,
+                fee_precision,
+                fee_rounding: FeeRounding::default(),
+            })
+        })
+    }
+
+    pub fn limits(&self, symbol: &str) -> AppResult<MarketLimits> {
+        Python::attach(|py| {
+            let exchange = self.instance.bind(py);
+            let markets = exchange.getattr("markets")?;
+            let market = markets.get_item(symbol)?;
+            let limits = Self::optional_item(&market, "limits");
+
+            let min = |key: &str| -> AppResult<Option<BigDecimal>> {
+                let value = limits
+                    .as_ref()
+                    .and_then(|limits| Self::optional_item(limits, key))
+                    .and_then(|limit| Self::optional_item(&limit, "min"));
+                match value {
+                    Some(value) => {
+                        let value: String = value.str()?.extract()?;
+                        Ok(Some(str_to_bigdecimal(
+                            &value,
+                            &format!("minimum {}", key),
+                        )?))
+                    }
+                    None => Ok(None),
+                }
+            };
+
+            Ok(MarketLimits {
+                min_amount: min("amount")?,
+                min_price: min("price")?,
+                min_cost: min("cost")?,
             })
         })
     }
 
+    pub fn markets(&self, symbol: Option<&str>) -> AppResult<Vec<MarketInfo>> {
+        let symbols = match symbol {
+            Some(symbol) => vec![symbol.to_string()],
+            None => self.symbols()?,
+        };
+
+        symbols
+            .into_iter()
+            .map(|symbol| {
+                Ok(MarketInfo {
+                    precision: self.precision(&symbol)?,
+                    fees: self.fees(&symbol)?,
+                    limits: self.limits(&symbol)?,
+                    symbol,
+                })
+            })
+            .collect()
+    }
+
+    fn optional_item<'py>(object: &Bound<'py, PyAny>, key: &str) -> Option<Bound<'py, PyAny>> {
+        object.get_item(key).ok().filter(|value| !value.is_none())
+    }
+
+    /// Fetches a page of candles along with the number of invalid candles
+    /// dropped from it.
     pub fn fetch_candles(
         &self,
         symbol: &str,
         timeframe: Timeframe,
         since: Option<i64>,
         limit: Option<i64>,
-    ) -> AppResult<Vec<Candle>> {
+    ) -> AppResult<(Vec<Candle>, u64)> {
         Python::attach(|py| {
             let exchange = self.instance.bind(py);
             let args = (symbol, timeframe.to_string(), since, limit);
 
-            let candles_any = exchange.call_method("fetch_ohlcv", args, None)?;
-            let candles_list = candles_any
+            let candles_any = self.with_retry(py, "fetch_ohlcv", || {
+                exchange.call_method1("fetch_ohlcv", args.clone())
+            })?;
+            let candles = self.parse_candles(symbol, timeframe, candles_any)?;
+            Self::validate_candles(symbol, timeframe, candles)
+        })
+    }
+
+    fn parse_candles(
+        &self,
+        symbol: &str,
+        timeframe: Timeframe,
+        candles_any: Bound<'_, PyAny>,
+    ) -> AppResult<Vec<Candle>> {
+        let candles_list = candles_any
+            .cast_into::<PyList>()
+            .map_err(|e| format!("Failed to cast candles to PyList: {}", e))?;
+
+        let mut candles = Vec::new();
+        for item in candles_list.iter() {
+            let candle_list = item
                 .cast_into::<PyList>()
-                .map_err(|e| format!("Failed to cast candles to PyList: {}", e))?;
+                .map_err(|e| format!("Failed to cast candle to PyList: {}", e))?;
 
-            let mut candles = Vec::new();
-            for item in candles_list.iter() {
-                let candle_list = item
-                    .cast_into::<PyList>()
-                    .map_err(|e| format!("Failed to cast candle to PyList: {}", e))?;
+            let timestamp_ms: i64 = candle_list.get_item(0)?.extract()?;
+            let Some(timestamp) = Utc.timestamp_millis_opt(timestamp_ms).single() else {
+                return Err(format!("Error while parse timestamp: {}", timestamp_ms).into());
+            };
+
+            let open: String = candle_list.get_item(1)?.str()?.extract()?;
+            let high: String = candle_list.get_item(2)?.str()?.extract()?;
+            let low: String = candle_list.get_item(3)?.str()?.extract()?;
+            let close: String = candle_list.get_item(4)?.str()?.extract()?;
+            let volume: String = candle_list.get_item(5)?.str()?.extract()?;
+
+            candles.push(Candle {
+                timestamp,
+                exchange: self.exchange_name.clone(),
+                symbol: symbol.to_string(),
+                timeframe,
+                open: str_to_bigdecimal(&open, "open price")?,
+                high: str_to_bigdecimal(&high, "high price")?,
+                low: str_to_bigdecimal(&low, "low price")?,
+                close: str_to_bigdecimal(&close, "close price")?,
+                volume: str_to_bigdecimal(&volume, "volume")?,
+            });
+        }
+
+        Ok(candles)
+    }
+
+    /// Streams candles of `symbol` as they close, starting with the one forming
+    /// now. Exchanges whose ccxt.pro class supports `watchOHLCV` are followed
+    /// over their websocket, others are polled with `fetch_ohlcv` once each
+    /// candle should have closed. The stream ends after the first error.
+    pub fn watch_candles(
+        &self,
+        symbol: &str,
+        timeframe: Timeframe,
+    ) -> impl Stream<Item = AppResult<Candle>> + use<> {
+        let (tx, mut rx) = mpsc::channel(16);
+        let client = self.clone();
+        let symbol = symbol.to_string();
+        std::thread::spawn(move || {
+            let start = timeframe.bucket_start(Utc::now());
+            let result = match client.pro_instance() {
+                Ok(Some(pro)) => client.watch_websocket(&pro, &symbol, timeframe, start, &tx),
+                Ok(None) => client.watch_polling(&symbol, timeframe, start, &tx),
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                let _ = tx.blocking_send(Err(e));
+            }
+        });
+
+        async_stream::stream! {
+            while let Some(candle) = rx.recv().await {
+                yield candle;
+            }
+        }
+    }
+
+    /// The ccxt.pro counterpart of this exchange with its own asyncio event
+    /// loop, if ccxt.pro is installed and the exchange supports `watchOHLCV`.
+    fn pro_instance(&self) -> AppResult<Option<(Py<PyAny>, Py<PyAny>)>> {
+        Python::attach(|py| {
+            let Ok(pro) = py.import(Self::PRO_MODULE_NAME) else {
+                return Ok(None);
+            };
+            let Ok(exchange_class) = pro.getattr(self.exchange_name.as_str()) else {
+                return Ok(None);
+            };
+
+            let exchange_config = Self::exchange_config(py, self.credentials.as_deref())?;
+            let exchange = exchange_class.call1((exchange_config,))?;
+            // `has` values are true, false, None or "emulated".
+            let supported = exchange
+                .getattr("has")?
+                .get_item("watchOHLCV")
+                .and_then(|value| value.is_truthy())
+                .unwrap_or(false);
+            if !supported {
+                return Ok(None);
+            }
+
+            let event_loop = py.import("asyncio")?.call_method0("new_event_loop")?;
+            Ok(Some((exchange.unbind(), event_loop.unbind())))
+        })
+    }
+
+    fn watch_websocket(
+        &self,
+        (exchange, event_loop): &(Py<PyAny>, Py<PyAny>),
+        symbol: &str,
+        timeframe: Timeframe,
+        start: DateTime<Utc>,
+        tx: &mpsc::Sender<AppResult<Candle>>,
+    ) -> AppResult<()> {
+        let result = (|| {
+            let mut next_open = start;
+            while !tx.is_closed() {
+                // watch_ohlcv resolves on every update with the cached candles,
+                // so a candle has closed once a later one shows up.
+                let candles = Python::attach(|py| {
+                    let coroutine = exchange
+                        .bind(py)
+                        .call_method1("watch_ohlcv", (symbol, timeframe.to_string()))?;
+                    let candles_any = event_loop
+                        .bind(py)
+                        .call_method1("run_until_complete", (coroutine,))?;
+                    self.parse_candles(symbol, timeframe, candles_any)
+                })?;
+                let Some(forming) = candles.last().map(|candle| candle.timestamp) else {
+                    continue;
+                };
+
+                let closed: Vec<Candle> = candles
+                    .into_iter()
+                    .filter(|candle| candle.timestamp >= next_open && candle.timestamp < forming)
+                    .collect();
+                let (closed, _) = Self::validate_candles(symbol, timeframe, closed)?;
+                for candle in closed {
+                    if tx.blocking_send(Ok(candle)).is_err() {
+                        return Ok(());
+                    }
+                }
+                next_open = next_open.max(forming);
+            }
+            Ok(())
+        })();
+
+        let _ = Python::attach(|py| -> PyResult<()> {
+            let coroutine = exchange.bind(py).call_method0("close")?;
+            let event_loop = event_loop.bind(py);
+            event_loop.call_method1("run_until_complete", (coroutine,))?;
+            event_loop.call_method0("close")?;
+            Ok(())
+        });
+
+        result
+    }
+
+    fn watch_polling(
+        &self,
+        symbol: &str,
+        timeframe: Timeframe,
+        start: DateTime<Utc>,
+        tx: &mpsc::Sender<AppResult<Candle>>,
+    ) -> AppResult<()> {
+        let retry_delay = Duration::from_millis(self.rate_limit_ms()?).max(Self::MIN_POLL_DELAY);
+        let mut next_open = start;
+        while !tx.is_closed() {
+            let close_time = timeframe.advance(next_open);
+            let wait = (close_time - Utc::now()).to_std().unwrap_or_default();
+            std::thread::sleep(wait + retry_delay);
+
+            let since = Some(next_open.timestamp_millis());
+            let (candles, _) = self.fetch_candles(symbol, timeframe, since, None)?;
+            let now = Utc::now();
+            for candle in candles {
+                if candle.timestamp < next_open || timeframe.advance(candle.timestamp) > now {
+                    continue;
+                }
+
+                next_open = timeframe.advance(candle.timestamp);
+                if tx.blocking_send(Ok(candle)).is_err() {
+                    return Ok(());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn fetch_funding_rates(
+        &self,
+        symbol: &str,
+        since: Option<i64>,
+        limit: Option<i64>,
+    ) -> AppResult<Vec<FundingRate>> {
+        Python::attach(|py| {
+            let exchange = self.instance.bind(py);
+            let args = (symbol, since, limit);
 
-                let timestamp_ms: i64 = candle_list.get_item(0)?.extract()?;
+            let history = self.with_retry(py, "fetch_funding_rate_history", || {
+                exchange.call_method1("fetch_funding_rate_history", args)
+            })?;
+            let history_list = history
+                .cast_into::<PyList>()
+                .map_err(|e| format!("Failed to cast funding rates to PyList: {}", e))?;
+
+            let mut rates = Vec::new();
+            for item in history_list.iter() {
+                let timestamp_ms: i64 = item.get_item("timestamp")?.extract()?;
                 let Some(timestamp) = Utc.timestamp_millis_opt(timestamp_ms).single() else {
                     return Err(format!("Error while parse timestamp: {}", timestamp_ms).into());
                 };
+                let rate: String = item.get_item("fundingRate")?.str()?.extract()?;
 
-                let open: String = candle_list.get_item(1)?.str()?.extract()?;
-                let high: String = candle_list.get_item(2)?.str()?.extract()?;
-                let low: String = candle_list.get_item(3)?.str()?.extract()?;
-                let close: String = candle_list.get_item(4)?.str()?.extract()?;
-                let volume: String = candle_list.get_item(5)?.str()?.extract()?;
-
-                candles.push(Candle {
+                rates.push(FundingRate {
                     timestamp,
                     exchange: self.exchange_name.clone(),
                     symbol: symbol.to_string(),
-                    timeframe,
-                    open: str_to_bigdecimal(&open, "open price")?,
-                    high: str_to_bigdecimal(&high, "high price")?,
-                    low: str_to_bigdecimal(&low, "low price")?,
-                    close: str_to_bigdecimal(&close, "close price")?,
-                    volume: str_to_bigdecimal(&volume, "volume")?,
+                    rate: str_to_bigdecimal(&rate, "funding rate")?,
+                });
+            }
+
+            rates.sort_by_key(|rate| rate.timestamp);
+            Ok(rates)
+        })
+    }
+
+    pub fn fetch_order_book(&self, symbol: &str, limit: Option<i64>) -> AppResult<OrderBook> {
+        Python::attach(|py| {
+            let exchange = self.instance.bind(py);
+            let order_book = self.with_retry(py, "fetch_order_book", || {
+                exchange.call_method1("fetch_order_book", (symbol, limit))
+            })?;
+
+            let levels = |side: &str| -> AppResult<Vec<OrderBookLevel>> {
+                let levels_list = order_book
+                    .get_item(side)?
+                    .cast_into::<PyList>()
+                    .map_err(|e| format!("Failed to cast {} to PyList: {}", side, e))?;
+
+                let mut levels = Vec::new();
+                for item in levels_list.iter() {
+                    let level_list = item
+                        .cast_into::<PyList>()
+                        .map_err(|e| format!("Failed to cast {} level to PyList: {}", side, e))?;
+
+                    let price: String = level_list.get_item(0)?.str()?.extract()?;
+                    let amount: String = level_list.get_item(1)?.str()?.extract()?;
+                    levels.push(OrderBookLevel {
+                        price: str_to_bigdecimal(&price, "order book price")?,
+                        amount: str_to_bigdecimal(&amount, "order book amount")?,
+                    });
+                }
+
+                Ok(levels)
+            };
+
+            Ok(OrderBook {
+                bids: levels("bids")?,
+                asks: levels("asks")?,
+            })
+        })
+    }
+
+    /// Balances of every currency the account holds, which requires an
+    /// instance created with credentials.
+    pub fn fetch_balance(&self) -> AppResult<Vec<Balance>> {
+        if !self.is_authenticated() {
+            return Err(AppError::BadRequest(format!(
+                "Fetching balances on {} requires API credentials",
+                self.exchange_name
+            )));
+        }
+
+        Python::attach(|py| {
+            let exchange = self.instance.bind(py);
+            let balance = self.with_retry(py, "fetch_balance", || {
+                exchange.call_method0("fetch_balance")
+            })?;
+
+            let amount = |kind: &str, currency: &str| -> AppResult<Option<BigDecimal>> {
+                let Some(amounts) = Self::optional_item(&balance, kind) else {
+                    return Ok(None);
+                };
+                let Some(value) = Self::optional_item(&amounts, currency) else {
+                    return Ok(None);
+                };
+                let value: String = value.str()?.extract()?;
+                str_to_bigdecimal(&value, &format!("{} balance", kind)).map(Some)
+            };
+
+            let Some(totals) = Self::optional_item(&balance, "total") else {
+                return Ok(Vec::new());
+            };
+            let totals = totals
+                .cast_into::<PyDict>()
+                .map_err(|e| format!("Failed to cast balance totals to PyDict: {}", e))?;
+
+            let mut balances = Vec::new();
+            for currency in totals.keys() {
+                let currency: String = currency.extract()?;
+                let total = amount("total", &currency)?;
+                if total.as_ref().is_some_and(|total| total.is_zero()) {
+                    continue;
+                }
+
+                balances.push(Balance {
+                    free: amount("free", &currency)?,
+                    used: amount("used", &currency)?,
+                    total,
+                    currency,
                 });
             }
+            balances.sort_by(|a, b| a.currency.cmp(&b.currency));
 
-            Ok(candles)
+            Ok(balances)
         })
     }
 
+    pub fn fetch_ticker(&self, symbol: &str) -> AppResult<Ticker> {
+        Python::attach(|py| {
+            let exchange = self.instance.bind(py);
+            let ticker = self.with_retry(py, "fetch_ticker", || {
+                exchange.call_method1("fetch_ticker", (symbol,))
+            })?;
+
+            let price = |key: &str| -> AppResult<Option<BigDecimal>> {
+                let Some(value) = Self::optional_item(&ticker, key) else {
+                    return Ok(None);
+                };
+                let value: String = value.str()?.extract()?;
+                str_to_bigdecimal(&value, &format!("ticker {}", key)).map(Some)
+            };
+
+            let timestamp = Self::optional_item(&ticker, "timestamp")
+                .map(|value| value.extract::<i64>())
+                .transpose()?
+                .and_then(|timestamp_ms| Utc.timestamp_millis_opt(timestamp_ms).single());
+
+            Ok(Ticker {
+                symbol: symbol.to_string(),
+                timestamp,
+                last: price("last")?,
+                bid: price("bid")?,
+                ask: price("ask")?,
+            })
+        })
+    }
+
+    fn invalid_ohlcv(candle: &Candle) -> Option<&'static str> {
+        if candle.low > candle.high {
+            Some("low is above high")
+        } else if candle.open < candle.low || candle.open > candle.high {
+            Some("open is outside the low/high range")
+        } else if candle.close < candle.low || candle.close > candle.high {
+            Some("close is outside the low/high range")
+        } else if candle.volume < BigDecimal::zero() {
+            Some("volume is negative")
+        } else {
+            None
+        }
+    }
+
+    fn validate_candles(
+        symbol: &str,
+        timeframe: Timeframe,
+        candles: Vec<Candle>,
+    ) -> AppResult<(Vec<Candle>, u64)> {
+        let validation = Self::candle_validation();
+        let delta = timeframe.to_delta();
+        let delta_ms = delta.num_milliseconds();
+        let check_grid = delta <= TimeDelta::days(1);
+
+        let mut validated: Vec<Candle> = Vec::with_capacity(candles.len());
+        let mut rejected = 0;
+        for mut candle in candles {
+            if let Some(problem) = Self::invalid_ohlcv(&candle) {
+                let message = format!(
+                    "Candle {} for {} is invalid: {}",
+                    candle.timestamp, symbol, problem
+                );
+                if validation == CandleValidation::Strict {
+                    return Err(message.into());
+                }
+
+                tracing::warn!("{}, dropping candle", message);
+                rejected += 1;
+                continue;
+            }
+
+            let timestamp_ms = candle.timestamp.timestamp_millis();
+            let offset = timestamp_ms.rem_euclid(delta_ms);
+            if check_grid && offset != 0 {
+                let message = format!(
+                    "Candle timestamp {} for {} is not aligned to timeframe {}",
+                    candle.timestamp, symbol, timeframe
+                );
+                if validation == CandleValidation::Strict {
+                    return Err(message.into());
+                }
+
+                tracing::warn!("{}, snapping to grid", message);
+                let Some(timestamp) = Utc.timestamp_millis_opt(timestamp_ms - offset).single()
+                else {
+                    return Err(message.into());
+                };
+                candle.timestamp = timestamp;
+            }
+
+            if let Some(last) = validated.last()
+                && candle.timestamp <= last.timestamp
+            {
+                let message = format!(
+                    "Candle timestamp {} for {} does not follow previous {}",
+                    candle.timestamp, symbol, last.timestamp
+                );
+                if validation == CandleValidation::Strict {
+                    return Err(message.into());
+                }
+
+                tracing::warn!("{}, dropping candle", message);
+                rejected += 1;
+                continue;
+            }
+
+            validated.push(candle);
+        }
+
+        Ok((validated, rejected))
+    }
+
+    /// Listing time of the market in milliseconds, when the exchange reports it.
+    fn listed_at(&self, symbol: &str) -> AppResult<Option<i64>> {
+        Python::attach(|py| {
+            let exchange = self.instance.bind(py);
+            let market = exchange.getattr("markets")?.get_item(symbol)?;
+            Ok(Self::optional_item(&market, "created")
+                .and_then(|value| value.extract::<i64>().ok()))
+        })
+    }
+
+    /// Finds the earliest candle by binary search over `since`, starting from
+    /// the market's listing time when known. Exchanges that return nothing for
+    /// old `since` values fall back to the earliest candle of the latest page.
     pub fn first_candle(&self, symbol: &str, timeframe: Timeframe) -> AppResult<Option<Candle>> {
-        let mut left = 0i64;
+        let mut left = self.listed_at(symbol)?.unwrap_or(0).max(0);
         let mut right = Utc::now().timestamp_millis();
         let mut first_candle: Option<Candle> = None;
 
         while left <= right {
             let mid = left + (right - left) / 2;
-            let candles = self.fetch_candles(symbol, timeframe, Some(mid), Some(1))?;
+            let (candles, _) = self.fetch_candles(symbol, timeframe, Some(mid), Some(1))?;
 
             if let Some(candle) = candles.into_iter().next() {
                 first_candle = Some(candle);
@@ -221,6 +1167,19 @@ impl CCXT {
             }
         }
 
+        if first_candle.is_none() {
+            let (candles, _) = self.fetch_candles(symbol, timeframe, None, None)?;
+            first_candle = candles.into_iter().next();
+            if let Some(candle) = &first_candle {
+                tracing::warn!(
+                    "No candles found by since for {} on {}, starting from {}",
+                    symbol,
+                    self.exchange_name,
+                    candle.timestamp
+                );
+            }
+        }
+
         Ok(first_candle)
     }
 }
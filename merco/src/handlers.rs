@@ -1,6 +1,10 @@
 pub mod backtest;
 pub mod candles;
+pub mod fetch_all_candles;
 pub mod fetch_candles;
 pub mod info;
+pub mod optimize;
 pub mod source;
 pub mod strategy;
+pub mod tasks;
+pub mod walk_forward;
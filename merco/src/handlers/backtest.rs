@@ -1,24 +1,33 @@
 use crate::app::AppState;
 use crate::errors::{ApiResult, AppError};
 use crate::exchange::ccxt::CCXT;
-use crate::models::Timeframe;
-use crate::tasks::{BacktestStatus, BacktestTask};
+use crate::handlers::tasks::stream_task_events;
+use crate::models::{ExportFormat, FeeRounding, Timeframe};
+use crate::services::backtests::load_backtest_results;
+use crate::strategy::ExecutionTiming;
+use crate::tasks::{BacktestResult, BacktestStatus, BacktestTask, MarketType, SlippageModel};
 use axum::{
-    extract::{Path, State},
+    body::Body,
+    extract::{Path, Query, State},
+    http::header,
     response::{
-        Json,
+        Json, Response,
         sse::{Event, KeepAlive, Sse},
     },
 };
-use chrono::Utc;
+use chrono::{DateTime, Utc, serde::ts_milliseconds_option};
 use futures::stream::Stream;
 use serde::{Deserialize, Serialize};
 use std::convert::Infallible;
 use std::sync::Arc;
 use tokio::sync::RwLock;
+use tokio::sync::broadcast::error::RecvError;
 use ts_rs::TS;
 use uuid::Uuid;
 
+const DEFAULT_HISTORY_LIMIT: i64 = 50;
+const MAX_HISTORY_LIMIT: i64 = 500;
+
 #[derive(Debug, Deserialize, TS)]
 #[ts(export)]
 pub struct CreateBacktestTaskRequest {
@@ -26,6 +35,34 @@ pub struct CreateBacktestTaskRequest {
     pub exchange: String,
     pub symbol: String,
     pub timeframe: Timeframe,
+    #[ts(optional)]
+    pub extra_symbols: Option<Vec<String>>,
+    #[ts(optional)]
+    pub extra_timeframes: Option<Vec<Timeframe>>,
+    #[ts(optional)]
+    pub force_rebuild: Option<bool>,
+    #[ts(optional, type = "Record<string, unknown>")]
+    pub params: Option<serde_json::Value>,
+    #[serde(default, with = "ts_milliseconds_option")]
+    #[ts(optional, type = "number")]
+    pub start: Option<DateTime<Utc>>,
+    #[serde(default, with = "ts_milliseconds_option")]
+    #[ts(optional, type = "number")]
+    pub end: Option<DateTime<Utc>>,
+    #[ts(optional)]
+    pub warmup: Option<usize>,
+    #[ts(optional)]
+    pub execution_timing: Option<ExecutionTiming>,
+    #[ts(optional)]
+    pub slippage: Option<SlippageModel>,
+    #[ts(optional)]
+    pub limit_fill_ratio: Option<f64>,
+    #[ts(optional)]
+    pub market_volume_ratio: Option<f64>,
+    #[ts(optional)]
+    pub market_type: Option<MarketType>,
+    #[ts(optional)]
+    pub fee_rounding: Option<FeeRounding>,
 }
 
 #[derive(Debug, Serialize, TS)]
@@ -34,23 +71,82 @@ pub struct CreateBacktestTaskResponse {
     pub task_id: Uuid,
 }
 
+#[derive(Debug, Deserialize, TS)]
+#[ts(export)]
+pub struct BacktestHistoryQuery {
+    pub name: String,
+    #[ts(optional)]
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export)]
+pub struct ExportBacktestQuery {
+    #[ts(optional)]
+    pub format: Option<ExportFormat>,
+}
+
 pub async fn create_task(
     State(state): State<AppState>,
     Json(request): Json<CreateBacktestTaskRequest>,
 ) -> ApiResult<CreateBacktestTaskResponse> {
-    let ccxt = CCXT::with_exchange(&request.exchange)?;
-    let precision = ccxt.precision(&request.symbol)?;
+    if let (Some(start), Some(end)) = (request.start, request.end)
+        && start >= end
+    {
+        return Err(AppError::BadRequest(
+            "Start time must be earlier than end time".to_string(),
+        ));
+    }
+
+    if let Some(slippage) = &request.slippage {
+        slippage.validate()?;
+    }
+
+    if let Some(ratio) = request.limit_fill_ratio
+        && !(ratio > 0.0 && ratio <= 1.0)
+    {
+        return Err(AppError::BadRequest(format!(
+            "Limit fill ratio must be in (0, 1], got {}",
+            ratio
+        )));
+    }
+
+    if let Some(ratio) = request.market_volume_ratio
+        && !(ratio > 0.0 && ratio <= 1.0)
+    {
+        return Err(AppError::BadRequest(format!(
+            "Market volume ratio must be in (0, 1], got {}",
+            ratio
+        )));
+    }
+
+    let ccxt = CCXT::with_exchange_async(&request.exchange).await?;
+    let precision = ccxt.precision_async(&request.symbol).await?;
 
     let now = Utc::now();
     let task = BacktestTask {
         id: Uuid::new_v4(),
         status: BacktestStatus::Pending,
         progress: 0.0,
+        throughput: None,
+        eta_secs: None,
         name: request.name.clone(),
         exchange: request.exchange.clone(),
         symbol: request.symbol.clone(),
         timeframe: request.timeframe,
         precision,
+        extra_symbols: request.extra_symbols.clone(),
+        extra_timeframes: request.extra_timeframes.clone(),
+        params: request.params.clone(),
+        start: request.start,
+        end: request.end,
+        warmup: request.warmup.unwrap_or(0),
+        execution_timing: request.execution_timing.unwrap_or_default(),
+        slippage: request.slippage.clone(),
+        limit_fill_ratio: request.limit_fill_ratio,
+        market_volume_ratio: request.market_volume_ratio,
+        market_type: request.market_type.unwrap_or_default(),
+        fee_rounding: request.fee_rounding.unwrap_or_default(),
         statistic: None,
         error_message: None,
         created_at: now,
@@ -58,6 +154,7 @@ pub async fn create_task(
         completed_at: None,
         updated_at: now,
         event_tx: Some(state.backtest_event_tx.clone()),
+        progress_tx: Some(state.backtest_progress_tx.clone()),
     };
     task.broadcast();
 
@@ -69,10 +166,17 @@ pub async fn create_task(
         tasks.insert(task_id, task.clone());
     }
 
-    tokio::spawn(async move {
+    state.spawn_task(task_id, "backtest", move |state, cancel| async move {
         let mut task = task.write().await;
-        task.execute(&state.strategy_manager, &request.name, state.db_pool)
-            .await;
+        task.execute(
+            &state.strategy_manager,
+            &request.name,
+            request.force_rebuild.unwrap_or(false),
+            state.backtest_config.run_limits(),
+            state.db_pool.clone(),
+            &cancel,
+        )
+        .await;
     });
 
     Ok(Json(CreateBacktestTaskResponse { task_id }))
@@ -108,45 +212,196 @@ pub async fn get_task(
     }
 }
 
+pub async fn get_history(
+    State(state): State<AppState>,
+    Query(query): Query<BacktestHistoryQuery>,
+) -> ApiResult<Vec<BacktestResult>> {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_HISTORY_LIMIT)
+        .clamp(1, MAX_HISTORY_LIMIT);
+    let results = load_backtest_results(&state.db_pool, &query.name, limit).await?;
+
+    Ok(Json(results))
+}
+
 pub async fn stream_tasks(
     State(state): State<AppState>,
 ) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
-    let mut rx = state.backtest_event_tx.subscribe();
-    let mut initial_events = Vec::new();
-    {
+    let rx = state.backtest_event_tx.subscribe();
+    let stream = stream_task_events(&state, &state.backtest_tasks, rx).await;
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+pub async fn stream_task(
+    State(state): State<AppState>,
+    Path(task_id): Path<Uuid>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, AppError> {
+    let task = {
         let backtest_tasks = state.backtest_tasks.read().await;
-        for task in backtest_tasks.values() {
-            let task = task.read().await;
-            if let Ok(data) = serde_json::to_string(&*task) {
-                initial_events.push(data);
-            }
-        }
-    }
+        backtest_tasks.get(&task_id).cloned()
+    };
+    let Some(task) = task else {
+        return Err(AppError::NotFound(format!(
+            "Task with id '{}' is not a Backtest task",
+            task_id
+        )));
+    };
+
+    let mut rx = state.backtest_event_tx.subscribe();
+    let mut progress_rx = state.backtest_progress_tx.subscribe();
+    let initial_task = task.try_read().ok().map(|task| task.clone());
 
     let stream = async_stream::stream! {
-        for data in initial_events {
-            yield Ok(Event::default().data(data));
+        let mut finished = false;
+        if let Some(task) = initial_task {
+            if let Ok(data) = serde_json::to_string(&task) {
+                yield Ok(Event::default().event("task").data(data));
+            }
+
+            finished = task.is_finished();
         }
 
-        loop {
+        while !finished {
             tokio::select! {
                 _ = state.shutdown_token.cancelled() => {
                     break;
                 }
                 result = rx.recv() => {
-                    let Ok(task) = result else {
-                        break;
+                    let task = match result {
+                        Ok(task) => task,
+                        // A running task holds its write lock and is sent
+                        // again with its next update.
+                        Err(RecvError::Lagged(_)) => match task.try_read() {
+                            Ok(task) => task.clone(),
+                            Err(_) => continue,
+                        },
+                        Err(RecvError::Closed) => {
+                            break;
+                        }
+                    };
+
+                    if task.id != task_id {
+                        continue;
+                    }
+
+                    if let Ok(data) = serde_json::to_string(&task) {
+                        yield Ok(Event::default().event("task").data(data));
+                    }
+
+                    finished = task.is_finished();
+                }
+                result = progress_rx.recv() => {
+                    let progress = match result {
+                        Ok(progress) => progress,
+                        Err(RecvError::Lagged(skipped)) => {
+                            tracing::warn!(
+                                "Backtest progress stream lagged behind by {} events",
+                                skipped
+                            );
+                            continue;
+                        }
+                        Err(RecvError::Closed) => {
+                            break;
+                        }
                     };
 
-                    let Ok(data) = serde_json::to_string(&task) else {
+                    if progress.task_id != task_id {
+                        continue;
+                    }
+
+                    let Ok(data) = serde_json::to_string(&progress) else {
                         continue;
                     };
 
-                    yield Ok(Event::default().data(data));
+                    yield Ok(Event::default().event("progress").data(data));
                 }
             }
         }
     };
 
-    Sse::new(stream).keep_alive(KeepAlive::default())
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
+
+pub async fn export_task(
+    State(state): State<AppState>,
+    Path(task_id): Path<Uuid>,
+    Query(query): Query<ExportBacktestQuery>,
+) -> Result<Response, AppError> {
+    let task = {
+        let backtest_tasks = state.backtest_tasks.read().await;
+        backtest_tasks.get(&task_id).cloned()
+    };
+    let Some(task) = task else {
+        return Err(AppError::NotFound(format!(
+            "Task with id '{}' is not a Backtest task",
+            task_id
+        )));
+    };
+
+    let statistic = match task.try_read() {
+        Ok(task) => task.statistic.clone(),
+        Err(_) => None,
+    };
+    let Some(statistic) = statistic else {
+        return Err(AppError::BadRequest(format!(
+            "Backtest task '{}' has no results to export",
+            task_id
+        )));
+    };
+
+    let format = query.format.unwrap_or_default();
+    let trades = statistic.trades;
+    let stream = async_stream::stream! {
+        match format {
+            ExportFormat::Csv => {
+                yield Ok::<_, Infallible>(
+                    "timestamp,symbol,trade_type,price,amount,fee,profit\n".to_string(),
+                );
+                for trade in trades {
+                    yield Ok(format!(
+                        "{},{},{},{},{},{},{}\n",
+                        trade.timestamp.to_rfc3339(),
+                        trade.symbol,
+                        trade.trade_type,
+                        trade.price,
+                        trade.amount,
+                        trade.fee,
+                        trade.profit.map(|profit| profit.to_string()).unwrap_or_default()
+                    ));
+                }
+            }
+            ExportFormat::Json => {
+                yield Ok("[".to_string());
+                let mut first = true;
+                for trade in &trades {
+                    let Ok(data) = serde_json::to_string(trade) else {
+                        continue;
+                    };
+
+                    if first {
+                        yield Ok(data);
+                        first = false;
+                    } else {
+                        yield Ok(format!(",{}", data));
+                    }
+                }
+                yield Ok("]".to_string());
+            }
+        }
+    };
+
+    Response::builder()
+        .header(header::CONTENT_TYPE, format.content_type())
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!(
+                "attachment; filename=\"backtest-{}.{}\"",
+                task_id,
+                format.extension()
+            ),
+        )
+        .body(Body::from_stream(stream))
+        .map_err(|e| AppError::Internal(e.to_string()))
 }
@@ -1,15 +1,29 @@
 use crate::app::AppState;
-use crate::errors::ApiResult;
-use crate::models::{AvailableCandleInfo, Candle, Timeframe};
+use crate::errors::{ApiResult, AppError};
+use crate::exchange::ccxt::CCXT;
+use crate::models::{AvailableCandleInfo, Candle, ExportFormat, Timeframe};
 use crate::services;
 use axum::{
     Json,
+    body::Body,
     extract::{Query, State},
+    http::header,
+    response::{IntoResponse, Response},
 };
-use chrono::{DateTime, Utc, serde::ts_milliseconds_option};
-use serde::Deserialize;
+use bigdecimal::{BigDecimal, FromPrimitive};
+use chrono::{DateTime, TimeDelta, Utc, serde::ts_milliseconds_option};
+use futures::{StreamExt, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
 use ts_rs::TS;
 
+const MAX_CANDLES_PER_PAGE: usize = 10_000;
+const DEFAULT_VERIFY_SAMPLES: usize = 20;
+const MAX_VERIFY_SAMPLES: usize = 200;
+const MAX_VERIFY_EXAMPLES: usize = 20;
+const MAX_VERIFY_DURATION: Duration = Duration::from_secs(60);
+const BUCKET_TIMEFRAME_HEADER: &str = "x-bucket-timeframe";
+
 #[derive(Debug, Deserialize, TS)]
 #[ts(export)]
 pub struct GetCandlesQuery {
@@ -22,13 +36,324 @@ pub struct GetCandlesQuery {
     #[serde(default, with = "ts_milliseconds_option")]
     #[ts(optional, type = "number")]
     pub end: Option<DateTime<Utc>>,
+    #[ts(optional)]
+    pub source: Option<Timeframe>,
+    /// Candles per page; with `source`, counts the resampled candles.
+    #[ts(optional)]
+    pub limit: Option<usize>,
+    #[ts(optional)]
+    pub max_points: Option<usize>,
+}
+
+#[derive(Debug, Serialize, TS)]
+#[ts(export)]
+pub struct GetCandlesResponse {
+    pub candles: Vec<Candle>,
+    #[serde(with = "ts_milliseconds_option")]
+    #[ts(optional, type = "number")]
+    pub next_cursor: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export)]
+pub struct LatestCandleQuery {
+    pub exchange: String,
+    pub symbol: String,
+    pub timeframe: Timeframe,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export)]
+pub struct ExportCandlesQuery {
+    pub exchange: String,
+    pub symbol: String,
+    pub timeframe: Timeframe,
+    #[serde(default, with = "ts_milliseconds_option")]
+    #[ts(optional, type = "number")]
+    pub start: Option<DateTime<Utc>>,
+    #[serde(default, with = "ts_milliseconds_option")]
+    #[ts(optional, type = "number")]
+    pub end: Option<DateTime<Utc>>,
+    #[ts(optional)]
+    pub format: Option<ExportFormat>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export)]
+pub struct DeleteCandlesQuery {
+    pub exchange: String,
+    pub symbol: String,
+    pub timeframe: Timeframe,
+    #[serde(default, with = "ts_milliseconds_option")]
+    #[ts(optional, type = "number")]
+    pub start: Option<DateTime<Utc>>,
+    #[serde(default, with = "ts_milliseconds_option")]
+    #[ts(optional, type = "number")]
+    pub end: Option<DateTime<Utc>>,
+    #[ts(optional)]
+    pub confirm: Option<bool>,
+}
+
+#[derive(Debug, Serialize, TS)]
+#[ts(export)]
+pub struct DeleteCandlesResponse {
+    #[ts(type = "number")]
+    pub deleted: u64,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export)]
+pub struct VerifyCandlesQuery {
+    pub exchange: String,
+    pub symbol: String,
+    pub timeframe: Timeframe,
+    #[serde(default, with = "ts_milliseconds_option")]
+    #[ts(optional, type = "number")]
+    pub start: Option<DateTime<Utc>>,
+    #[serde(default, with = "ts_milliseconds_option")]
+    #[ts(optional, type = "number")]
+    pub end: Option<DateTime<Utc>>,
+    #[ts(optional)]
+    pub samples: Option<usize>,
+    /// Largest accepted difference relative to the stored value, 0 by default.
+    #[ts(optional)]
+    pub tolerance: Option<f64>,
+}
+
+#[derive(Debug, Serialize, TS)]
+#[ts(export)]
+pub struct CandleMismatch {
+    pub stored: Candle,
+    /// Missing when the exchange returned no candle at the stored timestamp.
+    #[ts(optional)]
+    pub exchange: Option<Candle>,
+    pub fields: Vec<String>,
+}
+
+#[derive(Debug, Serialize, TS)]
+#[ts(export)]
+pub struct VerifyCandlesResponse {
+    pub sampled: usize,
+    pub matched: usize,
+    pub mismatched: usize,
+    pub missing: usize,
+    /// Sampled candles left unchecked because verification ran out of time.
+    pub skipped: usize,
+    pub examples: Vec<CandleMismatch>,
 }
 
 pub async fn get_candles(
     State(state): State<AppState>,
     Query(query): Query<GetCandlesQuery>,
-) -> ApiResult<Vec<Candle>> {
-    let candles = services::candles::get_candles(
+) -> Result<Response, AppError> {
+    let source = query.source.unwrap_or(query.timeframe);
+
+    if let Some(max_points) = query.max_points {
+        let max_points = max_points.clamp(1, MAX_CANDLES_PER_PAGE);
+        let span = services::candles::get_candles_span(
+            &state.db_pool,
+            &query.exchange,
+            &query.symbol,
+            source,
+            query.start,
+            query.end,
+        )
+        .await?;
+
+        let timeframe = match span {
+            Some((first, last)) => services::candles::downsample_timeframe(
+                source,
+                query.timeframe,
+                last - first,
+                max_points,
+            ),
+            None => query.timeframe,
+        };
+
+        let candles = services::candles::get_resampled_candles(
+            &state.db_pool,
+            &query.exchange,
+            &query.symbol,
+            source,
+            timeframe,
+            query.start,
+            query.end,
+        )
+        .await?;
+
+        let response = GetCandlesResponse {
+            candles,
+            next_cursor: None,
+        };
+
+        return Ok((
+            [(BUCKET_TIMEFRAME_HEADER, timeframe.to_string())],
+            Json(response),
+        )
+            .into_response());
+    }
+
+    let limit = query
+        .limit
+        .unwrap_or(MAX_CANDLES_PER_PAGE)
+        .clamp(1, MAX_CANDLES_PER_PAGE);
+
+    // With a source, the limit counts buckets and the cursor is a bucket
+    // boundary, so no bucket is cut off or emitted twice.
+    let (candles, next_cursor) = match query.source {
+        Some(source) => {
+            let buckets = services::candles::stream_resampled_candles(
+                state.db_pool.clone(),
+                query.exchange.clone(),
+                query.symbol.clone(),
+                source,
+                query.timeframe,
+                query.start,
+                query.end,
+            );
+            let mut candles: Vec<Candle> = buckets.take(limit + 1).try_collect().await?;
+            let has_more = candles.len() > limit;
+            candles.truncate(limit);
+            let next_cursor = candles
+                .last()
+                .filter(|_| has_more)
+                .map(|candle| query.timeframe.advance(candle.timestamp));
+            (candles, next_cursor)
+        }
+        None => {
+            let (candles, has_more) = services::candles::get_candles_page(
+                &state.db_pool,
+                &query.exchange,
+                &query.symbol,
+                query.timeframe,
+                query.start,
+                query.end,
+                limit,
+            )
+            .await?;
+            let next_cursor = candles
+                .last()
+                .filter(|_| has_more)
+                .map(|candle| candle.timestamp + TimeDelta::milliseconds(1));
+            (candles, next_cursor)
+        }
+    };
+
+    let response = GetCandlesResponse {
+        candles,
+        next_cursor,
+    };
+
+    Ok((
+        [(BUCKET_TIMEFRAME_HEADER, query.timeframe.to_string())],
+        Json(response),
+    )
+        .into_response())
+}
+
+pub async fn export_candles(
+    State(state): State<AppState>,
+    Query(query): Query<ExportCandlesQuery>,
+) -> Result<Response, AppError> {
+    let format = query.format.unwrap_or_default();
+    let filename = format!(
+        "{}-{}-{}.{}",
+        query.exchange,
+        query.symbol.replace('/', "_"),
+        query.timeframe,
+        format.extension()
+    );
+
+    let candles = services::candles::stream_candles(
+        state.db_pool.clone(),
+        query.exchange,
+        query.symbol,
+        query.timeframe,
+        query.start,
+        query.end,
+    );
+
+    let stream = async_stream::stream! {
+        let mut candles = std::pin::pin!(candles);
+        let mut first = true;
+
+        match format {
+            ExportFormat::Csv => {
+                yield Ok::<_, AppError>(
+                    "timestamp,exchange,symbol,timeframe,open,high,low,close,volume\n".to_string(),
+                );
+            }
+            ExportFormat::Json => {
+                yield Ok("[".to_string());
+            }
+        }
+
+        while let Some(candle) = candles.next().await {
+            let candle = match candle {
+                Ok(candle) => candle,
+                Err(e) => {
+                    tracing::error!("Failed to export candles: {}", e);
+                    yield Err(e);
+                    break;
+                }
+            };
+
+            match format {
+                ExportFormat::Csv => {
+                    yield Ok(format!(
+                        "{},{},{},{},{},{},{},{},{}\n",
+                        candle.timestamp.to_rfc3339(),
+                        candle.exchange,
+                        candle.symbol,
+                        candle.timeframe,
+                        candle.open,
+                        candle.high,
+                        candle.low,
+                        candle.close,
+                        candle.volume
+                    ));
+                }
+                ExportFormat::Json => {
+                    let Ok(data) = serde_json::to_string(&candle) else {
+                        continue;
+                    };
+
+                    if first {
+                        yield Ok(data);
+                    } else {
+                        yield Ok(format!(",{}", data));
+                    }
+                }
+            }
+            first = false;
+        }
+
+        if format == ExportFormat::Json {
+            yield Ok("]".to_string());
+        }
+    };
+
+    Response::builder()
+        .header(header::CONTENT_TYPE, format.content_type())
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}\"", filename),
+        )
+        .body(Body::from_stream(stream))
+        .map_err(|e| AppError::Internal(e.to_string()))
+}
+
+pub async fn delete_candles(
+    State(state): State<AppState>,
+    Query(query): Query<DeleteCandlesQuery>,
+) -> ApiResult<DeleteCandlesResponse> {
+    if query.start.is_none() && query.end.is_none() && query.confirm != Some(true) {
+        return Err(AppError::BadRequest(
+            "Deleting all candles of a series requires confirm=true".to_string(),
+        ));
+    }
+
+    let deleted = services::candles::delete_candles(
         &state.db_pool,
         &query.exchange,
         &query.symbol,
@@ -38,7 +363,35 @@ pub async fn get_candles(
     )
     .await?;
 
-    Ok(Json(candles))
+    tracing::info!(
+        "Deleted {} candles for {}/{} with timeframe {}",
+        deleted,
+        query.exchange,
+        query.symbol,
+        query.timeframe
+    );
+
+    Ok(Json(DeleteCandlesResponse { deleted }))
+}
+
+pub async fn latest_candle(
+    State(state): State<AppState>,
+    Query(query): Query<LatestCandleQuery>,
+) -> ApiResult<Candle> {
+    let candle = services::candles::get_latest_candle(
+        &state.db_pool,
+        &query.exchange,
+        &query.symbol,
+        query.timeframe,
+    )
+    .await?;
+
+    candle.map(Json).ok_or_else(|| {
+        AppError::NotFound(format!(
+            "No candles stored for {} on {} with timeframe {}",
+            query.symbol, query.exchange, query.timeframe
+        ))
+    })
 }
 
 pub async fn available_candles(
@@ -47,3 +400,123 @@ pub async fn available_candles(
     let available_candles = services::candles::get_available_candles(&state.db_pool).await?;
     Ok(Json(available_candles))
 }
+
+pub async fn verify_candles(
+    State(state): State<AppState>,
+    Query(query): Query<VerifyCandlesQuery>,
+) -> ApiResult<VerifyCandlesResponse> {
+    let samples = query.samples.unwrap_or(DEFAULT_VERIFY_SAMPLES);
+    if samples == 0 || samples > MAX_VERIFY_SAMPLES {
+        return Err(AppError::BadRequest(format!(
+            "Samples must be between 1 and {}, got {}",
+            MAX_VERIFY_SAMPLES, samples
+        )));
+    }
+    let tolerance = query.tolerance.unwrap_or(0.0);
+    let Some(tolerance) = BigDecimal::from_f64(tolerance).filter(|_| tolerance >= 0.0) else {
+        return Err(AppError::BadRequest(format!(
+            "Tolerance must be a non-negative number, got {}",
+            tolerance
+        )));
+    };
+
+    let stored = services::candles::sample_candles(
+        &state.db_pool,
+        &query.exchange,
+        &query.symbol,
+        query.timeframe,
+        query.start,
+        query.end,
+        samples,
+    )
+    .await?;
+    if stored.is_empty() {
+        return Err(AppError::NotFound(format!(
+            "No candles stored for {} on {} with timeframe {} in the requested range",
+            query.symbol, query.exchange, query.timeframe
+        )));
+    }
+
+    // Requests are spaced by the exchange's rate limit, and whatever is left
+    // once the time limit is reached is reported as skipped.
+    let ccxt = CCXT::with_exchange_async(&query.exchange).await?;
+    let delay = Duration::from_millis(ccxt.rate_limit_ms_async().await?);
+    let deadline = Instant::now() + MAX_VERIFY_DURATION;
+    let mut response = VerifyCandlesResponse {
+        sampled: stored.len(),
+        matched: 0,
+        mismatched: 0,
+        missing: 0,
+        skipped: 0,
+        examples: Vec::new(),
+    };
+    for (index, candle) in stored.into_iter().enumerate() {
+        if index > 0 {
+            if Instant::now() + delay > deadline {
+                response.skipped = response.sampled - index;
+                break;
+            }
+            tokio::time::sleep(delay).await;
+        }
+
+        let (fetched, _) = ccxt
+            .fetch_candles_async(
+                &query.symbol,
+                query.timeframe,
+                Some(candle.timestamp.timestamp_millis()),
+                Some(1),
+            )
+            .await?;
+        let fetched = fetched
+            .into_iter()
+            .find(|fetched| fetched.timestamp == candle.timestamp);
+
+        let fields = match &fetched {
+            Some(fetched) => mismatched_fields(&candle, fetched, &tolerance),
+            None => Vec::new(),
+        };
+        if fetched.is_none() {
+            response.missing += 1;
+        } else if fields.is_empty() {
+            response.matched += 1;
+            continue;
+        } else {
+            response.mismatched += 1;
+        }
+
+        if response.examples.len() < MAX_VERIFY_EXAMPLES {
+            response.examples.push(CandleMismatch {
+                stored: candle,
+                exchange: fetched,
+                fields,
+            });
+        }
+    }
+
+    tracing::info!(
+        "Verified {} candles for {}/{} with timeframe {}: {} mismatched, {} missing, {} skipped",
+        response.sampled,
+        query.exchange,
+        query.symbol,
+        query.timeframe,
+        response.mismatched,
+        response.missing,
+        response.skipped
+    );
+
+    Ok(Json(response))
+}
+
+fn mismatched_fields(stored: &Candle, fetched: &Candle, tolerance: &BigDecimal) -> Vec<String> {
+    [
+        ("open", &stored.open, &fetched.open),
+        ("high", &stored.high, &fetched.high),
+        ("low", &stored.low, &fetched.low),
+        ("close", &stored.close, &fetched.close),
+        ("volume", &stored.volume, &fetched.volume),
+    ]
+    .into_iter()
+    .filter(|(_, stored, fetched)| (*stored - *fetched).abs() > tolerance * stored.abs())
+    .map(|(field, _, _)| field.to_string())
+    .collect()
+}
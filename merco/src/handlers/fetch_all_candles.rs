@@ -0,0 +1,170 @@
+use crate::app::AppState;
+use crate::errors::{ApiResult, AppError};
+use crate::exchange::ccxt::CCXT;
+use crate::handlers::tasks::stream_task_events;
+use crate::models::Timeframe;
+use crate::tasks::{FetchAllCandlesTask, FetchCandlesStatus};
+use axum::{
+    extract::{Path, State},
+    response::{
+        Json,
+        sse::{Event, KeepAlive, Sse},
+    },
+};
+use chrono::Utc;
+use futures::stream::Stream;
+use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export)]
+pub struct CreateFetchAllCandlesTaskRequest {
+    pub exchange: String,
+    pub timeframe: Timeframe,
+    #[ts(optional)]
+    pub symbols: Option<Vec<String>>,
+    /// Overrides the delay between exchange requests, in milliseconds.
+    #[ts(optional, type = "number")]
+    pub delay_ms: Option<u64>,
+    #[ts(optional, type = "number")]
+    pub batch_limit: Option<u64>,
+}
+
+#[derive(Debug, Serialize, TS)]
+#[ts(export)]
+pub struct CreateFetchAllCandlesTaskResponse {
+    pub task_id: Uuid,
+}
+
+pub async fn create_task(
+    State(state): State<AppState>,
+    Json(request): Json<CreateFetchAllCandlesTaskRequest>,
+) -> ApiResult<CreateFetchAllCandlesTaskResponse> {
+    if request.batch_limit == Some(0) {
+        return Err(AppError::BadRequest(
+            "Batch limit must be greater than zero".to_string(),
+        ));
+    }
+
+    let ccxt = CCXT::with_exchange_async(&request.exchange).await?;
+    if !ccxt.timeframes_async().await?.contains(&request.timeframe) {
+        return Err(AppError::BadRequest(format!(
+            "Timeframe {} is not supported by {}",
+            request.timeframe, request.exchange
+        )));
+    }
+
+    let available_symbols = ccxt.symbols_async().await?;
+    let symbols = match request.symbols {
+        Some(symbols) => {
+            let unavailable: Vec<&str> = symbols
+                .iter()
+                .filter(|symbol| !available_symbols.contains(symbol))
+                .map(|symbol| symbol.as_str())
+                .collect();
+            if !unavailable.is_empty() {
+                return Err(AppError::BadRequest(format!(
+                    "Symbols not available on {}: {}",
+                    request.exchange,
+                    unavailable.join(", ")
+                )));
+            }
+            symbols
+        }
+        None => available_symbols,
+    };
+
+    if symbols.is_empty() {
+        return Err(AppError::BadRequest("No symbols to fetch".to_string()));
+    }
+
+    let delay_ms = match request.delay_ms {
+        Some(delay_ms) => delay_ms,
+        None => ccxt.rate_limit_ms_async().await?,
+    };
+
+    let now = Utc::now();
+    let task = FetchAllCandlesTask {
+        id: Uuid::new_v4(),
+        status: FetchCandlesStatus::Pending,
+        progress: 0.0,
+        exchange: request.exchange,
+        timeframe: request.timeframe,
+        symbols,
+        delay_ms,
+        batch_limit: request.batch_limit,
+        completed_symbols: 0,
+        current_symbol: None,
+        results: Vec::new(),
+        failures: Vec::new(),
+        error_message: None,
+        created_at: now,
+        started_at: None,
+        completed_at: None,
+        updated_at: now,
+        event_tx: Some(state.fetch_all_candles_event_tx.clone()),
+    };
+    task.broadcast();
+
+    let task_id = task.id;
+    let task = Arc::new(RwLock::new(task));
+
+    {
+        let mut tasks = state.fetch_all_candles_tasks.write().await;
+        tasks.insert(task_id, task.clone());
+    }
+
+    state.spawn_task(
+        task_id,
+        "fetch_all_candles",
+        move |state, cancel| async move {
+            let mut task = task.write().await;
+            task.execute(state.db_pool.clone(), &cancel).await;
+        },
+    );
+
+    Ok(Json(CreateFetchAllCandlesTaskResponse { task_id }))
+}
+
+pub async fn get_all_tasks(State(state): State<AppState>) -> ApiResult<Vec<FetchAllCandlesTask>> {
+    let mut tasks = Vec::new();
+    let fetch_all_candles_tasks = state.fetch_all_candles_tasks.read().await;
+    for task in fetch_all_candles_tasks.values() {
+        let task = task.read().await;
+        tasks.push(task.clone());
+    }
+
+    Ok(Json(tasks))
+}
+
+pub async fn get_task(
+    State(state): State<AppState>,
+    Path(task_id): Path<Uuid>,
+) -> ApiResult<FetchAllCandlesTask> {
+    let fetch_all_candles_tasks = state.fetch_all_candles_tasks.read().await;
+    let task = fetch_all_candles_tasks.get(&task_id);
+
+    match task {
+        Some(task) => {
+            let task = task.read().await;
+            Ok(Json(task.clone()))
+        }
+        _ => Err(AppError::NotFound(format!(
+            "Task with id '{}' is not a FetchAllCandles task",
+            task_id
+        ))),
+    }
+}
+
+pub async fn stream_tasks(
+    State(state): State<AppState>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let rx = state.fetch_all_candles_event_tx.subscribe();
+    let stream = stream_task_events(&state, &state.fetch_all_candles_tasks, rx).await;
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
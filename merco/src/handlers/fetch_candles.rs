@@ -1,6 +1,9 @@
 use crate::app::AppState;
-use crate::errors::{ApiResult, AppError};
+use crate::errors::{ApiResult, AppError, AppResult};
+use crate::exchange::ccxt::CCXT;
+use crate::handlers::tasks::stream_task_events;
 use crate::models::Timeframe;
+use crate::services;
 use crate::tasks::{FetchCandlesStatus, FetchCandlesTask};
 use axum::{
     extract::{Path, State},
@@ -9,7 +12,7 @@ use axum::{
         sse::{Event, KeepAlive, Sse},
     },
 };
-use chrono::Utc;
+use chrono::{DateTime, Utc, serde::ts_milliseconds_option};
 use futures::stream::Stream;
 use serde::{Deserialize, Serialize};
 use std::convert::Infallible;
@@ -24,6 +27,25 @@ pub struct CreateFetchCandlesTaskRequest {
     pub symbol: String,
     pub exchange: String,
     pub timeframe: Timeframe,
+    #[serde(default, with = "ts_milliseconds_option")]
+    #[ts(optional, type = "number")]
+    pub start: Option<DateTime<Utc>>,
+    #[serde(default, with = "ts_milliseconds_option")]
+    #[ts(optional, type = "number")]
+    pub end: Option<DateTime<Utc>>,
+    /// Overrides the delay between exchange requests, in milliseconds.
+    #[ts(optional, type = "number")]
+    pub delay_ms: Option<u64>,
+    #[ts(optional, type = "number")]
+    pub batch_limit: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export)]
+pub struct UpdateCandlesRequest {
+    pub exchange: String,
+    pub symbol: String,
+    pub timeframe: Timeframe,
 }
 
 #[derive(Debug, Serialize, TS)]
@@ -36,14 +58,93 @@ pub async fn create_task(
     State(state): State<AppState>,
     Json(request): Json<CreateFetchCandlesTaskRequest>,
 ) -> ApiResult<CreateFetchCandlesTaskResponse> {
+    if let (Some(start), Some(end)) = (request.start, request.end)
+        && start >= end
+    {
+        return Err(AppError::BadRequest(
+            "Start time must be earlier than end time".to_string(),
+        ));
+    }
+
+    if request.batch_limit == Some(0) {
+        return Err(AppError::BadRequest(
+            "Batch limit must be greater than zero".to_string(),
+        ));
+    }
+
+    let ccxt = CCXT::with_exchange_async(&request.exchange).await?;
+    validate_market(&ccxt, &request.exchange, &request.symbol, request.timeframe).await?;
+
+    let delay_ms = match request.delay_ms {
+        Some(delay_ms) => delay_ms,
+        None => ccxt.rate_limit_ms_async().await?,
+    };
+
     let now = Utc::now();
     let task = FetchCandlesTask {
         id: Uuid::new_v4(),
         status: FetchCandlesStatus::Pending,
         progress: 0.0,
+        throughput: None,
+        eta_secs: None,
         symbol: request.symbol.clone(),
         exchange: request.exchange.clone(),
         timeframe: request.timeframe,
+        start: request.start,
+        end: request.end,
+        delay_ms,
+        batch_limit: request.batch_limit,
+        result: None,
+        error_message: None,
+        created_at: now,
+        started_at: None,
+        completed_at: None,
+        updated_at: now,
+        event_tx: Some(state.fetch_candles_event_tx.clone()),
+    };
+
+    let task_id = spawn_task(state, task).await;
+    Ok(Json(CreateFetchCandlesTaskResponse { task_id }))
+}
+
+/// Creates a fetch task that tops up a stored series from its latest candle to
+/// now.
+pub async fn update_candles(
+    State(state): State<AppState>,
+    Json(request): Json<UpdateCandlesRequest>,
+) -> ApiResult<CreateFetchCandlesTaskResponse> {
+    let Some(latest) = services::candles::get_latest_candle(
+        &state.db_pool,
+        &request.exchange,
+        &request.symbol,
+        request.timeframe,
+    )
+    .await?
+    else {
+        return Err(AppError::NotFound(format!(
+            "No candles stored for {} on {} with timeframe {}",
+            request.symbol, request.exchange, request.timeframe
+        )));
+    };
+
+    let ccxt = CCXT::with_exchange_async(&request.exchange).await?;
+    validate_market(&ccxt, &request.exchange, &request.symbol, request.timeframe).await?;
+    let delay_ms = ccxt.rate_limit_ms_async().await?;
+
+    let now = Utc::now();
+    let task = FetchCandlesTask {
+        id: Uuid::new_v4(),
+        status: FetchCandlesStatus::Pending,
+        progress: 0.0,
+        throughput: None,
+        eta_secs: None,
+        symbol: request.symbol,
+        exchange: request.exchange,
+        timeframe: request.timeframe,
+        start: Some(request.timeframe.advance(latest.timestamp)),
+        end: None,
+        delay_ms,
+        batch_limit: None,
         result: None,
         error_message: None,
         created_at: now,
@@ -53,6 +154,36 @@ pub async fn create_task(
         event_tx: Some(state.fetch_candles_event_tx.clone()),
     };
 
+    let task_id = spawn_task(state, task).await;
+    Ok(Json(CreateFetchCandlesTaskResponse { task_id }))
+}
+
+async fn validate_market(
+    ccxt: &CCXT,
+    exchange: &str,
+    symbol: &str,
+    timeframe: Timeframe,
+) -> AppResult<()> {
+    if !ccxt.symbols_async().await?.iter().any(|s| s == symbol) {
+        return Err(AppError::BadRequest(format!(
+            "Symbol '{}' is not available on {}",
+            symbol, exchange
+        )));
+    }
+
+    if !ccxt.timeframes_async().await?.contains(&timeframe) {
+        return Err(AppError::BadRequest(format!(
+            "Timeframe {} is not supported by {}",
+            timeframe, exchange
+        )));
+    }
+
+    Ok(())
+}
+
+async fn spawn_task(state: AppState, task: FetchCandlesTask) -> Uuid {
+    task.broadcast();
+
     let task_id = task.id;
     let task = Arc::new(RwLock::new(task));
 
@@ -61,13 +192,12 @@ pub async fn create_task(
         tasks.insert(task_id, task.clone());
     }
 
-    let db_pool = state.db_pool.clone();
-    tokio::spawn(async move {
+    state.spawn_task(task_id, "fetch_candles", move |state, cancel| async move {
         let mut task = task.write().await;
-        task.execute(db_pool).await;
+        task.execute(state.db_pool.clone(), &cancel).await;
     });
 
-    Ok(Json(CreateFetchCandlesTaskResponse { task_id }))
+    task_id
 }
 
 pub async fn get_all_tasks(State(state): State<AppState>) -> ApiResult<Vec<FetchCandlesTask>> {
@@ -103,42 +233,8 @@ pub async fn get_task(
 pub async fn stream_tasks(
     State(state): State<AppState>,
 ) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
-    let mut rx = state.fetch_candles_event_tx.subscribe();
-    let mut initial_events = Vec::new();
-    {
-        let fetch_candles_tasks = state.fetch_candles_tasks.read().await;
-        for task in fetch_candles_tasks.values() {
-            let task = task.read().await;
-            if let Ok(data) = serde_json::to_string(&*task) {
-                initial_events.push(data);
-            }
-        }
-    }
-
-    let stream = async_stream::stream! {
-        for data in initial_events {
-            yield Ok(Event::default().data(data));
-        }
-
-        loop {
-            tokio::select! {
-                _ = state.shutdown_token.cancelled() => {
-                    break;
-                }
-                result = rx.recv() => {
-                    let Ok(task) = result else {
-                        break;
-                    };
-
-                    let Ok(data) = serde_json::to_string(&task) else {
-                        continue;
-                    };
-
-                    yield Ok(Event::default().data(data));
-                }
-            }
-        }
-    };
+    let rx = state.fetch_candles_event_tx.subscribe();
+    let stream = stream_task_events(&state, &state.fetch_candles_tasks, rx).await;
 
     Sse::new(stream).keep_alive(KeepAlive::default())
 }
@@ -1,8 +1,13 @@
-use crate::errors::ApiResult;
+use crate::app::AppState;
+use crate::errors::{ApiResult, AppError};
 use crate::exchange::ccxt::CCXT;
-use crate::models::Timeframe;
-use axum::{Json, extract::Query};
-use serde::Deserialize;
+use crate::models::{Balance, MarketInfo, Ticker, Timeframe};
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 
 #[derive(Debug, Deserialize, TS)]
@@ -11,20 +16,128 @@ pub struct ExchangeQuery {
     pub exchange: String,
 }
 
+#[derive(Debug, Deserialize, TS)]
+#[ts(export)]
+pub struct MarketsQuery {
+    #[ts(optional)]
+    pub symbol: Option<String>,
+}
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export)]
+pub struct TickerQuery {
+    pub exchange: String,
+    pub symbol: String,
+}
+
+#[derive(Debug, Serialize, TS)]
+#[ts(export)]
+pub struct ReadinessCheck {
+    pub name: String,
+    pub ok: bool,
+    #[ts(optional)]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, TS)]
+#[ts(export)]
+pub struct ReadinessResponse {
+    pub ready: bool,
+    pub checks: Vec<ReadinessCheck>,
+}
+
 pub async fn check() -> ApiResult<&'static str> {
     Ok(Json("OK"))
 }
 
+pub async fn ready(State(state): State<AppState>) -> (StatusCode, Json<ReadinessResponse>) {
+    let database = sqlx::query("SELECT 1")
+        .execute(&state.db_pool)
+        .await
+        .map(|_| ())
+        .map_err(|e| e.to_string());
+    let python = CCXT::check_available_async()
+        .await
+        .map_err(|e| e.to_string());
+
+    let checks: Vec<ReadinessCheck> = [("database", database), ("python", python)]
+        .into_iter()
+        .map(|(name, result)| ReadinessCheck {
+            name: name.to_string(),
+            ok: result.is_ok(),
+            error: result.err(),
+        })
+        .collect();
+
+    let ready = checks.iter().all(|check| check.ok);
+    for check in checks.iter().filter(|check| !check.ok) {
+        tracing::warn!(
+            "Readiness check '{}' failed: {}",
+            check.name,
+            check.error.as_deref().unwrap_or_default()
+        );
+    }
+
+    let status = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (status, Json(ReadinessResponse { ready, checks }))
+}
+
 pub async fn list_exchanges() -> ApiResult<Vec<String>> {
-    Ok(Json(CCXT::exchanges()?))
+    Ok(Json(CCXT::exchanges_async().await?))
 }
 
 pub async fn list_symbols(Query(query): Query<ExchangeQuery>) -> ApiResult<Vec<String>> {
-    let exchange = CCXT::with_exchange(&query.exchange)?;
-    Ok(Json(exchange.symbols()?))
+    let exchange = CCXT::with_exchange_async(&query.exchange).await?;
+    Ok(Json(exchange.symbols_async().await?))
 }
 
 pub async fn list_timeframes(Query(query): Query<ExchangeQuery>) -> ApiResult<Vec<Timeframe>> {
-    let exchange = CCXT::with_exchange(&query.exchange)?;
-    Ok(Json(exchange.timeframes()?))
+    let exchange = CCXT::with_exchange_async(&query.exchange).await?;
+    Ok(Json(exchange.timeframes_async().await?))
+}
+
+pub async fn list_markets(
+    Path(exchange): Path<String>,
+    Query(query): Query<MarketsQuery>,
+) -> ApiResult<Vec<MarketInfo>> {
+    let exchange = CCXT::with_exchange_async(&exchange).await?;
+    if let Some(symbol) = &query.symbol
+        && !exchange.symbols_async().await?.contains(symbol)
+    {
+        return Err(AppError::NotFound(format!("Symbol '{}' not found", symbol)));
+    }
+
+    Ok(Json(exchange.markets_async(query.symbol.as_deref()).await?))
+}
+
+pub async fn get_ticker(Query(query): Query<TickerQuery>) -> ApiResult<Ticker> {
+    let exchange = CCXT::with_exchange_async(&query.exchange).await?;
+    if !exchange.symbols_async().await?.contains(&query.symbol) {
+        return Err(AppError::NotFound(format!(
+            "Symbol '{}' not found",
+            query.symbol
+        )));
+    }
+
+    Ok(Json(exchange.fetch_ticker_async(&query.symbol).await?))
+}
+
+pub async fn get_balance(
+    State(state): State<AppState>,
+    Path(exchange): Path<String>,
+) -> ApiResult<Vec<Balance>> {
+    let Some(credentials) = state.exchange_credentials(&exchange) else {
+        return Err(AppError::BadRequest(format!(
+            "No API credentials configured for exchange '{}'",
+            exchange
+        )));
+    };
+
+    let exchange = CCXT::with_credentials_async(&exchange, credentials).await?;
+    Ok(Json(exchange.fetch_balance_async().await?))
 }
@@ -0,0 +1,129 @@
+use crate::app::AppState;
+use crate::errors::{ApiResult, AppError};
+use crate::handlers::tasks::stream_task_events;
+use crate::models::Timeframe;
+use crate::tasks::{OptimizeMetric, OptimizeStatus, OptimizeTask, ParameterRange};
+use axum::{
+    extract::{Path, State},
+    response::{
+        Json,
+        sse::{Event, KeepAlive, Sse},
+    },
+};
+use chrono::Utc;
+use futures::stream::Stream;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export)]
+pub struct CreateOptimizeTaskRequest {
+    pub name: String,
+    pub exchange: String,
+    pub symbol: String,
+    pub timeframe: Timeframe,
+    pub grid: BTreeMap<String, ParameterRange>,
+    pub metric: OptimizeMetric,
+}
+
+#[derive(Debug, Serialize, TS)]
+#[ts(export)]
+pub struct CreateOptimizeTaskResponse {
+    pub task_id: Uuid,
+}
+
+pub async fn create_task(
+    State(state): State<AppState>,
+    Json(request): Json<CreateOptimizeTaskRequest>,
+) -> ApiResult<CreateOptimizeTaskResponse> {
+    let total_combinations = OptimizeTask::combinations(&request.grid)?.len();
+
+    let now = Utc::now();
+    let task = OptimizeTask {
+        id: Uuid::new_v4(),
+        status: OptimizeStatus::Pending,
+        progress: 0.0,
+        name: request.name,
+        exchange: request.exchange,
+        symbol: request.symbol,
+        timeframe: request.timeframe,
+        grid: request.grid,
+        metric: request.metric,
+        total_combinations,
+        completed_combinations: 0,
+        failed_combinations: 0,
+        results: Vec::new(),
+        error_message: None,
+        created_at: now,
+        started_at: None,
+        completed_at: None,
+        updated_at: now,
+        event_tx: Some(state.optimize_event_tx.clone()),
+    };
+    task.broadcast();
+
+    let task_id = task.id;
+    let task = Arc::new(RwLock::new(task));
+
+    {
+        let mut tasks = state.optimize_tasks.write().await;
+        tasks.insert(task_id, task.clone());
+    }
+
+    state.spawn_task(task_id, "optimize", move |state, cancel| async move {
+        let mut task = task.write().await;
+        task.execute(
+            &state.strategy_manager,
+            state.backtest_config.run_limits(),
+            state.db_pool.clone(),
+            &cancel,
+        )
+        .await;
+    });
+
+    Ok(Json(CreateOptimizeTaskResponse { task_id }))
+}
+
+pub async fn get_all_tasks(State(state): State<AppState>) -> ApiResult<Vec<OptimizeTask>> {
+    let mut tasks = Vec::new();
+    let optimize_tasks = state.optimize_tasks.read().await;
+    for task in optimize_tasks.values() {
+        let task = task.read().await;
+        tasks.push(task.clone());
+    }
+
+    Ok(Json(tasks))
+}
+
+pub async fn get_task(
+    State(state): State<AppState>,
+    Path(task_id): Path<Uuid>,
+) -> ApiResult<OptimizeTask> {
+    let optimize_tasks = state.optimize_tasks.read().await;
+    let task = optimize_tasks.get(&task_id);
+
+    match task {
+        Some(task) => {
+            let task = task.read().await;
+            Ok(Json(task.clone()))
+        }
+        _ => Err(AppError::NotFound(format!(
+            "Task with id '{}' is not an Optimize task",
+            task_id
+        ))),
+    }
+}
+
+pub async fn stream_tasks(
+    State(state): State<AppState>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let rx = state.optimize_event_tx.subscribe();
+    let stream = stream_task_events(&state, &state.optimize_tasks, rx).await;
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
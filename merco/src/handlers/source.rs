@@ -1,11 +1,43 @@
-use crate::errors::{ApiResult, AppError};
-use crate::strategy::STRATEGY_WORKDIR_NAME;
+use crate::app::AppState;
+use crate::errors::{ApiResult, AppError, AppResult};
 use crate::utils::safe_join;
-use axum::{Json, extract::Query};
+use axum::{
+    Json,
+    extract::{Query, State},
+};
+use regex::{Regex, RegexBuilder};
 use serde::{Deserialize, Serialize};
+use std::path::Path;
 use tokio::fs;
 use ts_rs::TS;
 
+const MAX_SEARCH_RESULTS: usize = 500;
+const MAX_SEARCH_FILE_SIZE: u64 = 1024 * 1024;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SourceConfig {
+    /// Largest body `save_source` accepts, i.e. the JSON-encoded file.
+    /// Larger requests are rejected with 413 before they are buffered.
+    pub max_file_size: usize,
+    pub ignored: Vec<String>,
+}
+
+impl Default for SourceConfig {
+    fn default() -> Self {
+        Self {
+            max_file_size: 1024 * 1024,
+            ignored: vec!["target".to_string(), ".git".to_string()],
+        }
+    }
+}
+
+impl SourceConfig {
+    fn is_ignored(&self, name: &str) -> bool {
+        self.ignored.iter().any(|ignored| ignored == name)
+    }
+}
+
 #[derive(Debug, Clone, Deserialize, TS)]
 #[ts(export)]
 pub struct GetSourceQuery {
@@ -37,7 +69,7 @@ pub struct FileNode {
     pub node_type: FileNodeType,
 }
 
-#[derive(Debug, Clone, Serialize, TS)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[serde(rename_all = "snake_case")]
 #[ts(export)]
 pub enum FileNodeType {
@@ -45,12 +77,14 @@ pub enum FileNodeType {
     Directory,
 }
 
-pub async fn get_source(Query(query): Query<GetSourceQuery>) -> ApiResult<GetSourceResponse> {
-    let current_dir = std::env::current_dir()?;
-    let base_dir = current_dir.join(STRATEGY_WORKDIR_NAME).canonicalize()?;
-    let full_path = safe_join(&base_dir, &query.path)?;
+pub async fn get_source(
+    State(state): State<AppState>,
+    Query(query): Query<GetSourceQuery>,
+) -> ApiResult<GetSourceResponse> {
+    let base_dir = state.strategy_manager.workspace_dir();
+    let full_path = safe_join(base_dir, &query.path)?;
 
-    let Ok(relative_path) = full_path.strip_prefix(&base_dir) else {
+    let Ok(relative_path) = full_path.strip_prefix(base_dir) else {
         return Err(AppError::BadRequest(
             "Access denied: path outside workspace".to_string(),
         ));
@@ -71,6 +105,10 @@ pub async fn get_source(Query(query): Query<GetSourceQuery>) -> ApiResult<GetSou
         while let Some(entry) = read_dir.next_entry().await? {
             let entry_type = entry.file_type().await?;
             let entry_name = entry.file_name().to_str().unwrap_or_default().to_string();
+            if state.source_config.is_ignored(&entry_name) {
+                continue;
+            }
+
             let entry_path = relative_path
                 .join(&entry_name)
                 .to_string_lossy()
@@ -92,10 +130,8 @@ pub async fn get_source(Query(query): Query<GetSourceQuery>) -> ApiResult<GetSou
         }
 
         children.sort_by(|a, b| match (&a.node_type, &b.node_type) {
-            (FileNodeType::Directory { .. }, FileNodeType::File { .. }) => std::cmp::Ordering::Less,
-            (FileNodeType::File { .. }, FileNodeType::Directory { .. }) => {
-                std::cmp::Ordering::Greater
-            }
+            (FileNodeType::Directory, FileNodeType::File) => std::cmp::Ordering::Less,
+            (FileNodeType::File, FileNodeType::Directory) => std::cmp::Ordering::Greater,
             _ => a.name.cmp(&b.name),
         });
 
@@ -128,12 +164,18 @@ pub struct SaveSourceQuery {
 }
 
 pub async fn save_source(
+    State(state): State<AppState>,
     Query(query): Query<SaveSourceQuery>,
     Json(content): Json<String>,
 ) -> ApiResult<()> {
-    let current_dir = std::env::current_dir()?;
-    let base_dir = current_dir.join(STRATEGY_WORKDIR_NAME).canonicalize()?;
-    let full_path = safe_join(&base_dir, &query.path)?;
+    if content.contains('\0') {
+        return Err(AppError::BadRequest(
+            "Binary content is not allowed".to_string(),
+        ));
+    }
+
+    let base_dir = state.strategy_manager.workspace_dir();
+    let full_path = safe_join(base_dir, &query.path)?;
 
     if full_path.exists() {
         let metadata = fs::metadata(&full_path).await?;
@@ -150,16 +192,59 @@ pub async fn save_source(
     Ok(Json(()))
 }
 
+#[derive(Debug, Clone, Deserialize, TS)]
+#[ts(export)]
+pub struct CreateSourceRequest {
+    pub path: String,
+    pub kind: FileNodeType,
+}
+
+pub async fn create_source(
+    State(state): State<AppState>,
+    Json(request): Json<CreateSourceRequest>,
+) -> ApiResult<()> {
+    let base_dir = state.strategy_manager.workspace_dir();
+    let full_path = safe_join(base_dir, &request.path)?;
+
+    if full_path.exists() {
+        return Err(AppError::BadRequest("Path already exists".to_string()));
+    }
+
+    if let Some(parent) = full_path.parent() {
+        fs::create_dir_all(parent).await?;
+    }
+
+    let result = match request.kind {
+        FileNodeType::File => fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&full_path)
+            .await
+            .map(|_| ()),
+        FileNodeType::Directory => fs::create_dir(&full_path).await,
+    };
+
+    match result {
+        Ok(()) => Ok(Json(())),
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+            Err(AppError::BadRequest("Path already exists".to_string()))
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
 #[derive(Debug, Clone, Deserialize, TS)]
 #[ts(export)]
 pub struct DeleteSourceQuery {
     pub path: String,
 }
 
-pub async fn delete_source(Query(query): Query<DeleteSourceQuery>) -> ApiResult<()> {
-    let current_dir = std::env::current_dir()?;
-    let base_dir = current_dir.join(STRATEGY_WORKDIR_NAME).canonicalize()?;
-    let full_path = safe_join(&base_dir, &query.path)?;
+pub async fn delete_source(
+    State(state): State<AppState>,
+    Query(query): Query<DeleteSourceQuery>,
+) -> ApiResult<()> {
+    let base_dir = state.strategy_manager.workspace_dir();
+    let full_path = safe_join(base_dir, &query.path)?;
 
     if !full_path.exists() {
         return Err(AppError::NotFound("Path does not exist".to_string()));
@@ -188,11 +273,13 @@ pub struct MoveSourceQuery {
     pub new_path: String,
 }
 
-pub async fn move_source(Query(query): Query<MoveSourceQuery>) -> ApiResult<()> {
-    let current_dir = std::env::current_dir()?;
-    let base_dir = current_dir.join(STRATEGY_WORKDIR_NAME).canonicalize()?;
-    let full_old_path = safe_join(&base_dir, &query.old_path)?;
-    let full_new_path = safe_join(&base_dir, &query.new_path)?;
+pub async fn move_source(
+    State(state): State<AppState>,
+    Query(query): Query<MoveSourceQuery>,
+) -> ApiResult<()> {
+    let base_dir = state.strategy_manager.workspace_dir();
+    let full_old_path = safe_join(base_dir, &query.old_path)?;
+    let full_new_path = safe_join(base_dir, &query.new_path)?;
 
     if !full_old_path.exists() {
         return Err(AppError::NotFound("Path does not exist".to_string()));
@@ -213,3 +300,135 @@ pub async fn move_source(Query(query): Query<MoveSourceQuery>) -> ApiResult<()>
     fs::rename(&full_old_path, &full_new_path).await?;
     Ok(Json(()))
 }
+
+#[derive(Debug, Clone, Deserialize, TS)]
+#[ts(export)]
+pub struct SearchSourceQuery {
+    pub q: String,
+    #[ts(optional)]
+    pub regex: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct SearchSourceMatch {
+    pub path: String,
+    #[ts(optional)]
+    pub line: Option<usize>,
+    #[ts(optional)]
+    pub text: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct SearchSourceResponse {
+    pub matches: Vec<SearchSourceMatch>,
+    pub truncated: bool,
+}
+
+pub async fn search_source(
+    State(state): State<AppState>,
+    Query(query): Query<SearchSourceQuery>,
+) -> ApiResult<SearchSourceResponse> {
+    if query.q.is_empty() {
+        return Err(AppError::BadRequest(
+            "Search query must not be empty".to_string(),
+        ));
+    }
+
+    let pattern = if query.regex.unwrap_or(false) {
+        query.q.clone()
+    } else {
+        regex::escape(&query.q)
+    };
+    let matcher = RegexBuilder::new(&pattern)
+        .size_limit(1024 * 1024)
+        .build()
+        .map_err(|e| AppError::BadRequest(format!("Invalid search pattern: {}", e)))?;
+
+    let base_dir = state.strategy_manager.workspace_dir();
+
+    let mut matches = Vec::new();
+    let truncated =
+        search_directory(base_dir, &state.source_config, &matcher, &mut matches).await?;
+
+    Ok(Json(SearchSourceResponse { matches, truncated }))
+}
+
+async fn search_directory(
+    base_dir: &Path,
+    source_config: &SourceConfig,
+    matcher: &Regex,
+    matches: &mut Vec<SearchSourceMatch>,
+) -> AppResult<bool> {
+    let mut pending = vec![base_dir.to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        let mut read_dir = fs::read_dir(&dir).await?;
+        let mut entries = Vec::new();
+        while let Some(entry) = read_dir.next_entry().await? {
+            entries.push(entry);
+        }
+        entries.sort_by_key(|entry| entry.file_name());
+
+        for entry in entries {
+            let entry_type = entry.file_type().await?;
+            let entry_name = entry.file_name().to_string_lossy().to_string();
+            let entry_path = entry.path();
+
+            if source_config.is_ignored(&entry_name) {
+                continue;
+            }
+
+            if entry_type.is_dir() {
+                pending.push(entry_path);
+                continue;
+            }
+
+            if !entry_type.is_file() {
+                continue;
+            }
+
+            let Ok(relative_path) = entry_path.strip_prefix(base_dir) else {
+                continue;
+            };
+            let relative_path = relative_path.to_string_lossy().to_string();
+
+            if matcher.is_match(&entry_name) {
+                if matches.len() >= MAX_SEARCH_RESULTS {
+                    return Ok(true);
+                }
+                matches.push(SearchSourceMatch {
+                    path: relative_path.clone(),
+                    line: None,
+                    text: None,
+                });
+            }
+
+            if entry.metadata().await?.len() > MAX_SEARCH_FILE_SIZE {
+                continue;
+            }
+
+            let Ok(content) = fs::read_to_string(&entry_path).await else {
+                continue;
+            };
+
+            for (index, line) in content.lines().enumerate() {
+                if !matcher.is_match(line) {
+                    continue;
+                }
+
+                if matches.len() >= MAX_SEARCH_RESULTS {
+                    return Ok(true);
+                }
+                matches.push(SearchSourceMatch {
+                    path: relative_path.clone(),
+                    line: Some(index + 1),
+                    text: Some(line.to_string()),
+                });
+            }
+        }
+    }
+
+    Ok(false)
+}
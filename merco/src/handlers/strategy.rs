@@ -1,5 +1,13 @@
-use crate::{app::AppState, errors::ApiResult};
-use axum::{Json, extract::State};
+use crate::strategy::StrategyMetadata;
+use crate::tasks::StrategyRunner;
+use crate::{
+    app::AppState,
+    errors::{ApiResult, AppError},
+};
+use axum::{
+    Json,
+    extract::{Path, State},
+};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use toml_edit::DocumentMut;
@@ -21,17 +29,94 @@ pub async fn add_strategy(
     Ok(Json(()))
 }
 
+#[derive(Debug, Clone, Deserialize, TS)]
+#[ts(export)]
+pub struct RemoveStrategyRequest {
+    pub name: String,
+}
+
+pub async fn remove_strategy(
+    State(state): State<AppState>,
+    Json(request): Json<RemoveStrategyRequest>,
+) -> ApiResult<()> {
+    let strategy_manager = state.strategy_manager;
+    strategy_manager.remove_strategy(&request.name)?;
+
+    Ok(Json(()))
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+#[ts(export)]
+pub struct ValidateStrategyRequest {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum StrategyValidationStage {
+    Build,
+    Load,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct ValidateStrategyResponse {
+    pub valid: bool,
+    #[ts(optional)]
+    pub stage: Option<StrategyValidationStage>,
+    #[ts(optional)]
+    pub message: Option<String>,
+}
+
+pub async fn validate_strategy(
+    State(state): State<AppState>,
+    Json(request): Json<ValidateStrategyRequest>,
+) -> ApiResult<ValidateStrategyResponse> {
+    let result = match state
+        .strategy_manager
+        .build_strategy(&request.name, false)
+        .await
+    {
+        Ok(lib_path) => {
+            StrategyRunner::validate(
+                &state.strategy_manager,
+                &lib_path,
+                state.backtest_config.run_limits(),
+            )
+            .await
+        }
+        Err(e) => Err(e),
+    };
+
+    let (stage, message) = match result {
+        Ok(()) => {
+            return Ok(Json(ValidateStrategyResponse {
+                valid: true,
+                stage: None,
+                message: None,
+            }));
+        }
+        Err(AppError::Build(message)) => (StrategyValidationStage::Build, message),
+        Err(AppError::Strategy(message)) => (StrategyValidationStage::Load, message),
+        Err(e) => return Err(e),
+    };
+
+    Ok(Json(ValidateStrategyResponse {
+        valid: false,
+        stage: Some(stage),
+        message: Some(message),
+    }))
+}
+
 #[derive(Debug, Clone, Serialize, TS)]
 #[ts(export)]
 pub struct ListStrategiesResponse {
     pub strategies: Vec<String>,
 }
 
-pub async fn list_strategies() -> ApiResult<ListStrategiesResponse> {
-    let current_dir = std::env::current_dir().map_err(|e| {
-        crate::errors::AppError::Internal(format!("Failed to get current dir: {}", e))
-    })?;
-    let workspace_toml_path = current_dir.join("strategies").join("Cargo.toml");
+pub async fn list_strategies(State(state): State<AppState>) -> ApiResult<ListStrategiesResponse> {
+    let workspace_toml_path = state.strategy_manager.workspace_dir().join("Cargo.toml");
     let content = fs::read_to_string(&workspace_toml_path).map_err(|e| {
         crate::errors::AppError::Internal(format!(
             "Failed to read Cargo.toml at {:?}: {}",
@@ -60,3 +145,18 @@ pub async fn list_strategies() -> ApiResult<ListStrategiesResponse> {
 
     Ok(Json(ListStrategiesResponse { strategies }))
 }
+
+pub async fn strategy_metadata(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> ApiResult<StrategyMetadata> {
+    let lib_path = state.strategy_manager.build_strategy(&name, false).await?;
+    let metadata = StrategyRunner::metadata(
+        &state.strategy_manager,
+        &lib_path,
+        state.backtest_config.run_limits(),
+    )
+    .await?;
+
+    Ok(Json(metadata))
+}
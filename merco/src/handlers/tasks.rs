@@ -0,0 +1,803 @@
+use crate::app::AppState;
+use crate::errors::{ApiResult, AppError, AppResult};
+use crate::services::tasks::delete_tasks;
+use crate::tasks::{
+    BacktestTask, FetchAllCandlesTask, FetchCandlesTask, OptimizeTask, TaskStatus, TaskSummary,
+    TaskType, WalkForwardTask,
+};
+use axum::{
+    Json,
+    extract::{
+        Path, Query, State,
+        ws::{Message, WebSocket, WebSocketUpgrade},
+    },
+    response::{Response, sse::Event},
+};
+use chrono::{DateTime, TimeDelta, Utc, serde::ts_milliseconds_option};
+use futures::stream::Stream;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::convert::Infallible;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio::sync::broadcast::{self, error::RecvError};
+use ts_rs::TS;
+use uuid::Uuid;
+
+type TaskMap<T> = RwLock<HashMap<Uuid, Arc<RwLock<T>>>>;
+
+const DEFAULT_TASKS_PER_PAGE: usize = 100;
+const MAX_TASKS_PER_PAGE: usize = 1000;
+
+/// Finished tasks are evicted once older than `max_age_secs` or when more
+/// than `max_finished` of a kind are kept. A zero value disables that limit.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RetentionConfig {
+    pub interval_secs: u64,
+    pub max_age_secs: u64,
+    pub max_finished: usize,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            interval_secs: 60,
+            max_age_secs: 24 * 60 * 60,
+            max_finished: 1000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[ts(export, tag = "type")]
+pub enum TaskEvent {
+    /// Running tasks are left out and arrive with their next update.
+    Snapshot {
+        fetch_candles: Vec<FetchCandlesTask>,
+        fetch_all_candles: Vec<FetchAllCandlesTask>,
+        backtest: Vec<BacktestTask>,
+        optimize: Vec<OptimizeTask>,
+        walk_forward: Vec<WalkForwardTask>,
+    },
+    FetchCandles {
+        task: FetchCandlesTask,
+    },
+    FetchAllCandles {
+        task: FetchAllCandlesTask,
+    },
+    Backtest {
+        task: Box<BacktestTask>,
+    },
+    Optimize {
+        task: OptimizeTask,
+    },
+    WalkForward {
+        task: Box<WalkForwardTask>,
+    },
+    Removed {
+        task_id: Uuid,
+    },
+}
+
+impl TaskEvent {
+    fn task_id(&self) -> Option<Uuid> {
+        match self {
+            TaskEvent::Snapshot { .. } => None,
+            TaskEvent::FetchCandles { task } => Some(task.id),
+            TaskEvent::FetchAllCandles { task } => Some(task.id),
+            TaskEvent::Backtest { task } => Some(task.id),
+            TaskEvent::Optimize { task } => Some(task.id),
+            TaskEvent::WalkForward { task } => Some(task.id),
+            TaskEvent::Removed { task_id } => Some(*task_id),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, TS)]
+#[ts(export)]
+pub struct ClearTasksResponse {
+    pub removed: Vec<Uuid>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum TaskSortField {
+    #[default]
+    CreatedAt,
+    UpdatedAt,
+    CompletedAt,
+}
+
+impl TaskSortField {
+    fn key(self, task: &TaskSummary) -> Option<DateTime<Utc>> {
+        match self {
+            TaskSortField::CreatedAt => Some(task.created_at),
+            TaskSortField::UpdatedAt => Some(task.updated_at),
+            TaskSortField::CompletedAt => task.completed_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+/// Filters on `start` and `end` apply to the task's creation time, both
+/// inclusive.
+#[derive(Debug, Deserialize, TS)]
+#[ts(export)]
+pub struct ListTasksQuery {
+    #[ts(optional)]
+    pub status: Option<TaskStatus>,
+    #[ts(optional)]
+    pub task_type: Option<TaskType>,
+    #[serde(default, with = "ts_milliseconds_option")]
+    #[ts(optional, type = "number")]
+    pub start: Option<DateTime<Utc>>,
+    #[serde(default, with = "ts_milliseconds_option")]
+    #[ts(optional, type = "number")]
+    pub end: Option<DateTime<Utc>>,
+    #[ts(optional)]
+    pub sort: Option<TaskSortField>,
+    #[ts(optional)]
+    pub order: Option<SortOrder>,
+    #[ts(optional)]
+    pub offset: Option<usize>,
+    #[ts(optional)]
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize, TS)]
+#[ts(export)]
+pub struct ListTasksResponse {
+    pub total: usize,
+    pub tasks: Vec<TaskSummary>,
+}
+
+/// A client receives the events of every task until its first `subscribe`,
+/// and from then on only those of the tasks it is subscribed to, even once
+/// it has unsubscribed from all of them. `unsubscribe` before any `subscribe`
+/// has no effect. `cancel` stops pending or running tasks, which then fail
+/// with a cancellation error; ids of unknown or finished tasks are ignored.
+#[derive(Debug, Deserialize, TS)]
+#[serde(tag = "action", rename_all = "snake_case")]
+#[ts(export, tag = "action")]
+pub enum TaskClientMessage {
+    Subscribe { task_ids: Vec<Uuid> },
+    Unsubscribe { task_ids: Vec<Uuid> },
+    Cancel { task_ids: Vec<Uuid> },
+}
+
+/// The tasks a websocket client receives events for.
+#[derive(Debug, Default)]
+struct TaskSubscriptions {
+    // None until the client first subscribes, meaning every task.
+    task_ids: Option<HashSet<Uuid>>,
+}
+
+impl TaskSubscriptions {
+    fn subscribe(&mut self, task_ids: Vec<Uuid>) {
+        self.task_ids.get_or_insert_default().extend(task_ids);
+    }
+
+    fn unsubscribe(&mut self, task_ids: Vec<Uuid>) {
+        if let Some(subscribed) = &mut self.task_ids {
+            for task_id in task_ids {
+                subscribed.remove(&task_id);
+            }
+        }
+    }
+
+    fn contains(&self, task_id: Uuid) -> bool {
+        self.task_ids
+            .as_ref()
+            .is_none_or(|task_ids| task_ids.contains(&task_id))
+    }
+}
+
+// Running tasks hold their write lock while executing and are skipped rather
+// than waited on; their next update is broadcast anyway.
+async fn task_events<T: Serialize>(tasks: &TaskMap<T>) -> Vec<Event> {
+    let mut events = Vec::new();
+    for task in tasks.read().await.values() {
+        let Ok(task) = task.try_read() else {
+            continue;
+        };
+        if let Ok(data) = serde_json::to_string(&*task) {
+            events.push(Event::default().data(data));
+        }
+    }
+
+    events
+}
+
+/// SSE stream of every task of a kind followed by their updates. A client
+/// that falls behind the channel is sent every task again instead of being
+/// disconnected, except running tasks, which are sent with their next update.
+pub async fn stream_task_events<T>(
+    state: &AppState,
+    tasks: &Arc<TaskMap<T>>,
+    mut rx: broadcast::Receiver<T>,
+) -> impl Stream<Item = Result<Event, Infallible>> + use<T>
+where
+    T: Serialize + Clone + Send + Sync + 'static,
+{
+    let initial_events = task_events(tasks).await;
+    let tasks = tasks.clone();
+    let shutdown_token = state.shutdown_token.clone();
+
+    async_stream::stream! {
+        for event in initial_events {
+            yield Ok(event);
+        }
+
+        loop {
+            tokio::select! {
+                _ = shutdown_token.cancelled() => {
+                    break;
+                }
+                result = rx.recv() => {
+                    let task = match result {
+                        Ok(task) => task,
+                        Err(RecvError::Lagged(skipped)) => {
+                            tracing::warn!(
+                                "Task stream lagged behind by {} events, resending tasks",
+                                skipped
+                            );
+                            for event in task_events(&tasks).await {
+                                yield Ok(event);
+                            }
+                            continue;
+                        }
+                        Err(RecvError::Closed) => {
+                            break;
+                        }
+                    };
+
+                    let Ok(data) = serde_json::to_string(&task) else {
+                        continue;
+                    };
+
+                    yield Ok(Event::default().data(data));
+                }
+            }
+        }
+    }
+}
+
+// Returns None when the task is unknown and Some(false) when it is still
+// pending or running. Running tasks hold their write lock while executing.
+async fn remove_task<T>(
+    tasks: &TaskMap<T>,
+    task_id: Uuid,
+    is_finished: fn(&T) -> bool,
+) -> Option<bool> {
+    let mut tasks = tasks.write().await;
+    let finished = tasks
+        .get(&task_id)?
+        .try_read()
+        .is_ok_and(|task| is_finished(&task));
+    if finished {
+        tasks.remove(&task_id);
+    }
+
+    Some(finished)
+}
+
+async fn clear_finished<T>(tasks: &TaskMap<T>, is_finished: fn(&T) -> bool) -> Vec<Uuid> {
+    let mut tasks = tasks.write().await;
+    let task_ids: Vec<Uuid> = tasks
+        .iter()
+        .filter(|(_, task)| task.try_read().is_ok_and(|task| is_finished(&task)))
+        .map(|(task_id, _)| *task_id)
+        .collect();
+    for task_id in &task_ids {
+        tasks.remove(task_id);
+    }
+
+    task_ids
+}
+
+async fn evict_expired<T>(
+    tasks: &TaskMap<T>,
+    finished_at: fn(&T) -> Option<DateTime<Utc>>,
+    config: &RetentionConfig,
+) -> Vec<Uuid> {
+    let cutoff = Utc::now() - TimeDelta::seconds(config.max_age_secs as i64);
+
+    let mut tasks = tasks.write().await;
+    let mut finished: Vec<(Uuid, DateTime<Utc>)> = tasks
+        .iter()
+        .filter_map(|(task_id, task)| {
+            let finished_at = finished_at(&*task.try_read().ok()?)?;
+            Some((*task_id, finished_at))
+        })
+        .collect();
+    finished.sort_by_key(|(_, finished_at)| Reverse(*finished_at));
+
+    let task_ids: Vec<Uuid> = finished
+        .into_iter()
+        .enumerate()
+        .filter(|(index, (_, finished_at))| {
+            (config.max_finished > 0 && *index >= config.max_finished)
+                || (config.max_age_secs > 0 && *finished_at < cutoff)
+        })
+        .map(|(_, (task_id, _))| task_id)
+        .collect();
+    for task_id in &task_ids {
+        tasks.remove(task_id);
+    }
+
+    task_ids
+}
+
+async fn sweep_tasks(state: &AppState, config: &RetentionConfig) -> AppResult<Vec<Uuid>> {
+    let mut removed = evict_expired(
+        &state.fetch_candles_tasks,
+        FetchCandlesTask::finished_at,
+        config,
+    )
+    .await;
+    removed.extend(
+        evict_expired(
+            &state.fetch_all_candles_tasks,
+            FetchAllCandlesTask::finished_at,
+            config,
+        )
+        .await,
+    );
+    removed.extend(evict_expired(&state.backtest_tasks, BacktestTask::finished_at, config).await);
+    removed.extend(evict_expired(&state.optimize_tasks, OptimizeTask::finished_at, config).await);
+    removed.extend(
+        evict_expired(
+            &state.walk_forward_tasks,
+            WalkForwardTask::finished_at,
+            config,
+        )
+        .await,
+    );
+
+    if !removed.is_empty() {
+        delete_tasks(&state.db_pool, &removed).await?;
+        for task_id in &removed {
+            let _ = state.task_removed_tx.send(*task_id);
+        }
+    }
+
+    Ok(removed)
+}
+
+pub fn spawn_task_sweeper(state: AppState, config: RetentionConfig) {
+    if config.interval_secs == 0 {
+        return;
+    }
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(config.interval_secs));
+        loop {
+            tokio::select! {
+                _ = state.shutdown_token.cancelled() => break,
+                _ = interval.tick() => {}
+            }
+
+            match sweep_tasks(&state, &config).await {
+                Ok(removed) if !removed.is_empty() => {
+                    tracing::info!("Evicted {} finished tasks", removed.len());
+                }
+                Ok(_) => {}
+                Err(e) => tracing::error!("Failed to evict finished tasks: {}", e),
+            }
+        }
+    });
+}
+
+/// Keeps `AppState::task_summaries` up to date from the task events, so tasks
+/// can be listed while running without waiting for their lock.
+pub fn spawn_summary_cache(state: AppState) {
+    let mut fetch_candles_rx = state.fetch_candles_event_tx.subscribe();
+    let mut fetch_all_candles_rx = state.fetch_all_candles_event_tx.subscribe();
+    let mut backtest_rx = state.backtest_event_tx.subscribe();
+    let mut optimize_rx = state.optimize_event_tx.subscribe();
+    let mut walk_forward_rx = state.walk_forward_event_tx.subscribe();
+    let mut task_removed_rx = state.task_removed_tx.subscribe();
+
+    tokio::spawn(async move {
+        loop {
+            // A removed task is reported without a summary.
+            let update = tokio::select! {
+                _ = state.shutdown_token.cancelled() => break,
+                result = fetch_candles_rx.recv() => {
+                    result.map(|task| (task.id, Some(task.summary())))
+                }
+                result = fetch_all_candles_rx.recv() => {
+                    result.map(|task| (task.id, Some(task.summary())))
+                }
+                result = backtest_rx.recv() => result.map(|task| (task.id, Some(task.summary()))),
+                result = optimize_rx.recv() => result.map(|task| (task.id, Some(task.summary()))),
+                result = walk_forward_rx.recv() => {
+                    result.map(|task| (task.id, Some(task.summary())))
+                }
+                result = task_removed_rx.recv() => result.map(|task_id| (task_id, None)),
+            };
+
+            match update {
+                Ok((task_id, summary)) => {
+                    if let Ok(mut summaries) = state.task_summaries.lock() {
+                        match summary {
+                            Some(summary) => summaries.insert(task_id, summary),
+                            None => summaries.remove(&task_id),
+                        };
+                    }
+                }
+                // Missed summaries are refreshed by the next event or listing.
+                Err(RecvError::Lagged(_)) => {}
+                Err(RecvError::Closed) => break,
+            }
+        }
+    });
+}
+
+// Running tasks hold their write lock while executing, so they are listed
+// from the cache instead of waited on.
+async fn task_summaries<T>(
+    tasks: &TaskMap<T>,
+    summary: fn(&T) -> TaskSummary,
+    cache: &Mutex<HashMap<Uuid, TaskSummary>>,
+) -> Vec<TaskSummary> {
+    let tasks: Vec<(Uuid, Arc<RwLock<T>>)> = tasks
+        .read()
+        .await
+        .iter()
+        .map(|(task_id, task)| (*task_id, task.clone()))
+        .collect();
+    let Ok(mut cache) = cache.lock() else {
+        return Vec::new();
+    };
+
+    let mut summaries = Vec::new();
+    for (task_id, task) in tasks {
+        match task.try_read() {
+            Ok(task) => {
+                let summary = summary(&task);
+                cache.insert(task_id, summary.clone());
+                summaries.push(summary);
+            }
+            Err(_) => summaries.extend(cache.get(&task_id).cloned()),
+        }
+    }
+
+    summaries
+}
+
+pub async fn list_tasks(
+    State(state): State<AppState>,
+    Query(query): Query<ListTasksQuery>,
+) -> ApiResult<ListTasksResponse> {
+    if let (Some(start), Some(end)) = (query.start, query.end)
+        && start >= end
+    {
+        return Err(AppError::BadRequest(
+            "Start time must be earlier than end time".to_string(),
+        ));
+    }
+
+    let includes = |task_type| query.task_type.is_none_or(|t| t == task_type);
+    let mut tasks = Vec::new();
+    if includes(TaskType::FetchCandles) {
+        tasks.extend(
+            task_summaries(
+                &state.fetch_candles_tasks,
+                FetchCandlesTask::summary,
+                &state.task_summaries,
+            )
+            .await,
+        );
+    }
+    if includes(TaskType::FetchAllCandles) {
+        tasks.extend(
+            task_summaries(
+                &state.fetch_all_candles_tasks,
+                FetchAllCandlesTask::summary,
+                &state.task_summaries,
+            )
+            .await,
+        );
+    }
+    if includes(TaskType::Backtest) {
+        tasks.extend(
+            task_summaries(
+                &state.backtest_tasks,
+                BacktestTask::summary,
+                &state.task_summaries,
+            )
+            .await,
+        );
+    }
+    if includes(TaskType::Optimize) {
+        tasks.extend(
+            task_summaries(
+                &state.optimize_tasks,
+                OptimizeTask::summary,
+                &state.task_summaries,
+            )
+            .await,
+        );
+    }
+    if includes(TaskType::WalkForward) {
+        tasks.extend(
+            task_summaries(
+                &state.walk_forward_tasks,
+                WalkForwardTask::summary,
+                &state.task_summaries,
+            )
+            .await,
+        );
+    }
+
+    tasks.retain(|task| {
+        query.status.is_none_or(|status| task.status == status)
+            && query.start.is_none_or(|start| task.created_at >= start)
+            && query.end.is_none_or(|end| task.created_at <= end)
+    });
+
+    // Ties are broken by id so pages stay stable across requests.
+    let sort = query.sort.unwrap_or_default();
+    tasks.sort_by_key(|task| (sort.key(task), task.id));
+    if query.order.unwrap_or_default() == SortOrder::Desc {
+        tasks.reverse();
+    }
+
+    let total = tasks.len();
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_TASKS_PER_PAGE)
+        .min(MAX_TASKS_PER_PAGE);
+    let tasks = tasks
+        .into_iter()
+        .skip(query.offset.unwrap_or(0))
+        .take(limit)
+        .collect();
+
+    Ok(Json(ListTasksResponse { total, tasks }))
+}
+
+pub async fn delete_task(
+    State(state): State<AppState>,
+    Path(task_id): Path<Uuid>,
+) -> ApiResult<()> {
+    let mut removed = remove_task(
+        &state.fetch_candles_tasks,
+        task_id,
+        FetchCandlesTask::is_finished,
+    )
+    .await;
+    if removed.is_none() {
+        removed = remove_task(
+            &state.fetch_all_candles_tasks,
+            task_id,
+            FetchAllCandlesTask::is_finished,
+        )
+        .await;
+    }
+    if removed.is_none() {
+        removed = remove_task(&state.backtest_tasks, task_id, BacktestTask::is_finished).await;
+    }
+    if removed.is_none() {
+        removed = remove_task(&state.optimize_tasks, task_id, OptimizeTask::is_finished).await;
+    }
+    if removed.is_none() {
+        removed = remove_task(
+            &state.walk_forward_tasks,
+            task_id,
+            WalkForwardTask::is_finished,
+        )
+        .await;
+    }
+
+    match removed {
+        None => Err(AppError::NotFound(format!(
+            "Task with id '{}' not found",
+            task_id
+        ))),
+        Some(false) => Err(AppError::BadRequest(format!(
+            "Task with id '{}' is still running",
+            task_id
+        ))),
+        Some(true) => {
+            delete_tasks(&state.db_pool, &[task_id]).await?;
+            let _ = state.task_removed_tx.send(task_id);
+            Ok(Json(()))
+        }
+    }
+}
+
+pub async fn clear_completed(State(state): State<AppState>) -> ApiResult<ClearTasksResponse> {
+    let mut removed =
+        clear_finished(&state.fetch_candles_tasks, FetchCandlesTask::is_finished).await;
+    removed.extend(
+        clear_finished(
+            &state.fetch_all_candles_tasks,
+            FetchAllCandlesTask::is_finished,
+        )
+        .await,
+    );
+    removed.extend(clear_finished(&state.backtest_tasks, BacktestTask::is_finished).await);
+    removed.extend(clear_finished(&state.optimize_tasks, OptimizeTask::is_finished).await);
+    removed.extend(clear_finished(&state.walk_forward_tasks, WalkForwardTask::is_finished).await);
+
+    delete_tasks(&state.db_pool, &removed).await?;
+    for task_id in &removed {
+        let _ = state.task_removed_tx.send(*task_id);
+    }
+
+    Ok(Json(ClearTasksResponse { removed }))
+}
+
+pub async fn ws_tasks(ws: WebSocketUpgrade, State(state): State<AppState>) -> Response {
+    ws.on_upgrade(move |socket| handle_socket(socket, state))
+}
+
+// Running tasks hold their write lock while executing and are left out rather
+// than waited on; their next update is sent anyway.
+async fn snapshot_tasks<T: Clone>(tasks: &TaskMap<T>) -> Vec<T> {
+    let mut snapshot = Vec::new();
+    for task in tasks.read().await.values() {
+        if let Ok(task) = task.try_read() {
+            snapshot.push(task.clone());
+        }
+    }
+
+    snapshot
+}
+
+async fn snapshot(state: &AppState) -> TaskEvent {
+    TaskEvent::Snapshot {
+        fetch_candles: snapshot_tasks(&state.fetch_candles_tasks).await,
+        fetch_all_candles: snapshot_tasks(&state.fetch_all_candles_tasks).await,
+        backtest: snapshot_tasks(&state.backtest_tasks).await,
+        optimize: snapshot_tasks(&state.optimize_tasks).await,
+        walk_forward: snapshot_tasks(&state.walk_forward_tasks).await,
+    }
+}
+
+async fn send_event(socket: &mut WebSocket, event: &TaskEvent) -> Result<(), axum::Error> {
+    let Ok(data) = serde_json::to_string(event) else {
+        return Ok(());
+    };
+
+    socket.send(Message::Text(data.into())).await
+}
+
+async fn handle_socket(mut socket: WebSocket, state: AppState) {
+    let mut fetch_candles_rx = state.fetch_candles_event_tx.subscribe();
+    let mut fetch_all_candles_rx = state.fetch_all_candles_event_tx.subscribe();
+    let mut backtest_rx = state.backtest_event_tx.subscribe();
+    let mut optimize_rx = state.optimize_event_tx.subscribe();
+    let mut walk_forward_rx = state.walk_forward_event_tx.subscribe();
+    let mut task_removed_rx = state.task_removed_tx.subscribe();
+    let mut subscriptions = TaskSubscriptions::default();
+
+    if send_event(&mut socket, &snapshot(&state).await)
+        .await
+        .is_err()
+    {
+        return;
+    }
+
+    loop {
+        let result = tokio::select! {
+            _ = state.shutdown_token.cancelled() => {
+                break;
+            }
+            message = socket.recv() => {
+                match message {
+                    Some(Ok(Message::Text(text))) => {
+                        match serde_json::from_str::<TaskClientMessage>(&text) {
+                            Ok(TaskClientMessage::Subscribe { task_ids }) => {
+                                subscriptions.subscribe(task_ids);
+                            }
+                            Ok(TaskClientMessage::Unsubscribe { task_ids }) => {
+                                subscriptions.unsubscribe(task_ids);
+                            }
+                            Ok(TaskClientMessage::Cancel { task_ids }) => {
+                                for task_id in task_ids {
+                                    if state.cancel_task(task_id) {
+                                        tracing::info!("Cancelling task {}", task_id);
+                                    }
+                                }
+                            }
+                            Err(e) => {
+                                tracing::warn!("Invalid task websocket message: {}", e);
+                            }
+                        }
+                        continue;
+                    }
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => {
+                        break;
+                    }
+                    Some(Ok(_)) => {
+                        continue;
+                    }
+                }
+            }
+            result = fetch_candles_rx.recv() => result.map(|task| TaskEvent::FetchCandles { task }),
+            result = fetch_all_candles_rx.recv() => {
+                result.map(|task| TaskEvent::FetchAllCandles { task })
+            }
+            result = backtest_rx.recv() => {
+                result.map(|task| TaskEvent::Backtest { task: Box::new(task) })
+            }
+            result = optimize_rx.recv() => result.map(|task| TaskEvent::Optimize { task }),
+            result = walk_forward_rx.recv() => {
+                result.map(|task| TaskEvent::WalkForward { task: Box::new(task) })
+            }
+            result = task_removed_rx.recv() => {
+                result.map(|task_id| TaskEvent::Removed { task_id })
+            }
+        };
+
+        let event = match result {
+            Ok(event) => event,
+            Err(RecvError::Lagged(skipped)) => {
+                tracing::warn!(
+                    "Task websocket lagged behind by {} events, sending snapshot",
+                    skipped
+                );
+                snapshot(&state).await
+            }
+            Err(RecvError::Closed) => {
+                break;
+            }
+        };
+
+        if let Some(task_id) = event.task_id()
+            && !subscriptions.contains(task_id)
+        {
+            continue;
+        }
+
+        if send_event(&mut socket, &event).await.is_err() {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn subscriptions_cover_every_task_until_the_first_subscribe() {
+        let mut subscriptions = TaskSubscriptions::default();
+        let (first, second) = (Uuid::from_u128(1), Uuid::from_u128(2));
+        assert!(subscriptions.contains(first));
+
+        subscriptions.unsubscribe(vec![first]);
+        assert!(subscriptions.contains(first));
+
+        subscriptions.subscribe(vec![first]);
+        assert!(subscriptions.contains(first));
+        assert!(!subscriptions.contains(second));
+    }
+
+    #[test]
+    fn unsubscribing_from_the_last_task_does_not_resubscribe_to_all() {
+        let mut subscriptions = TaskSubscriptions::default();
+        let (first, second) = (Uuid::from_u128(1), Uuid::from_u128(2));
+        subscriptions.subscribe(vec![first]);
+        subscriptions.unsubscribe(vec![first]);
+
+        assert!(!subscriptions.contains(first));
+        assert!(!subscriptions.contains(second));
+    }
+}
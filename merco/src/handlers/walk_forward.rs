@@ -0,0 +1,147 @@
+use crate::app::AppState;
+use crate::errors::{ApiResult, AppError};
+use crate::handlers::tasks::stream_task_events;
+use crate::models::Timeframe;
+use crate::tasks::{
+    OptimizeMetric, OptimizeTask, ParameterRange, WalkForwardStatus, WalkForwardTask,
+};
+use axum::{
+    extract::{Path, State},
+    response::{
+        Json,
+        sse::{Event, KeepAlive, Sse},
+    },
+};
+use chrono::Utc;
+use futures::stream::Stream;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Deserialize, TS)]
+#[ts(export)]
+pub struct CreateWalkForwardTaskRequest {
+    pub name: String,
+    pub exchange: String,
+    pub symbol: String,
+    pub timeframe: Timeframe,
+    pub grid: BTreeMap<String, ParameterRange>,
+    pub metric: OptimizeMetric,
+    /// Candles in each in-sample window.
+    pub in_sample: usize,
+    /// Candles in each out-of-sample window.
+    pub out_of_sample: usize,
+    /// Candles the windows move forward between folds, the out-of-sample
+    /// size by default.
+    #[ts(optional)]
+    pub step: Option<usize>,
+}
+
+#[derive(Debug, Serialize, TS)]
+#[ts(export)]
+pub struct CreateWalkForwardTaskResponse {
+    pub task_id: Uuid,
+}
+
+pub async fn create_task(
+    State(state): State<AppState>,
+    Json(request): Json<CreateWalkForwardTaskRequest>,
+) -> ApiResult<CreateWalkForwardTaskResponse> {
+    OptimizeTask::combinations(&request.grid)?;
+    let step = request.step.unwrap_or(request.out_of_sample);
+    if request.in_sample == 0 || request.out_of_sample == 0 || step == 0 {
+        return Err(AppError::BadRequest(
+            "Walk-forward window sizes and step must be positive".to_string(),
+        ));
+    }
+
+    let now = Utc::now();
+    let task = WalkForwardTask {
+        id: Uuid::new_v4(),
+        status: WalkForwardStatus::Pending,
+        progress: 0.0,
+        name: request.name,
+        exchange: request.exchange,
+        symbol: request.symbol,
+        timeframe: request.timeframe,
+        grid: request.grid,
+        metric: request.metric,
+        in_sample: request.in_sample,
+        out_of_sample: request.out_of_sample,
+        step,
+        total_folds: 0,
+        folds: Vec::new(),
+        summary: None,
+        error_message: None,
+        created_at: now,
+        started_at: None,
+        completed_at: None,
+        updated_at: now,
+        event_tx: Some(state.walk_forward_event_tx.clone()),
+    };
+    task.broadcast();
+
+    let task_id = task.id;
+    let task = Arc::new(RwLock::new(task));
+
+    {
+        let mut tasks = state.walk_forward_tasks.write().await;
+        tasks.insert(task_id, task.clone());
+    }
+
+    state.spawn_task(task_id, "walk_forward", move |state, cancel| async move {
+        let mut task = task.write().await;
+        task.execute(
+            &state.strategy_manager,
+            state.backtest_config.run_limits(),
+            state.db_pool.clone(),
+            &cancel,
+        )
+        .await;
+    });
+
+    Ok(Json(CreateWalkForwardTaskResponse { task_id }))
+}
+
+pub async fn get_all_tasks(State(state): State<AppState>) -> ApiResult<Vec<WalkForwardTask>> {
+    let mut tasks = Vec::new();
+    let walk_forward_tasks = state.walk_forward_tasks.read().await;
+    for task in walk_forward_tasks.values() {
+        let task = task.read().await;
+        tasks.push(task.clone());
+    }
+
+    Ok(Json(tasks))
+}
+
+pub async fn get_task(
+    State(state): State<AppState>,
+    Path(task_id): Path<Uuid>,
+) -> ApiResult<WalkForwardTask> {
+    let walk_forward_tasks = state.walk_forward_tasks.read().await;
+    let task = walk_forward_tasks.get(&task_id);
+
+    match task {
+        Some(task) => {
+            let task = task.read().await;
+            Ok(Json(task.clone()))
+        }
+        _ => Err(AppError::NotFound(format!(
+            "Task with id '{}' is not a Walk-forward task",
+            task_id
+        ))),
+    }
+}
+
+pub async fn stream_tasks(
+    State(state): State<AppState>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let rx = state.walk_forward_event_tx.subscribe();
+    let stream = stream_task_events(&state, &state.walk_forward_tasks, rx).await;
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
@@ -7,6 +7,8 @@ pub mod exchange;
 #[doc(hidden)]
 pub mod handlers;
 #[doc(hidden)]
+pub mod middleware;
+#[doc(hidden)]
 pub mod models;
 #[doc(hidden)]
 pub mod services;
@@ -18,6 +20,11 @@ pub mod tasks;
 pub mod utils;
 
 pub use crate::errors::AppResult;
-pub use crate::models::{Candle, MarketPrecision, Timeframe, TradingFees};
-pub use crate::strategy::{Order, OrderType, Strategy, StrategyContext, Trade, TradeType};
+pub use crate::models::{Candle, MarketLimits, MarketPrecision, Timeframe, TradingFees};
+#[doc(hidden)]
+pub use crate::strategy::{GuardedStrategy, PLUGIN_ABI_VERSION, PluginMetadata};
+pub use crate::strategy::{
+    LogLevel, Order, OrderType, PlotMarker, PlotPoint, Strategy, StrategyContext, StrategyLog,
+    StrategyMetadata, Trade, TradeIntent, TradeType, Trail, TrailingStop,
+};
 pub use strategy_macro::strategy;
@@ -1,8 +1,10 @@
 mod config;
 
-use merco::app::create_app;
+use merco::app::{AppConfig, create_app};
 use merco::errors::{AppError, AppResult};
-use sqlx::postgres::PgPoolOptions;
+use merco::exchange::ccxt::CCXT;
+use merco::tasks::{SANDBOX_WORKER_ARG, run_sandbox_worker};
+use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
 use std::{
     net::{Ipv4Addr, SocketAddrV4},
     str::FromStr,
@@ -12,11 +14,12 @@ use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 #[tokio::main]
 async fn main() -> AppResult<()> {
-    let Ok(config) = config::Config::load() else {
-        return Err(AppError::Internal(
-            "Failed to load configuration".to_string(),
-        ));
-    };
+    if std::env::args().nth(1).as_deref() == Some(SANDBOX_WORKER_ARG) {
+        return run_sandbox_worker();
+    }
+
+    let config = config::Config::load()
+        .map_err(|e| AppError::Internal(format!("Failed to load configuration: {}", e)))?;
 
     tracing_subscriber::registry()
         .with(tracing_subscriber::EnvFilter::new(config.log_level))
@@ -24,17 +27,41 @@ async fn main() -> AppResult<()> {
         .init();
     tracing::info!("Loaded configuration");
 
-    tracing::info!("Connecting to database at {}", config.database.url);
+    CCXT::set_retry_config(config.ccxt_retry.clone());
+    CCXT::set_candle_validation(config.candle_validation);
+    CCXT::set_cache_config(config.ccxt_cache.clone());
+
+    // The URL may carry a password expanded from the secrets file, so only
+    // where it points is logged.
+    let connect_options = PgConnectOptions::from_str(&config.database.url)?;
+    tracing::info!(
+        "Connecting to database {} at {}:{}",
+        connect_options.get_database().unwrap_or_default(),
+        connect_options.get_host(),
+        connect_options.get_port()
+    );
     let db_pool = PgPoolOptions::new()
         .max_connections(config.database.max_connections)
-        .connect(&config.database.url)
+        .connect_with(connect_options)
         .await?;
     tracing::info!("Connected to database");
 
     sqlx::migrate!("./migrations").run(&db_pool).await?;
 
     let token = CancellationToken::new();
-    let app = create_app(db_pool, token.clone()).await?;
+    let app = create_app(
+        db_pool,
+        AppConfig {
+            exchange_credentials: config.exchanges,
+            max_concurrent_tasks: config.max_concurrent_tasks,
+            source: config.source,
+            task_retention: config.task_retention,
+            backtest: config.backtest,
+            strategy: config.strategy,
+        },
+        token.clone(),
+    )
+    .await?;
 
     let Ok(host) = Ipv4Addr::from_str(&config.server.host) else {
         return Err(AppError::Internal(format!(
@@ -1,5 +1,12 @@
 mod candles;
 mod exchange;
+mod export;
+mod funding;
 
 pub use candles::{AvailableCandleInfo, Candle, Timeframe};
-pub use exchange::{MarketPrecision, TradingFees};
+pub use exchange::{
+    Balance, FeeRounding, MarketInfo, MarketLimits, MarketPrecision, OrderBook, OrderBookLevel,
+    Ticker, TradingFees,
+};
+pub use export::ExportFormat;
+pub use funding::FundingRate;
@@ -1,13 +1,13 @@
 use crate::errors::AppError;
 use bigdecimal::BigDecimal;
-use chrono::{DateTime, TimeDelta, Utc, serde::ts_milliseconds};
+use chrono::{DateTime, Datelike, Months, TimeDelta, TimeZone, Utc, serde::ts_milliseconds};
 use core::fmt;
 use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, Type};
 use std::{hash::Hash, str::FromStr};
 use ts_rs::TS;
 
-#[derive(Debug, Clone, Serialize, FromRow, TS)]
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, TS)]
 #[ts(export)]
 pub struct Candle {
     #[serde(with = "ts_milliseconds")]
@@ -28,6 +28,56 @@ pub struct Candle {
     pub volume: BigDecimal,
 }
 
+/// Concise constructors for test fixtures, available to dependent crates
+/// through the `test-util` feature.
+#[cfg(any(test, feature = "test-util"))]
+impl Candle {
+    /// A flat BTC/USDT 1m candle that opens, peaks and closes at `close` with a
+    /// volume of 1.
+    pub fn test(timestamp: DateTime<Utc>, close: impl Into<BigDecimal>) -> Self {
+        let close = close.into();
+        Self {
+            timestamp,
+            exchange: "test".to_string(),
+            symbol: "BTC/USDT".to_string(),
+            timeframe: Timeframe::M1,
+            open: close.clone(),
+            high: close.clone(),
+            low: close.clone(),
+            close,
+            volume: BigDecimal::from(1),
+        }
+    }
+
+    pub fn with_symbol(mut self, symbol: &str) -> Self {
+        self.symbol = symbol.to_string();
+        self
+    }
+
+    pub fn with_timeframe(mut self, timeframe: Timeframe) -> Self {
+        self.timeframe = timeframe;
+        self
+    }
+
+    /// Sets the open, high and low, keeping the close.
+    pub fn with_range(
+        mut self,
+        open: impl Into<BigDecimal>,
+        high: impl Into<BigDecimal>,
+        low: impl Into<BigDecimal>,
+    ) -> Self {
+        self.open = open.into();
+        self.high = high.into();
+        self.low = low.into();
+        self
+    }
+
+    pub fn with_volume(mut self, volume: impl Into<BigDecimal>) -> Self {
+        self.volume = volume.into();
+        self
+    }
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, Type, TS)]
 #[serde(rename_all = "lowercase")]
 #[sqlx(type_name = "text", rename_all = "lowercase")]
@@ -123,6 +173,126 @@ pub enum Timeframe {
 }
 
 impl Timeframe {
+    const ALL: [Timeframe; 22] = [
+        Timeframe::S1,
+        Timeframe::S10,
+        Timeframe::M1,
+        Timeframe::M3,
+        Timeframe::M5,
+        Timeframe::M10,
+        Timeframe::M15,
+        Timeframe::M30,
+        Timeframe::H1,
+        Timeframe::H2,
+        Timeframe::H3,
+        Timeframe::H4,
+        Timeframe::H6,
+        Timeframe::H8,
+        Timeframe::H12,
+        Timeframe::D1,
+        Timeframe::D3,
+        Timeframe::W1,
+        Timeframe::MN1,
+        Timeframe::MN3,
+        Timeframe::MN4,
+        Timeframe::Y1,
+    ];
+
+    pub fn all() -> &'static [Timeframe] {
+        &Self::ALL
+    }
+
+    pub fn from_ms(ms: u64) -> Option<Timeframe> {
+        Self::ALL
+            .into_iter()
+            .find(|timeframe| timeframe.to_ms() == ms)
+    }
+
+    pub fn is_multiple_of(&self, other: &Timeframe) -> bool {
+        match (self.months(), other.months()) {
+            (Some(months), Some(other_months)) => months % other_months == 0,
+            (Some(_), None) => {
+                (TimeDelta::days(1).num_milliseconds() as u64).is_multiple_of(other.to_ms())
+            }
+            (None, Some(_)) => false,
+            (None, None) => {
+                self.to_ms() >= other.to_ms() && self.to_ms().is_multiple_of(other.to_ms())
+            }
+        }
+    }
+
+    fn months(&self) -> Option<u32> {
+        match self {
+            Timeframe::MN1 => Some(1),
+            Timeframe::MN3 => Some(3),
+            Timeframe::MN4 => Some(4),
+            Timeframe::Y1 => Some(12),
+            _ => None,
+        }
+    }
+
+    pub fn bucket_start(&self, timestamp: DateTime<Utc>) -> DateTime<Utc> {
+        if let Some(months) = self.months() {
+            let months = months as i32;
+            let index =
+                (timestamp.year() * 12 + timestamp.month0() as i32).div_euclid(months) * months;
+            return Utc
+                .with_ymd_and_hms(
+                    index.div_euclid(12),
+                    index.rem_euclid(12) as u32 + 1,
+                    1,
+                    0,
+                    0,
+                    0,
+                )
+                .single()
+                .unwrap_or(timestamp);
+        }
+
+        // Weeks start on Monday, and 1970-01-01 was a Thursday.
+        let origin_ms = match self {
+            Timeframe::W1 => TimeDelta::days(4).num_milliseconds(),
+            _ => 0,
+        };
+        let timestamp_ms = timestamp.timestamp_millis();
+        let bucket_ms = timestamp_ms - (timestamp_ms - origin_ms).rem_euclid(self.to_ms() as i64);
+        DateTime::from_timestamp_millis(bucket_ms).unwrap_or(timestamp)
+    }
+
+    pub fn advance(&self, timestamp: DateTime<Utc>) -> DateTime<Utc> {
+        match self.months() {
+            Some(months) => timestamp
+                .checked_add_months(Months::new(months))
+                .unwrap_or(timestamp + self.to_delta()),
+            None => timestamp + self.to_delta(),
+        }
+    }
+
+    pub fn periods_between(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> u64 {
+        if end <= start {
+            return 0;
+        }
+
+        match self.months() {
+            Some(months) => {
+                let elapsed =
+                    (end.year() - start.year()) * 12 + end.month0() as i32 - start.month0() as i32;
+                let mut periods = (elapsed.max(0) as u32).div_ceil(months);
+                if start
+                    .checked_add_months(Months::new(periods * months))
+                    .is_some_and(|boundary| boundary < end)
+                {
+                    periods += 1;
+                }
+                periods as u64
+            }
+            None => {
+                let elapsed_ms = (end - start).num_milliseconds() as u64;
+                elapsed_ms.div_ceil(self.to_ms())
+            }
+        }
+    }
+
     pub fn to_ms(&self) -> u64 {
         self.to_delta().num_milliseconds() as u64
     }
@@ -184,3 +354,127 @@ pub struct AvailableCandleInfo {
     #[ts(type = "number")]
     pub end: DateTime<Utc>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(year: i32, month: u32, day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, month, day, hour, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn month_advances_to_the_last_day_of_shorter_months() {
+        assert_eq!(
+            Timeframe::MN1.advance(at(2024, 1, 31, 0)),
+            at(2024, 2, 29, 0)
+        );
+        assert_eq!(
+            Timeframe::MN1.advance(at(2023, 1, 31, 0)),
+            at(2023, 2, 28, 0)
+        );
+        assert_eq!(Timeframe::MN1.advance(at(2024, 1, 1, 0)), at(2024, 2, 1, 0));
+        assert_eq!(
+            Timeframe::MN1.advance(at(2024, 12, 1, 0)),
+            at(2025, 1, 1, 0)
+        );
+    }
+
+    #[test]
+    fn month_buckets_start_on_the_first() {
+        assert_eq!(
+            Timeframe::MN1.bucket_start(at(2024, 1, 31, 23)),
+            at(2024, 1, 1, 0)
+        );
+        assert_eq!(
+            Timeframe::MN1.bucket_start(at(2024, 2, 29, 12)),
+            at(2024, 2, 1, 0)
+        );
+        assert_eq!(
+            Timeframe::MN1.bucket_start(at(2023, 2, 28, 12)),
+            at(2023, 2, 1, 0)
+        );
+        assert_eq!(
+            Timeframe::MN3.bucket_start(at(2024, 5, 15, 0)),
+            at(2024, 4, 1, 0)
+        );
+        assert_eq!(
+            Timeframe::Y1.bucket_start(at(2024, 12, 31, 23)),
+            at(2024, 1, 1, 0)
+        );
+    }
+
+    #[test]
+    fn week_buckets_start_on_monday() {
+        // 2024-01-01 was a Monday.
+        assert_eq!(
+            Timeframe::W1.bucket_start(at(2024, 1, 3, 12)),
+            at(2024, 1, 1, 0)
+        );
+        assert_eq!(
+            Timeframe::W1.bucket_start(at(2024, 1, 7, 23)),
+            at(2024, 1, 1, 0)
+        );
+        assert_eq!(
+            Timeframe::W1.bucket_start(at(2024, 1, 8, 0)),
+            at(2024, 1, 8, 0)
+        );
+        assert_eq!(
+            Timeframe::W1.bucket_start(at(1970, 1, 1, 0)),
+            at(1969, 12, 29, 0)
+        );
+    }
+
+    #[test]
+    fn monthly_candles_sit_on_their_bucket_starts() {
+        let mut timestamp = at(2023, 11, 1, 0);
+        for _ in 0..6 {
+            let candle = Candle::test(timestamp, 100).with_timeframe(Timeframe::MN1);
+            assert_eq!(
+                candle.timeframe.bucket_start(candle.timestamp),
+                candle.timestamp
+            );
+            assert_eq!(
+                candle
+                    .timeframe
+                    .bucket_start(timestamp + TimeDelta::days(27)),
+                timestamp
+            );
+            timestamp = candle.timeframe.advance(candle.timestamp);
+        }
+        assert_eq!(timestamp, at(2024, 5, 1, 0));
+    }
+
+    #[test]
+    fn periods_between_counts_partial_months() {
+        let month = Timeframe::MN1;
+        assert_eq!(
+            month.periods_between(at(2024, 1, 1, 0), at(2024, 2, 1, 0)),
+            1
+        );
+        assert_eq!(
+            month.periods_between(at(2024, 1, 1, 0), at(2024, 2, 1, 1)),
+            2
+        );
+        assert_eq!(
+            month.periods_between(at(2024, 1, 15, 0), at(2024, 2, 10, 0)),
+            1
+        );
+        assert_eq!(
+            month.periods_between(at(2024, 1, 31, 0), at(2024, 2, 29, 0)),
+            1
+        );
+        assert_eq!(
+            month.periods_between(at(2024, 1, 31, 0), at(2024, 3, 1, 0)),
+            2
+        );
+        assert_eq!(
+            month.periods_between(at(2024, 2, 1, 0), at(2024, 1, 1, 0)),
+            0
+        );
+        assert_eq!(
+            Timeframe::H1.periods_between(at(2024, 1, 31, 23), at(2024, 2, 1, 1)),
+            2
+        );
+    }
+}
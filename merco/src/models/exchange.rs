@@ -1,13 +1,50 @@
+use crate::errors::{AppError, AppResult};
 use bigdecimal::{BigDecimal, RoundingMode, Zero};
+use chrono::{DateTime, Utc, serde::ts_milliseconds_option};
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
 pub struct TradingFees {
+    #[ts(type = "string")]
     pub maker: BigDecimal,
+    #[ts(type = "string")]
     pub taker: BigDecimal,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderBookLevel {
+    pub price: BigDecimal,
+    pub amount: BigDecimal,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OrderBook {
+    pub bids: Vec<OrderBookLevel>,
+    pub asks: Vec<OrderBookLevel>,
+}
+
+impl OrderBook {
+    pub fn mid_price(&self) -> Option<BigDecimal> {
+        let bid = &self.bids.first()?.price;
+        let ask = &self.asks.first()?.price;
+        Some((bid + ask) / BigDecimal::from(2))
+    }
+}
+
+/// Direction fees are rounded in to `fee_precision`. `Up` charges any
+/// fraction of a step to the trader.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum FeeRounding {
+    #[default]
+    Up,
+    Down,
+    HalfEven,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[ts(export)]
 pub struct MarketPrecision {
@@ -15,6 +52,12 @@ pub struct MarketPrecision {
     pub price_precision: BigDecimal,
     #[ts(type = "string")]
     pub amount_precision: BigDecimal,
+    /// Step of the quote currency fees are charged in. Zero disables rounding.
+    #[serde(default)]
+    #[ts(type = "string")]
+    pub fee_precision: BigDecimal,
+    #[serde(default)]
+    pub fee_rounding: FeeRounding,
 }
 
 impl MarketPrecision {
@@ -37,4 +80,101 @@ impl MarketPrecision {
         let floored = divided.with_scale_round(0, mode);
         floored * &self.amount_precision
     }
+
+    pub fn round_fee(&self, value: &BigDecimal) -> BigDecimal {
+        if self.fee_precision.is_zero() {
+            return value.clone();
+        }
+
+        let mode = match self.fee_rounding {
+            FeeRounding::Up => RoundingMode::Up,
+            FeeRounding::Down => RoundingMode::Down,
+            FeeRounding::HalfEven => RoundingMode::HalfEven,
+        };
+        let divided = value / &self.fee_precision;
+        divided.with_scale_round(0, mode) * &self.fee_precision
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct MarketLimits {
+    #[ts(optional, type = "string")]
+    pub min_amount: Option<BigDecimal>,
+    #[ts(optional, type = "string")]
+    pub min_price: Option<BigDecimal>,
+    #[ts(optional, type = "string")]
+    pub min_cost: Option<BigDecimal>,
+}
+
+impl MarketLimits {
+    pub fn check(&self, price: &BigDecimal, amount: &BigDecimal) -> AppResult<()> {
+        if let Some(min_amount) = &self.min_amount
+            && amount < min_amount
+        {
+            return Err(AppError::Strategy(format!(
+                "Order amount {} is below the minimum amount {}",
+                amount, min_amount
+            )));
+        }
+
+        if let Some(min_price) = &self.min_price
+            && price < min_price
+        {
+            return Err(AppError::Strategy(format!(
+                "Order price {} is below the minimum price {}",
+                price, min_price
+            )));
+        }
+
+        if let Some(min_cost) = &self.min_cost {
+            let cost = price * amount;
+            if &cost < min_cost {
+                return Err(AppError::Strategy(format!(
+                    "Order cost {} is below the minimum cost {}",
+                    cost, min_cost
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct MarketInfo {
+    pub symbol: String,
+    pub precision: MarketPrecision,
+    pub fees: TradingFees,
+    pub limits: MarketLimits,
+}
+
+/// Holdings of one currency in an exchange account. Exchanges that do not
+/// report a field leave it empty.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct Balance {
+    pub currency: String,
+    #[ts(optional, type = "string")]
+    pub free: Option<BigDecimal>,
+    #[ts(optional, type = "string")]
+    pub used: Option<BigDecimal>,
+    #[ts(optional, type = "string")]
+    pub total: Option<BigDecimal>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct Ticker {
+    pub symbol: String,
+    #[serde(with = "ts_milliseconds_option")]
+    #[ts(optional, type = "number")]
+    pub timestamp: Option<DateTime<Utc>>,
+    #[ts(optional, type = "string")]
+    pub last: Option<BigDecimal>,
+    #[ts(optional, type = "string")]
+    pub bid: Option<BigDecimal>,
+    #[ts(optional, type = "string")]
+    pub ask: Option<BigDecimal>,
 }
@@ -0,0 +1,17 @@
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Utc, serde::ts_milliseconds};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use ts_rs::TS;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, TS)]
+#[ts(export)]
+pub struct FundingRate {
+    #[serde(with = "ts_milliseconds")]
+    #[ts(type = "number")]
+    pub timestamp: DateTime<Utc>,
+    pub exchange: String,
+    pub symbol: String,
+    #[ts(type = "string")]
+    pub rate: BigDecimal,
+}
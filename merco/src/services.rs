@@ -1,2 +1,4 @@
+pub mod backtests;
 pub mod candles;
+pub mod funding;
 pub mod tasks;
@@ -0,0 +1,68 @@
+use crate::errors::AppResult;
+use crate::models::Timeframe;
+use crate::tasks::BacktestResult;
+use sqlx::PgPool;
+use std::str::FromStr;
+
+pub async fn save_backtest_result(pool: &PgPool, result: &BacktestResult) -> AppResult<()> {
+    let statistic = serde_json::to_value(&result.statistic)?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO backtest_results
+            (id, name, exchange, symbol, timeframe, params, statistic, completed_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        ON CONFLICT (id) DO UPDATE SET
+            statistic = EXCLUDED.statistic,
+            completed_at = EXCLUDED.completed_at
+        "#,
+        result.id,
+        result.name,
+        result.exchange,
+        result.symbol,
+        result.timeframe.to_string(),
+        result.params,
+        statistic,
+        result.completed_at
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn load_backtest_results(
+    pool: &PgPool,
+    name: &str,
+    limit: i64,
+) -> AppResult<Vec<BacktestResult>> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT id, name, exchange, symbol, timeframe, params, statistic, completed_at
+        FROM backtest_results
+        WHERE name = $1
+        ORDER BY completed_at DESC
+        LIMIT $2
+        "#,
+        name,
+        limit
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut results = Vec::new();
+    for row in rows {
+        results.push(BacktestResult {
+            id: row.id,
+            name: row.name,
+            exchange: row.exchange,
+            symbol: row.symbol,
+            timeframe: Timeframe::from_str(&row.timeframe)?,
+            params: row.params,
+            statistic: serde_json::from_value(row.statistic)?,
+            completed_at: row.completed_at,
+        });
+    }
+
+    Ok(results)
+}
@@ -1,18 +1,26 @@
-use crate::errors::AppResult;
-use crate::models::{Candle, Timeframe, AvailableCandleInfo};
-use chrono::{DateTime, Utc};
-use sqlx::PgPool;
+use crate::errors::{AppError, AppResult};
+use crate::models::{AvailableCandleInfo, Candle, Timeframe};
+use chrono::{DateTime, TimeDelta, Utc};
+use futures::{Stream, TryStreamExt};
+use sqlx::{PgPool, Postgres, QueryBuilder};
 
-pub async fn insert_candles(pool: &PgPool, candles: &[Candle]) -> AppResult<()> {
+/// Inserts candles, skipping those already stored, and returns how many were
+/// new.
+pub async fn insert_candles(pool: &PgPool, candles: &[Candle]) -> AppResult<u64> {
     if candles.is_empty() {
-        return Ok(());
+        return Ok(0);
     }
 
-    let mut conn = pool.acquire().await?;
-    let mut copy = conn.copy_in_raw(
-          "COPY candles (timestamp, exchange, symbol, timeframe, open, high, low, close, volume) FROM STDIN WITH (FORMAT
-  csv)"
-      ).await?;
+    let mut tx = pool.begin().await?;
+    sqlx::query("CREATE TEMP TABLE candles_staging (LIKE candles) ON COMMIT DROP")
+        .execute(&mut *tx)
+        .await?;
+
+    let mut copy = tx
+        .copy_in_raw(
+            "COPY candles_staging (timestamp, exchange, symbol, timeframe, open, high, low, close, volume) FROM STDIN WITH (FORMAT csv)",
+        )
+        .await?;
 
     let mut buffer = Vec::new();
     for candle in candles {
@@ -34,18 +42,37 @@ pub async fn insert_candles(pool: &PgPool, candles: &[Candle]) -> AppResult<()>
     copy.send(buffer).await?;
     copy.finish().await?;
 
-    Ok(())
+    let inserted =
+        sqlx::query("INSERT INTO candles SELECT * FROM candles_staging ON CONFLICT DO NOTHING")
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+    tx.commit().await?;
+
+    Ok(inserted)
 }
 
-pub async fn get_candles(
-    pool: &PgPool,
-    exchange: &str,
-    symbol: &str,
+fn candles_query<'a>(
+    exchange: &'a str,
+    symbol: &'a str,
     timeframe: Timeframe,
     start: Option<DateTime<Utc>>,
     end: Option<DateTime<Utc>>,
-) -> AppResult<Vec<Candle>> {
-    let mut query_builder = sqlx::QueryBuilder::new(
+) -> QueryBuilder<'a, Postgres> {
+    let mut query_builder = candles_select(exchange, symbol, timeframe, start, end);
+    query_builder.push(" ORDER BY timestamp ASC");
+
+    query_builder
+}
+
+fn candles_select<'a>(
+    exchange: &'a str,
+    symbol: &'a str,
+    timeframe: Timeframe,
+    start: Option<DateTime<Utc>>,
+    end: Option<DateTime<Utc>>,
+) -> QueryBuilder<'a, Postgres> {
+    let mut query_builder = QueryBuilder::new(
         "SELECT timestamp, exchange, symbol, timeframe, open, high, low, close, volume
            FROM candles
            WHERE exchange = ",
@@ -67,23 +94,275 @@ pub async fn get_candles(
         query_builder.push_bind(e);
     }
 
-    query_builder.push(" ORDER BY timestamp ASC");
+    query_builder
+}
+
+pub async fn get_candles(
+    pool: &PgPool,
+    exchange: &str,
+    symbol: &str,
+    timeframe: Timeframe,
+    start: Option<DateTime<Utc>>,
+    end: Option<DateTime<Utc>>,
+) -> AppResult<Vec<Candle>> {
+    let candles = candles_query(exchange, symbol, timeframe, start, end)
+        .build_query_as::<Candle>()
+        .fetch_all(pool)
+        .await?;
+
+    Ok(candles)
+}
+
+/// Picks up to `count` stored candles of the range at random, in timestamp
+/// order.
+pub async fn sample_candles(
+    pool: &PgPool,
+    exchange: &str,
+    symbol: &str,
+    timeframe: Timeframe,
+    start: Option<DateTime<Utc>>,
+    end: Option<DateTime<Utc>>,
+    count: usize,
+) -> AppResult<Vec<Candle>> {
+    let mut query_builder = candles_select(exchange, symbol, timeframe, start, end);
+    query_builder.push(" ORDER BY random() LIMIT ");
+    query_builder.push_bind(count as i64);
 
-    let candles = query_builder
+    let mut candles = query_builder
         .build_query_as::<Candle>()
         .fetch_all(pool)
         .await?;
+    candles.sort_by_key(|candle| candle.timestamp);
 
     Ok(candles)
 }
 
+pub async fn get_candles_page(
+    pool: &PgPool,
+    exchange: &str,
+    symbol: &str,
+    timeframe: Timeframe,
+    start: Option<DateTime<Utc>>,
+    end: Option<DateTime<Utc>>,
+    limit: usize,
+) -> AppResult<(Vec<Candle>, bool)> {
+    let mut query_builder = candles_query(exchange, symbol, timeframe, start, end);
+    query_builder.push(" LIMIT ");
+    query_builder.push_bind(limit as i64 + 1);
+
+    let mut candles = query_builder
+        .build_query_as::<Candle>()
+        .fetch_all(pool)
+        .await?;
+
+    let has_more = candles.len() > limit;
+    candles.truncate(limit);
+
+    Ok((candles, has_more))
+}
+
+pub fn stream_candles(
+    pool: PgPool,
+    exchange: String,
+    symbol: String,
+    timeframe: Timeframe,
+    start: Option<DateTime<Utc>>,
+    end: Option<DateTime<Utc>>,
+) -> impl Stream<Item = AppResult<Candle>> {
+    async_stream::try_stream! {
+        let mut query_builder = candles_query(&exchange, &symbol, timeframe, start, end);
+        let mut candles = query_builder.build_query_as::<Candle>().fetch(&pool);
+        while let Some(candle) = candles.try_next().await.map_err(AppError::from)? {
+            yield candle;
+        }
+    }
+}
+
+pub async fn delete_candles(
+    pool: &PgPool,
+    exchange: &str,
+    symbol: &str,
+    timeframe: Timeframe,
+    start: Option<DateTime<Utc>>,
+    end: Option<DateTime<Utc>>,
+) -> AppResult<u64> {
+    let mut query_builder = QueryBuilder::new("DELETE FROM candles WHERE exchange = ");
+
+    query_builder.push_bind(exchange);
+    query_builder.push(" AND symbol = ");
+    query_builder.push_bind(symbol);
+    query_builder.push(" AND timeframe = ");
+    query_builder.push_bind(timeframe);
+
+    if let Some(s) = start {
+        query_builder.push(" AND timestamp >= ");
+        query_builder.push_bind(s);
+    }
+
+    if let Some(e) = end {
+        query_builder.push(" AND timestamp <= ");
+        query_builder.push_bind(e);
+    }
+
+    let result = query_builder.build().execute(pool).await?;
+
+    Ok(result.rows_affected())
+}
+
+fn check_resample(source: Timeframe, target: Timeframe) -> AppResult<()> {
+    if !target.is_multiple_of(&source) {
+        return Err(AppError::BadRequest(format!(
+            "Timeframe {} is not an integer multiple of {}",
+            target, source
+        )));
+    }
+
+    Ok(())
+}
+
+/// Merges a candle into the current bucket, returning the previous bucket
+/// once the candle starts a new one.
+fn merge_into_bucket(
+    bucket: &mut Option<Candle>,
+    candle: &Candle,
+    target: Timeframe,
+) -> Option<Candle> {
+    let start = target.bucket_start(candle.timestamp);
+    match bucket {
+        Some(current) if current.timestamp == start => {
+            if candle.high > current.high {
+                current.high = candle.high.clone();
+            }
+            if candle.low < current.low {
+                current.low = candle.low.clone();
+            }
+            current.close = candle.close.clone();
+            current.volume += &candle.volume;
+            None
+        }
+        _ => bucket.replace(Candle {
+            timestamp: start,
+            timeframe: target,
+            ..candle.clone()
+        }),
+    }
+}
+
+pub fn downsample_timeframe(
+    source: Timeframe,
+    minimum: Timeframe,
+    span: TimeDelta,
+    max_points: usize,
+) -> Timeframe {
+    let span_ms = span.num_milliseconds().max(0) as u64;
+    let candidates: Vec<Timeframe> = Timeframe::all()
+        .iter()
+        .copied()
+        .filter(|timeframe| {
+            timeframe.to_ms() >= minimum.to_ms() && timeframe.is_multiple_of(&source)
+        })
+        .collect();
+
+    candidates
+        .iter()
+        .copied()
+        .find(|timeframe| span_ms / timeframe.to_ms() < max_points as u64)
+        .or_else(|| candidates.last().copied())
+        .unwrap_or(minimum)
+}
+
+pub async fn get_candles_span(
+    pool: &PgPool,
+    exchange: &str,
+    symbol: &str,
+    timeframe: Timeframe,
+    start: Option<DateTime<Utc>>,
+    end: Option<DateTime<Utc>>,
+) -> AppResult<Option<(DateTime<Utc>, DateTime<Utc>)>> {
+    let mut query_builder =
+        QueryBuilder::new("SELECT MIN(timestamp), MAX(timestamp) FROM candles WHERE exchange = ");
+
+    query_builder.push_bind(exchange);
+    query_builder.push(" AND symbol = ");
+    query_builder.push_bind(symbol);
+    query_builder.push(" AND timeframe = ");
+    query_builder.push_bind(timeframe);
+
+    if let Some(s) = start {
+        query_builder.push(" AND timestamp >= ");
+        query_builder.push_bind(s);
+    }
+
+    if let Some(e) = end {
+        query_builder.push(" AND timestamp <= ");
+        query_builder.push_bind(e);
+    }
+
+    let (first, last) = query_builder
+        .build_query_as::<(Option<DateTime<Utc>>, Option<DateTime<Utc>>)>()
+        .fetch_one(pool)
+        .await?;
+
+    Ok(first.zip(last))
+}
+
+pub async fn get_resampled_candles(
+    pool: &PgPool,
+    exchange: &str,
+    symbol: &str,
+    source: Timeframe,
+    target: Timeframe,
+    start: Option<DateTime<Utc>>,
+    end: Option<DateTime<Utc>>,
+) -> AppResult<Vec<Candle>> {
+    stream_resampled_candles(
+        pool.clone(),
+        exchange.to_string(),
+        symbol.to_string(),
+        source,
+        target,
+        start,
+        end,
+    )
+    .try_collect()
+    .await
+}
+
+/// Streams source candles resampled into `target` buckets, each yielded once
+/// the next one starts or the candles run out, so only one bucket is held.
+pub fn stream_resampled_candles(
+    pool: PgPool,
+    exchange: String,
+    symbol: String,
+    source: Timeframe,
+    target: Timeframe,
+    start: Option<DateTime<Utc>>,
+    end: Option<DateTime<Utc>>,
+) -> impl Stream<Item = AppResult<Candle>> {
+    async_stream::try_stream! {
+        check_resample(source, target)?;
+
+        let candles = stream_candles(pool, exchange, symbol, source, start, end);
+        let mut candles = std::pin::pin!(candles);
+        let mut bucket = None;
+        while let Some(candle) = candles.try_next().await? {
+            if let Some(completed) = merge_into_bucket(&mut bucket, &candle, target) {
+                yield completed;
+            }
+        }
+        if let Some(last) = bucket {
+            yield last;
+        }
+    }
+}
+
 pub async fn get_latest_candle(
     pool: &PgPool,
     exchange: &str,
     symbol: &str,
     timeframe: Timeframe,
 ) -> AppResult<Option<Candle>> {
-    let mut query_builder = sqlx::QueryBuilder::new(
+    let mut query_builder = QueryBuilder::new(
         "SELECT timestamp, exchange, symbol, timeframe, open, high, low, close, volume
            FROM candles
            WHERE exchange = ",
@@ -127,3 +406,35 @@ pub async fn get_available_candles(pool: &PgPool) -> AppResult<Vec<AvailableCand
 
     Ok(result)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bigdecimal::BigDecimal;
+
+    #[test]
+    fn buckets_are_completed_when_the_next_one_starts() {
+        // 20 minutes past the hour, so the first bucket is partial.
+        let start = DateTime::from_timestamp(1_700_000_400, 0).unwrap();
+        let candles: Vec<Candle> = (0..100)
+            .map(|minute| Candle::test(start + TimeDelta::minutes(minute), minute))
+            .collect();
+
+        let mut bucket = None;
+        let completed: Vec<Candle> = candles
+            .iter()
+            .filter_map(|candle| merge_into_bucket(&mut bucket, candle, Timeframe::H1))
+            .collect();
+
+        assert_eq!(completed.len(), 1);
+        assert_eq!(completed[0].volume, BigDecimal::from(40));
+        assert_eq!(completed[0].close, BigDecimal::from(39));
+        let bucket = bucket.unwrap();
+        assert_eq!(
+            bucket.timestamp,
+            Timeframe::H1.advance(completed[0].timestamp)
+        );
+        assert_eq!(bucket.volume, BigDecimal::from(60));
+        assert_eq!(bucket.close, BigDecimal::from(99));
+    }
+}
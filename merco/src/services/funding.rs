@@ -0,0 +1,65 @@
+use crate::errors::AppResult;
+use crate::models::FundingRate;
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, QueryBuilder};
+
+pub async fn insert_funding_rates(pool: &PgPool, rates: &[FundingRate]) -> AppResult<u64> {
+    if rates.is_empty() {
+        return Ok(0);
+    }
+
+    let mut query_builder =
+        QueryBuilder::new("INSERT INTO funding_rates (timestamp, exchange, symbol, rate) ");
+    query_builder.push_values(rates, |mut row, rate| {
+        row.push_bind(rate.timestamp)
+            .push_bind(&rate.exchange)
+            .push_bind(&rate.symbol)
+            .push_bind(&rate.rate);
+    });
+    query_builder.push(" ON CONFLICT DO NOTHING");
+
+    let result = query_builder.build().execute(pool).await?;
+
+    Ok(result.rows_affected())
+}
+
+pub async fn get_funding_rates(
+    pool: &PgPool,
+    exchange: &str,
+    symbol: &str,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> AppResult<Vec<FundingRate>> {
+    let rates = sqlx::query_as::<_, FundingRate>(
+        "SELECT timestamp, exchange, symbol, rate
+           FROM funding_rates
+           WHERE exchange = $1 AND symbol = $2 AND timestamp >= $3 AND timestamp <= $4
+           ORDER BY timestamp ASC",
+    )
+    .bind(exchange)
+    .bind(symbol)
+    .bind(start)
+    .bind(end)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rates)
+}
+
+pub async fn get_funding_rate_range(
+    pool: &PgPool,
+    exchange: &str,
+    symbol: &str,
+) -> AppResult<Option<(DateTime<Utc>, DateTime<Utc>)>> {
+    let range: (Option<DateTime<Utc>>, Option<DateTime<Utc>>) = sqlx::query_as(
+        "SELECT MIN(timestamp), MAX(timestamp)
+           FROM funding_rates
+           WHERE exchange = $1 AND symbol = $2",
+    )
+    .bind(exchange)
+    .bind(symbol)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(range.0.zip(range.1))
+}
@@ -1,6 +1,9 @@
 use crate::errors::AppResult;
-use crate::tasks::{BacktestTask, FetchCandlesTask};
+use crate::tasks::{
+    BacktestTask, FetchAllCandlesTask, FetchCandlesTask, OptimizeTask, WalkForwardTask,
+};
 use sqlx::PgPool;
+use uuid::Uuid;
 
 pub async fn save_fetch_candles_task(pool: &PgPool, task: &FetchCandlesTask) -> AppResult<()> {
     let data = serde_json::to_value(task)?;
@@ -44,6 +47,51 @@ pub async fn load_fetch_candles_tasks(pool: &PgPool) -> AppResult<Vec<FetchCandl
     Ok(tasks)
 }
 
+pub async fn save_fetch_all_candles_task(
+    pool: &PgPool,
+    task: &FetchAllCandlesTask,
+) -> AppResult<()> {
+    let data = serde_json::to_value(task)?;
+    let completed_at = task.completed_at.ok_or("Task not completed yet")?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO fetch_all_candles_tasks (id, data, completed_at)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (id) DO UPDATE SET
+            data = EXCLUDED.data,
+            completed_at = EXCLUDED.completed_at
+        "#,
+        task.id,
+        data,
+        completed_at
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn load_fetch_all_candles_tasks(pool: &PgPool) -> AppResult<Vec<FetchAllCandlesTask>> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT data
+        FROM fetch_all_candles_tasks
+        ORDER BY completed_at DESC
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut tasks = Vec::new();
+    for row in rows {
+        let task = serde_json::from_value(row.data)?;
+        tasks.push(task);
+    }
+
+    Ok(tasks)
+}
+
 pub async fn save_backtest_task(pool: &PgPool, task: &BacktestTask) -> AppResult<()> {
     let data = serde_json::to_value(task)?;
     let completed_at = task.completed_at.ok_or("Task not completed yet")?;
@@ -85,3 +133,120 @@ pub async fn load_backtest_tasks(pool: &PgPool) -> AppResult<Vec<BacktestTask>>
 
     Ok(tasks)
 }
+
+pub async fn save_optimize_task(pool: &PgPool, task: &OptimizeTask) -> AppResult<()> {
+    let data = serde_json::to_value(task)?;
+    let completed_at = task.completed_at.ok_or("Task not completed yet")?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO optimize_tasks (id, data, completed_at)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (id) DO UPDATE SET
+            data = EXCLUDED.data,
+            completed_at = EXCLUDED.completed_at
+        "#,
+        task.id,
+        data,
+        completed_at
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn load_optimize_tasks(pool: &PgPool) -> AppResult<Vec<OptimizeTask>> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT data
+        FROM optimize_tasks
+        ORDER BY completed_at DESC
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut tasks = Vec::new();
+    for row in rows {
+        let task = serde_json::from_value(row.data)?;
+        tasks.push(task);
+    }
+
+    Ok(tasks)
+}
+
+pub async fn save_walk_forward_task(pool: &PgPool, task: &WalkForwardTask) -> AppResult<()> {
+    let data = serde_json::to_value(task)?;
+    let completed_at = task.completed_at.ok_or("Task not completed yet")?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO walk_forward_tasks (id, data, completed_at)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (id) DO UPDATE SET
+            data = EXCLUDED.data,
+            completed_at = EXCLUDED.completed_at
+        "#,
+        task.id,
+        data,
+        completed_at
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn load_walk_forward_tasks(pool: &PgPool) -> AppResult<Vec<WalkForwardTask>> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT data
+        FROM walk_forward_tasks
+        ORDER BY completed_at DESC
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut tasks = Vec::new();
+    for row in rows {
+        let task = serde_json::from_value(row.data)?;
+        tasks.push(task);
+    }
+
+    Ok(tasks)
+}
+
+pub async fn delete_tasks(pool: &PgPool, task_ids: &[Uuid]) -> AppResult<()> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query!(
+        "DELETE FROM fetch_candles_tasks WHERE id = ANY($1)",
+        task_ids
+    )
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query!(
+        "DELETE FROM fetch_all_candles_tasks WHERE id = ANY($1)",
+        task_ids
+    )
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query!("DELETE FROM backtest_tasks WHERE id = ANY($1)", task_ids)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query!("DELETE FROM optimize_tasks WHERE id = ANY($1)", task_ids)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query!(
+        "DELETE FROM walk_forward_tasks WHERE id = ANY($1)",
+        task_ids
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
@@ -2,11 +2,127 @@ mod context;
 mod handle;
 mod manager;
 
-use crate::errors::AppResult;
-pub use context::{Order, OrderType, StrategyContext, Trade, TradeType};
-pub use handle::StrategyHandle;
-pub use manager::{STRATEGY_WORKDIR_NAME, StrategyManager};
+use crate::errors::{AppError, AppResult};
+pub use context::{
+    ExecutionTiming, FundingPayment, LogLevel, Order, OrderIds, OrderType, PlotMarker, PlotPoint,
+    Slippage, StrategyContext, StrategyLog, Trade, TradeIntent, TradeType, Trail, TrailingStop,
+};
+pub use handle::{LibrarySnapshot, StrategyHandle};
+pub use manager::{STRATEGY_WORKDIR_NAME, SandboxConfig, StrategyConfig, StrategyManager};
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use ts_rs::TS;
+
+/// Identifies the `Strategy` layout a plugin was compiled against, derived from
+/// the merco version so that plugins must be rebuilt after an upgrade.
+pub const PLUGIN_ABI_VERSION: u32 = parse_version(env!("CARGO_PKG_VERSION_MAJOR")) * 1_000_000
+    + parse_version(env!("CARGO_PKG_VERSION_MINOR")) * 1_000
+    + parse_version(env!("CARGO_PKG_VERSION_PATCH"));
+
+const fn parse_version(component: &str) -> u32 {
+    let bytes = component.as_bytes();
+    let mut value = 0;
+    let mut index = 0;
+    while index < bytes.len() {
+        value = value * 10 + (bytes[index] - b'0') as u32;
+        index += 1;
+    }
+    value
+}
+
+/// Descriptive metadata exported by a plugin through `#[strategy]`.
+#[repr(C)]
+pub struct PluginMetadata {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub author: &'static str,
+    pub version: &'static str,
+}
+
+/// A copy of a plugin's metadata that outlives the loaded library.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct StrategyMetadata {
+    pub name: String,
+    pub description: String,
+    pub author: String,
+    pub version: String,
+}
+
+impl From<&PluginMetadata> for StrategyMetadata {
+    fn from(metadata: &PluginMetadata) -> Self {
+        Self {
+            name: metadata.name.to_string(),
+            description: metadata.description.to_string(),
+            author: metadata.author.to_string(),
+            version: metadata.version.to_string(),
+        }
+    }
+}
 
 pub trait Strategy: Send {
     fn tick(&mut self, context: &mut StrategyContext) -> AppResult<()>;
+
+    fn configure(&mut self, _params: &str) -> AppResult<()> {
+        Ok(())
+    }
+
+    fn on_start(&mut self, _context: &mut StrategyContext) -> AppResult<()> {
+        Ok(())
+    }
+
+    fn on_finish(&mut self, _context: &mut StrategyContext) -> AppResult<()> {
+        Ok(())
+    }
+}
+
+pub struct GuardedStrategy<S>(pub S);
+
+impl<S: Strategy> Strategy for GuardedStrategy<S> {
+    fn tick(&mut self, context: &mut StrategyContext) -> AppResult<()> {
+        catch_unwind(AssertUnwindSafe(|| self.0.tick(context))).unwrap_or_else(|payload| {
+            Err(AppError::Strategy(format!(
+                "Strategy panicked: {}",
+                panic_message(payload.as_ref())
+            )))
+        })
+    }
+
+    fn configure(&mut self, params: &str) -> AppResult<()> {
+        catch_unwind(AssertUnwindSafe(|| self.0.configure(params))).unwrap_or_else(|payload| {
+            Err(AppError::Strategy(format!(
+                "Strategy panicked while configuring: {}",
+                panic_message(payload.as_ref())
+            )))
+        })
+    }
+
+    fn on_start(&mut self, context: &mut StrategyContext) -> AppResult<()> {
+        catch_unwind(AssertUnwindSafe(|| self.0.on_start(context))).unwrap_or_else(|payload| {
+            Err(AppError::Strategy(format!(
+                "Strategy panicked while starting: {}",
+                panic_message(payload.as_ref())
+            )))
+        })
+    }
+
+    fn on_finish(&mut self, context: &mut StrategyContext) -> AppResult<()> {
+        catch_unwind(AssertUnwindSafe(|| self.0.on_finish(context))).unwrap_or_else(|payload| {
+            Err(AppError::Strategy(format!(
+                "Strategy panicked while finishing: {}",
+                panic_message(payload.as_ref())
+            )))
+        })
+    }
+}
+
+pub(crate) fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
 }
@@ -1,11 +1,19 @@
 use crate::errors::{AppError, AppResult};
-use crate::models::{Candle, MarketPrecision, TradingFees};
+use crate::models::{
+    Candle, FundingRate, MarketLimits, MarketPrecision, OrderBook, Timeframe, TradingFees,
+};
 use bigdecimal::{BigDecimal, RoundingMode, Zero};
 use chrono::{DateTime, Utc, serde::ts_milliseconds};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
 use ts_rs::TS;
 use uuid::Uuid;
 
+const MAX_STRATEGY_LOGS: usize = 100_000;
+const MAX_PLOT_SERIES: usize = 32;
+const MAX_PLOT_MARKERS: usize = 100_000;
+
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[serde(rename_all = "snake_case")]
 #[ts(export)]
@@ -14,6 +22,33 @@ pub enum TradeType {
     MarketSell,
     LimitBuy,
     LimitSell,
+    TrailingStopSell,
+}
+
+impl TradeType {
+    /// Whether the trade bought the base currency.
+    pub fn is_buy(&self) -> bool {
+        matches!(self, Self::MarketBuy | Self::LimitBuy)
+    }
+}
+
+/// What a trade did to the position: buys open or add to a long and sells
+/// close it, while a short is opened by selling and closed by buying to cover.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum TradeIntent {
+    OpenLong,
+    CloseLong,
+    OpenShort,
+    CloseShort,
+}
+
+impl fmt::Display for TradeType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let json = serde_json::to_string(self).map_err(|_| fmt::Error)?;
+        write!(f, "{}", json.trim_matches('"'))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
@@ -22,7 +57,13 @@ pub struct Trade {
     #[serde(with = "ts_milliseconds")]
     #[ts(type = "number")]
     pub timestamp: DateTime<Utc>,
+    #[serde(default)]
+    pub symbol: String,
     pub trade_type: TradeType,
+    /// Missing on trades recorded before intents were tracked.
+    #[serde(default)]
+    #[ts(optional)]
+    pub intent: Option<TradeIntent>,
     #[ts(type = "string")]
     pub price: BigDecimal,
     #[ts(type = "string")]
@@ -33,182 +74,1132 @@ pub struct Trade {
     pub profit: Option<BigDecimal>,
 }
 
+/// A funding charge on a perpetual swap position. Positive amounts are paid,
+/// negative amounts are received.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct FundingPayment {
+    #[serde(with = "ts_milliseconds")]
+    #[ts(type = "number")]
+    pub timestamp: DateTime<Utc>,
+    pub symbol: String,
+    #[ts(type = "string")]
+    pub rate: BigDecimal,
+    #[ts(type = "string")]
+    pub amount: BigDecimal,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum LogLevel {
+    Info,
+    Signal,
+    Debug,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct StrategyLog {
+    #[serde(with = "ts_milliseconds")]
+    #[ts(type = "number")]
+    pub timestamp: DateTime<Utc>,
+    pub level: LogLevel,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct PlotPoint {
+    #[serde(with = "ts_milliseconds")]
+    #[ts(type = "number")]
+    pub timestamp: DateTime<Utc>,
+    #[ts(type = "string")]
+    pub value: BigDecimal,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct PlotMarker {
+    #[serde(with = "ts_milliseconds")]
+    #[ts(type = "number")]
+    pub timestamp: DateTime<Utc>,
+    pub label: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[serde(rename_all = "snake_case")]
 #[ts(export)]
 pub enum OrderType {
     LimitBuy,
     LimitSell,
+    TrailingStopSell,
+}
+
+impl OrderType {
+    /// Whether the order buys the base currency when it fills.
+    pub fn is_buy(&self) -> bool {
+        matches!(self, Self::LimitBuy)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Trail {
+    Absolute(BigDecimal),
+    Percent(BigDecimal),
+}
+
+impl Trail {
+    fn stop_price(&self, high_water: &BigDecimal) -> BigDecimal {
+        match self {
+            Trail::Absolute(amount) => high_water - amount,
+            Trail::Percent(percent) => high_water - high_water * percent / BigDecimal::from(100),
+        }
+    }
+}
+
+/// When the strategy acts relative to the candle it sees. With `Close`, `tick`
+/// sees the current candle's close and market orders fill at it, which assumes
+/// the order could be placed at the very price that closed the candle. With
+/// `NextOpen`, `tick` sees only closed candles and orders fill from the open of
+/// the following candle.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum ExecutionTiming {
+    #[default]
+    Close,
+    NextOpen,
+}
+
+/// Where the IDs of resting orders come from.
+#[derive(Debug, Clone, Default)]
+pub enum OrderIds {
+    #[default]
+    Random,
+    /// Counts up from the given value, so runs over identical data assign
+    /// identical IDs.
+    Sequential(u128),
+}
+
+impl OrderIds {
+    fn next(&mut self) -> Uuid {
+        match self {
+            Self::Random => Uuid::new_v4(),
+            Self::Sequential(next) => {
+                let id = Uuid::from_u128(*next);
+                *next = next.wrapping_add(1);
+                id
+            }
+        }
+    }
+}
+
+/// How market orders move the fill price away from the candle close.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub enum Slippage {
+    #[default]
+    None,
+    /// Moves the price by `factor * amount / candle.volume` of the close.
+    VolumeImpact { factor: BigDecimal },
+    /// Walks an order book snapshot whose prices are scaled so its mid price
+    /// matches the candle close. Size beyond the book fills at its worst level.
+    /// The snapshot is live data, so runs using it are not reproducible.
+    OrderBook(OrderBook),
+}
+
+#[derive(Debug, Clone)]
+pub struct TrailingStop {
+    pub trail: Trail,
+    pub high_water: BigDecimal,
 }
 
 #[derive(Debug, Clone)]
 pub struct Order {
     pub id: Uuid,
+    pub symbol: String,
     pub order_type: OrderType,
     pub price: BigDecimal,
     pub amount: BigDecimal,
+    /// Fee reserved for the fill. Resting limit orders reserve the maker fee,
+    /// even if a candle gaps through their price; trailing stops reserve
+    /// nothing and pay the taker fee when they execute.
     pub fee: BigDecimal,
+    pub cost_basis: BigDecimal,
+    pub trailing: Option<TrailingStop>,
 }
 
 #[derive(Debug, Clone)]
-pub struct StrategyContext<'a> {
+pub(crate) struct MarketState<'a> {
     pub(crate) candles: &'a [Candle],
-    pub(crate) balance: BigDecimal,
     pub(crate) position: BigDecimal,
-    pub(crate) trades: Vec<Trade>,
-    pub(crate) orders: Vec<Order>,
+    pub(crate) cost_basis: BigDecimal,
     pub(crate) fees: TradingFees,
     pub(crate) precision: MarketPrecision,
+    pub(crate) limits: MarketLimits,
+    pub(crate) slippage: Slippage,
+    /// Share of each candle's volume a limit order may fill, with the rest
+    /// resting. Limit orders fill completely when unset.
+    pub(crate) limit_fill_ratio: Option<BigDecimal>,
+    /// Share of the last closed candle's volume a market order may trade.
+    /// Market orders are not limited when unset.
+    pub(crate) market_volume_ratio: Option<BigDecimal>,
+    /// Funding rates charged against the position, for perpetual swaps.
+    pub(crate) funding_rates: &'a [FundingRate],
+    pub(crate) funding_offset: usize,
+    /// Closed candles of additional timeframes.
+    pub(crate) timeframe_candles: HashMap<Timeframe, &'a [Candle]>,
+    pub(crate) timing: ExecutionTiming,
 }
 
-impl StrategyContext<'_> {
+impl<'a> MarketState<'a> {
+    fn new(fees: TradingFees, precision: MarketPrecision, limits: MarketLimits) -> Self {
+        Self {
+            candles: &[],
+            position: BigDecimal::zero(),
+            cost_basis: BigDecimal::zero(),
+            fees,
+            precision,
+            limits,
+            slippage: Slippage::None,
+            limit_fill_ratio: None,
+            market_volume_ratio: None,
+            funding_rates: &[],
+            funding_offset: 0,
+            timeframe_candles: HashMap::new(),
+            timing: ExecutionTiming::Close,
+        }
+    }
+
+    fn candle(&self) -> AppResult<Candle> {
+        self.candles
+            .last()
+            .cloned()
+            .ok_or(AppError::Strategy("No candles available".into()))
+    }
+
+    /// Candles visible to the strategy, leaving out the candle orders are
+    /// filled on under `ExecutionTiming::NextOpen`.
+    fn closed_candles(&self) -> &'a [Candle] {
+        match self.timing {
+            ExecutionTiming::Close => self.candles,
+            ExecutionTiming::NextOpen => &self.candles[..self.candles.len().saturating_sub(1)],
+        }
+    }
+
+    /// Price market orders trade at before slippage.
+    fn price(&self) -> AppResult<BigDecimal> {
+        let candle = self.candle()?;
+        Ok(match self.timing {
+            ExecutionTiming::Close => candle.close,
+            ExecutionTiming::NextOpen => candle.open,
+        })
+    }
+
+    fn fill_price(&self, is_buy: bool, amount: &BigDecimal) -> AppResult<BigDecimal> {
+        let base_price = self.price()?;
+        let price = match &self.slippage {
+            Slippage::None => return Ok(base_price),
+            Slippage::VolumeImpact { factor } => {
+                let Some(volume) = self
+                    .closed_candles()
+                    .last()
+                    .map(|candle| &candle.volume)
+                    .filter(|volume| !volume.is_zero())
+                else {
+                    return Ok(base_price);
+                };
+
+                let impact = factor * amount / volume;
+                if is_buy {
+                    &base_price * (BigDecimal::from(1) + impact)
+                } else {
+                    &base_price * (BigDecimal::from(1) - impact)
+                }
+            }
+            Slippage::OrderBook(book) => {
+                let Some(mid_price) = book.mid_price().filter(|price| !price.is_zero()) else {
+                    return Ok(base_price);
+                };
+                if amount.is_zero() {
+                    return Ok(base_price);
+                }
+
+                let levels = if is_buy { &book.asks } else { &book.bids };
+                let mut remaining = amount.clone();
+                let mut cost = BigDecimal::zero();
+                let mut worst_price = mid_price.clone();
+                for level in levels {
+                    if remaining <= BigDecimal::zero() {
+                        break;
+                    }
+
+                    let filled = if level.amount < remaining {
+                        level.amount.clone()
+                    } else {
+                        remaining.clone()
+                    };
+                    cost += &filled * &level.price;
+                    remaining -= &filled;
+                    worst_price = level.price.clone();
+                }
+                if remaining > BigDecimal::zero() {
+                    cost += &remaining * &worst_price;
+                }
+
+                &base_price * (cost / amount) / mid_price
+            }
+        };
+
+        let mode = if is_buy {
+            RoundingMode::Up
+        } else {
+            RoundingMode::Down
+        };
+        Ok(self
+            .precision
+            .round_price(&price.max(BigDecimal::zero()), mode))
+    }
+
+    // Returns the rounded taker fee and the total cost of a market buy.
+    fn buy_total(&self, price: &BigDecimal, amount: &BigDecimal) -> (BigDecimal, BigDecimal) {
+        let cost = price * amount;
+        let fee = self.precision.round_fee(&(&cost * &self.fees.taker));
+        let total = cost + &fee;
+        (fee, total)
+    }
+
+    fn affordable_amount(&self, budget: &BigDecimal) -> AppResult<BigDecimal> {
+        let price = self.price()?;
+        if price <= BigDecimal::zero() {
+            return Err(AppError::Strategy("Price must be positive".into()));
+        }
+
+        let fee_rate = BigDecimal::from(1) + &self.fees.taker;
+        let estimate = self
+            .precision
+            .round_amount(&(budget / (&price * &fee_rate)), RoundingMode::Down);
+        let price = self.fill_price(true, &estimate)?;
+        let mut amount = self
+            .precision
+            .round_amount(&(budget / (&price * &fee_rate)), RoundingMode::Down);
+
+        let (_, total) = self.buy_total(&self.fill_price(true, &amount)?, &amount);
+        if &total > budget {
+            amount -= &self.precision.amount_precision;
+        }
+
+        Ok(amount.max(BigDecimal::zero()))
+    }
+
+    fn check_volume(&self, amount: &BigDecimal) -> AppResult<()> {
+        let (Some(ratio), Some(candle)) = (&self.market_volume_ratio, self.closed_candles().last())
+        else {
+            return Ok(());
+        };
+
+        let available = self
+            .precision
+            .round_amount(&(&candle.volume * ratio), RoundingMode::Down);
+        if amount > &available {
+            return Err(AppError::Strategy(format!(
+                "Order amount exceeds candle volume, at most {} can be traded",
+                available
+            )));
+        }
+        Ok(())
+    }
+
+    fn fill_amount(&self, order: &Order, candle: &Candle) -> BigDecimal {
+        let Some(ratio) = &self.limit_fill_ratio else {
+            return order.amount.clone();
+        };
+        if matches!(order.order_type, OrderType::TrailingStopSell) {
+            return order.amount.clone();
+        }
+
+        let available = self
+            .precision
+            .round_amount(&(&candle.volume * ratio), RoundingMode::Down);
+        available.min(order.amount.clone())
+    }
+
+    fn release_cost_basis(&mut self, amount: &BigDecimal) -> BigDecimal {
+        if self.position.is_zero() {
+            return BigDecimal::zero();
+        }
+
+        let released = if amount >= &self.position {
+            self.cost_basis.clone()
+        } else {
+            &self.cost_basis * amount / &self.position
+        };
+        self.cost_basis -= &released;
+
+        released
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StrategyContext<'a> {
+    pub(crate) exchange: String,
+    pub(crate) symbol: String,
+    pub(crate) timeframe: Timeframe,
+    /// Ordered by symbol so that per-market work such as funding runs in the
+    /// same order on every run.
+    pub(crate) markets: BTreeMap<String, MarketState<'a>>,
+    pub(crate) balance: BigDecimal,
+    pub(crate) trades: Vec<Trade>,
+    pub(crate) funding_payments: Vec<FundingPayment>,
+    pub(crate) orders: Vec<Order>,
+    pub(crate) logs: Vec<StrategyLog>,
+    pub(crate) plots: BTreeMap<String, Vec<PlotPoint>>,
+    pub(crate) markers: Vec<PlotMarker>,
+    pub(crate) order_ids: OrderIds,
+}
+
+impl<'a> StrategyContext<'a> {
     pub(crate) fn new(
         balance: BigDecimal,
+        exchange: &str,
+        symbol: &str,
+        timeframe: Timeframe,
         fees: TradingFees,
         precision: MarketPrecision,
+        limits: MarketLimits,
     ) -> AppResult<Self> {
+        let mut markets = BTreeMap::new();
+        markets.insert(
+            symbol.to_string(),
+            MarketState::new(fees, precision, limits),
+        );
+
         Ok(Self {
-            candles: &[],
+            exchange: exchange.to_string(),
+            symbol: symbol.to_string(),
+            timeframe,
+            markets,
             balance,
-            position: BigDecimal::zero(),
             trades: Vec::new(),
+            funding_payments: Vec::new(),
             orders: Vec::new(),
-            fees,
-            precision,
+            logs: Vec::new(),
+            plots: BTreeMap::new(),
+            markers: Vec::new(),
+            order_ids: OrderIds::default(),
         })
     }
 
-    pub(crate) fn before(&mut self) -> AppResult<()> {
-        let candle = self.candle()?;
-        let mut orders_to_execute = Vec::new();
+    pub(crate) fn add_market(
+        &mut self,
+        symbol: &str,
+        fees: TradingFees,
+        precision: MarketPrecision,
+        limits: MarketLimits,
+    ) {
+        self.markets.insert(
+            symbol.to_string(),
+            MarketState::new(fees, precision, limits),
+        );
+    }
+
+    pub(crate) fn set_slippage(&mut self, symbol: &str, slippage: Slippage) -> AppResult<()> {
+        self.market_mut(symbol)?.slippage = slippage;
+        Ok(())
+    }
+
+    pub(crate) fn set_limit_fill_ratio(
+        &mut self,
+        symbol: &str,
+        ratio: Option<BigDecimal>,
+    ) -> AppResult<()> {
+        self.market_mut(symbol)?.limit_fill_ratio = ratio;
+        Ok(())
+    }
+
+    pub(crate) fn set_market_volume_ratio(
+        &mut self,
+        symbol: &str,
+        ratio: Option<BigDecimal>,
+    ) -> AppResult<()> {
+        self.market_mut(symbol)?.market_volume_ratio = ratio;
+        Ok(())
+    }
+
+    pub(crate) fn set_funding_rates(
+        &mut self,
+        symbol: &str,
+        rates: &'a [FundingRate],
+    ) -> AppResult<()> {
+        let market = self.market_mut(symbol)?;
+        market.funding_rates = rates;
+        market.funding_offset = 0;
+        Ok(())
+    }
+
+    pub(crate) fn set_candles(&mut self, symbol: &str, candles: &'a [Candle]) -> AppResult<()> {
+        self.market_mut(symbol)?.candles = candles;
+        Ok(())
+    }
+
+    pub(crate) fn set_order_ids(&mut self, order_ids: OrderIds) {
+        self.order_ids = order_ids;
+    }
+
+    pub(crate) fn set_execution_timing(&mut self, timing: ExecutionTiming) {
+        for market in self.markets.values_mut() {
+            market.timing = timing;
+        }
+    }
+
+    pub(crate) fn set_timeframe_candles(
+        &mut self,
+        symbol: &str,
+        timeframe: Timeframe,
+        candles: &'a [Candle],
+    ) -> AppResult<()> {
+        self.market_mut(symbol)?
+            .timeframe_candles
+            .insert(timeframe, candles);
+        Ok(())
+    }
+
+    pub(crate) fn before(&mut self) -> AppResult<()> {
+        self.apply_funding();
+
+        match self.market(&self.symbol)?.timing {
+            ExecutionTiming::Close => self.match_orders(),
+            ExecutionTiming::NextOpen => Ok(()),
+        }
+    }
+
+    /// Fills resting orders the latest candle trades through.
+    fn match_orders(&mut self) -> AppResult<()> {
+        let timestamp = self.market(&self.symbol)?.candle()?.timestamp;
+        let mut orders_to_execute = Vec::new();
+        for order in &mut self.orders {
+            let market = &self.markets[&order.symbol];
+            let Some(candle) = market.candles.last() else {
+                continue;
+            };
+
+            if candle.timestamp < timestamp {
+                continue;
+            }
+
+            let filled = match order.order_type {
+                OrderType::LimitBuy => order.price >= candle.low,
+                OrderType::LimitSell => order.price <= candle.high,
+                OrderType::TrailingStopSell => order.price >= candle.low,
+            };
+            if filled {
+                let amount = market.fill_amount(order, candle);
+                if amount > BigDecimal::zero() {
+                    orders_to_execute.push((candle.clone(), order.clone(), amount));
+                }
+            } else if let Some(trailing) = &mut order.trailing
+                && candle.high > trailing.high_water
+            {
+                trailing.high_water = candle.high.clone();
+                order.price = market.precision.round_price(
+                    &trailing.trail.stop_price(&trailing.high_water),
+                    RoundingMode::Down,
+                );
+            }
+        }
+
+        for (candle, order, amount) in orders_to_execute {
+            let order = if amount < order.amount {
+                // Fill a proportional share of the reserved fee and cost basis,
+                // leaving the remainder of the order resting.
+                let fill = Order {
+                    fee: &order.fee * &amount / &order.amount,
+                    cost_basis: &order.cost_basis * &amount / &order.amount,
+                    amount,
+                    ..order
+                };
+                if let Some(resting) = self.orders.iter_mut().find(|o| o.id == fill.id) {
+                    resting.amount -= &fill.amount;
+                    resting.fee -= &fill.fee;
+                    resting.cost_basis -= &fill.cost_basis;
+                }
+                fill
+            } else {
+                self.orders.retain(|o| o.id != order.id);
+                order
+            };
+
+            match order.order_type {
+                OrderType::LimitBuy => {
+                    self.execute_limit_buy(&candle, &order);
+                }
+                OrderType::LimitSell => {
+                    self.execute_limit_sell(&candle, &order);
+                }
+                OrderType::TrailingStopSell => {
+                    self.execute_trailing_stop_sell(&candle, &order);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Charges every funding interval reached by the latest candle against the
+    /// balance, including amounts reserved by resting sell orders.
+    fn apply_funding(&mut self) {
+        for (symbol, market) in &mut self.markets {
+            let Some(candle) = market.candles.last() else {
+                continue;
+            };
+
+            let mut held = market.position.clone();
+            for order in &self.orders {
+                if &order.symbol == symbol
+                    && matches!(
+                        order.order_type,
+                        OrderType::LimitSell | OrderType::TrailingStopSell
+                    )
+                {
+                    held += &order.amount;
+                }
+            }
+
+            while let Some(rate) = market.funding_rates.get(market.funding_offset)
+                && rate.timestamp <= candle.timestamp
+            {
+                market.funding_offset += 1;
+                if held.is_zero() {
+                    continue;
+                }
+
+                let amount = &held * &candle.open * &rate.rate;
+                self.balance -= &amount;
+                self.funding_payments.push(FundingPayment {
+                    timestamp: rate.timestamp,
+                    symbol: symbol.clone(),
+                    rate: rate.rate.clone(),
+                    amount,
+                });
+            }
+        }
+    }
+
+    pub(crate) fn after(&mut self) -> AppResult<()> {
+        // Orders placed at the open of the latest candle may fill within it.
+        match self.market(&self.symbol)?.timing {
+            ExecutionTiming::Close => Ok(()),
+            ExecutionTiming::NextOpen => self.match_orders(),
+        }
+    }
+
+    pub(crate) fn end(&mut self) -> AppResult<()> {
+        let order_ids: Vec<Uuid> = self.orders.iter().map(|o| o.id).collect();
+        for id in order_ids {
+            self.cancel_order(id);
+        }
+        Ok(())
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.candles()
+            .last()
+            .map(|candle| candle.timestamp)
+            .unwrap_or_default()
+    }
+
+    fn market(&self, symbol: &str) -> AppResult<&MarketState<'a>> {
+        self.markets
+            .get(symbol)
+            .ok_or_else(|| AppError::Strategy(format!("Unknown symbol '{}'", symbol)))
+    }
+
+    fn market_mut(&mut self, symbol: &str) -> AppResult<&mut MarketState<'a>> {
+        self.markets
+            .get_mut(symbol)
+            .ok_or_else(|| AppError::Strategy(format!("Unknown symbol '{}'", symbol)))
+    }
+
+    pub fn exchange(&self) -> &str {
+        &self.exchange
+    }
+
+    pub fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    pub fn timeframe(&self) -> Timeframe {
+        self.timeframe
+    }
+
+    pub fn symbols(&self) -> Vec<&str> {
+        self.markets.keys().map(String::as_str).collect()
+    }
+
+    pub fn candles(&self) -> &[Candle] {
+        self.candles_for(&self.symbol)
+    }
+
+    pub fn candles_for(&self, symbol: &str) -> &[Candle] {
+        self.markets
+            .get(symbol)
+            .map(|market| market.closed_candles())
+            .unwrap_or(&[])
+    }
+
+    /// Candles of another timeframe loaded for the backtest, limited to those
+    /// closed by the time the current candle closes.
+    pub fn candles_tf(&self, timeframe: Timeframe) -> &[Candle] {
+        self.candles_tf_for(&self.symbol, timeframe)
+    }
+
+    pub fn candles_tf_for(&self, symbol: &str, timeframe: Timeframe) -> &[Candle] {
+        if timeframe == self.timeframe {
+            return self.candles_for(symbol);
+        }
+
+        self.markets
+            .get(symbol)
+            .and_then(|market| market.timeframe_candles.get(&timeframe).copied())
+            .unwrap_or(&[])
+    }
+
+    /// The last `n` candles, or all of them if fewer are available.
+    pub fn window(&self, n: usize) -> &[Candle] {
+        self.window_for(&self.symbol, n)
+    }
+
+    pub fn window_for(&self, symbol: &str, n: usize) -> &[Candle] {
+        let candles = self.candles_for(symbol);
+        &candles[candles.len().saturating_sub(n)..]
+    }
+
+    /// Close prices of the last `n` candles, oldest first.
+    pub fn closes(&self, n: usize) -> Vec<BigDecimal> {
+        self.closes_for(&self.symbol, n)
+    }
+
+    pub fn closes_for(&self, symbol: &str, n: usize) -> Vec<BigDecimal> {
+        self.window_values(symbol, n, |candle| &candle.close)
+    }
+
+    pub fn highs(&self, n: usize) -> Vec<BigDecimal> {
+        self.highs_for(&self.symbol, n)
+    }
+
+    pub fn highs_for(&self, symbol: &str, n: usize) -> Vec<BigDecimal> {
+        self.window_values(symbol, n, |candle| &candle.high)
+    }
+
+    pub fn lows(&self, n: usize) -> Vec<BigDecimal> {
+        self.lows_for(&self.symbol, n)
+    }
+
+    pub fn lows_for(&self, symbol: &str, n: usize) -> Vec<BigDecimal> {
+        self.window_values(symbol, n, |candle| &candle.low)
+    }
+
+    fn window_values(
+        &self,
+        symbol: &str,
+        n: usize,
+        value: impl Fn(&Candle) -> &BigDecimal,
+    ) -> Vec<BigDecimal> {
+        self.window_for(symbol, n)
+            .iter()
+            .map(|candle| value(candle).clone())
+            .collect()
+    }
+
+    /// Zero-based index of the current candle, counting warmup candles, so a
+    /// schedule stays aligned to the data regardless of where ticking starts.
+    pub fn candle_index(&self) -> usize {
+        self.candles().len().saturating_sub(1)
+    }
+
+    /// Whether the current candle falls on every `n`th candle of the series.
+    pub fn every(&self, n: usize) -> bool {
+        n > 0 && self.candle_index().is_multiple_of(n)
+    }
+
+    pub fn candle(&self) -> AppResult<Candle> {
+        self.candle_for(&self.symbol)
+    }
+
+    pub fn candle_for(&self, symbol: &str) -> AppResult<Candle> {
+        self.market(symbol)?
+            .closed_candles()
+            .last()
+            .cloned()
+            .ok_or(AppError::Strategy("No candles available".into()))
+    }
+
+    pub fn balance(&self) -> BigDecimal {
+        self.balance.clone()
+    }
+
+    pub fn position(&self) -> BigDecimal {
+        self.position_for(&self.symbol)
+    }
+
+    pub fn position_for(&self, symbol: &str) -> BigDecimal {
+        self.markets
+            .get(symbol)
+            .map(|market| market.position.clone())
+            .unwrap_or_else(BigDecimal::zero)
+    }
+
+    pub fn unrealized_pnl(&self) -> BigDecimal {
+        self.unrealized_pnl_for(&self.symbol)
+    }
+
+    pub fn unrealized_pnl_for(&self, symbol: &str) -> BigDecimal {
+        let Some(market) = self.markets.get(symbol) else {
+            return BigDecimal::zero();
+        };
+        let Ok(price) = market.price() else {
+            return BigDecimal::zero();
+        };
+
+        let mut amount = market.position.clone();
+        let mut cost_basis = market.cost_basis.clone();
+        for order in &self.orders {
+            if order.symbol == symbol
+                && let OrderType::LimitSell | OrderType::TrailingStopSell = order.order_type
+            {
+                amount += &order.amount;
+                cost_basis += &order.cost_basis;
+            }
+        }
+
+        &amount * &price - &cost_basis
+    }
+
+    pub fn trades(&self) -> &[Trade] {
+        &self.trades
+    }
+
+    pub fn funding_payments(&self) -> &[FundingPayment] {
+        &self.funding_payments
+    }
+
+    pub fn orders(&self) -> &[Order] {
+        &self.orders
+    }
+
+    pub fn logs(&self) -> &[StrategyLog] {
+        &self.logs
+    }
+
+    pub fn plots(&self) -> &BTreeMap<String, Vec<PlotPoint>> {
+        &self.plots
+    }
+
+    pub fn markers(&self) -> &[PlotMarker] {
+        &self.markers
+    }
+
+    pub fn plot(&mut self, series_name: &str, value: BigDecimal) {
+        let timestamp = self.timestamp();
+        if !self.plots.contains_key(series_name) && self.plots.len() >= MAX_PLOT_SERIES {
+            return;
+        }
+
+        let series = self.plots.entry(series_name.to_string()).or_default();
+        match series.last_mut() {
+            Some(point) if point.timestamp == timestamp => point.value = value,
+            _ => series.push(PlotPoint { timestamp, value }),
+        }
+    }
+
+    pub fn marker(&mut self, label: &str) {
+        if self.markers.len() >= MAX_PLOT_MARKERS {
+            return;
+        }
+
+        let timestamp = self.timestamp();
+        self.markers.push(PlotMarker {
+            timestamp,
+            label: label.to_string(),
+        });
+    }
+
+    pub fn log(&mut self, message: impl Into<String>) {
+        self.log_with_level(LogLevel::Info, message);
+    }
+
+    pub fn log_with_level(&mut self, level: LogLevel, message: impl Into<String>) {
+        if self.logs.len() >= MAX_STRATEGY_LOGS {
+            return;
+        }
+
+        let timestamp = self.timestamp();
+        self.logs.push(StrategyLog {
+            timestamp,
+            level,
+            message: message.into(),
+        });
+    }
+
+    pub fn precision(&self) -> &MarketPrecision {
+        &self.markets[&self.symbol].precision
+    }
+
+    pub fn precision_for(&self, symbol: &str) -> AppResult<&MarketPrecision> {
+        Ok(&self.market(symbol)?.precision)
+    }
+
+    /// Rounds an order amount down to the market's amount precision, as the
+    /// order methods do before executing.
+    pub fn round_amount(&self, amount: &BigDecimal) -> BigDecimal {
+        self.precision().round_amount(amount, RoundingMode::Down)
+    }
+
+    pub fn round_amount_for(&self, symbol: &str, amount: &BigDecimal) -> AppResult<BigDecimal> {
+        Ok(self
+            .precision_for(symbol)?
+            .round_amount(amount, RoundingMode::Down))
+    }
 
-        for order in &self.orders {
-            match order.order_type {
-                OrderType::LimitBuy => {
-                    if order.price >= candle.low {
-                        orders_to_execute.push((
-                            order.id,
-                            OrderType::LimitBuy,
-                            order.price.clone(),
-                            order.amount.clone(),
-                            order.fee.clone(),
-                        ));
-                    }
-                }
-                OrderType::LimitSell => {
-                    if order.price <= candle.high {
-                        orders_to_execute.push((
-                            order.id,
-                            OrderType::LimitSell,
-                            order.price.clone(),
-                            order.amount.clone(),
-                            order.fee.clone(),
-                        ));
-                    }
-                }
-            }
-        }
+    /// Rounds a limit price down to the market's price precision, as the order
+    /// methods do before placing it.
+    pub fn round_price(&self, price: &BigDecimal) -> BigDecimal {
+        self.precision().round_price(price, RoundingMode::Down)
+    }
 
-        for (order_id, order_type, price, amount, fee) in orders_to_execute {
-            match order_type {
-                OrderType::LimitBuy => {
-                    self.execute_limit_buy(&candle, &price, &amount, &fee);
-                }
-                OrderType::LimitSell => {
-                    self.execute_limit_sell(&candle, &price, &amount, &fee);
-                }
-            }
-            self.orders.retain(|o| o.id != order_id);
-        }
+    pub fn round_price_for(&self, symbol: &str, price: &BigDecimal) -> AppResult<BigDecimal> {
+        Ok(self
+            .precision_for(symbol)?
+            .round_price(price, RoundingMode::Down))
+    }
 
-        Ok(())
+    pub fn limits(&self) -> &MarketLimits {
+        &self.markets[&self.symbol].limits
     }
 
-    pub(crate) fn after(&mut self) -> AppResult<()> {
-        Ok(())
+    pub fn limits_for(&self, symbol: &str) -> AppResult<&MarketLimits> {
+        Ok(&self.market(symbol)?.limits)
     }
 
-    pub(crate) fn end(&mut self) -> AppResult<()> {
-        let order_ids: Vec<Uuid> = self.orders.iter().map(|o| o.id).collect();
-        for id in order_ids {
-            self.cancel_order(id);
+    pub fn cancel_order(&mut self, order_id: Uuid) {
+        if let Some(pos) = self.orders.iter().position(|o| o.id == order_id) {
+            let order = self.orders.remove(pos);
+            self.refund_order(order);
         }
-        Ok(())
     }
 
-    pub fn candles(&self) -> &[Candle] {
-        &self.candles
+    /// Cancels every pending order the predicate matches, returning how many
+    /// were cancelled.
+    pub fn cancel_orders_where(&mut self, predicate: impl Fn(&Order) -> bool) -> usize {
+        let cancelled: Vec<Order> = self.orders.extract_if(.., |o| predicate(o)).collect();
+        let count = cancelled.len();
+        for order in cancelled {
+            self.refund_order(order);
+        }
+        count
     }
 
-    pub fn candle(&self) -> AppResult<Candle> {
-        self.candles
-            .last()
-            .cloned()
-            .ok_or(AppError::Strategy("No candles available".into()))
+    pub fn cancel_all_orders(&mut self) -> usize {
+        self.cancel_orders_where(|_| true)
     }
 
-    pub fn balance(&self) -> BigDecimal {
-        self.balance.clone()
+    pub fn cancel_buys(&mut self) -> usize {
+        let symbol = self.symbol.clone();
+        self.cancel_buys_for(&symbol)
     }
 
-    pub fn position(&self) -> BigDecimal {
-        self.position.clone()
+    pub fn cancel_buys_for(&mut self, symbol: &str) -> usize {
+        self.cancel_orders_where(|o| o.symbol == symbol && o.order_type.is_buy())
     }
 
-    pub fn trades(&self) -> &[Trade] {
-        &self.trades
+    pub fn cancel_sells(&mut self) -> usize {
+        let symbol = self.symbol.clone();
+        self.cancel_sells_for(&symbol)
     }
 
-    pub fn orders(&self) -> &[Order] {
-        &self.orders
+    pub fn cancel_sells_for(&mut self, symbol: &str) -> usize {
+        self.cancel_orders_where(|o| o.symbol == symbol && !o.order_type.is_buy())
     }
 
-    pub fn precision(&self) -> &MarketPrecision {
-        &self.precision
+    /// Returns what a removed order reserved: the quote cost of a buy, or the
+    /// position and cost basis of a sell, plus the reserved fee.
+    fn refund_order(&mut self, order: Order) {
+        match order.order_type {
+            OrderType::LimitBuy => {
+                let refund = &order.price * &order.amount + &order.fee;
+                self.balance += &refund;
+            }
+            OrderType::LimitSell | OrderType::TrailingStopSell => {
+                if let Some(market) = self.markets.get_mut(&order.symbol) {
+                    market.position += &order.amount;
+                    market.cost_basis += &order.cost_basis;
+                }
+                self.balance += &order.fee;
+            }
+        }
     }
 
-    pub fn cancel_order(&mut self, order_id: Uuid) {
-        if let Some(pos) = self.orders.iter().position(|o| o.id == order_id) {
-            let order = &self.orders[pos];
-            match order.order_type {
-                OrderType::LimitBuy => {
-                    let refund = &order.price * &order.amount + &order.fee;
-                    self.balance += &refund;
+    pub fn modify_order(
+        &mut self,
+        order_id: Uuid,
+        price: &BigDecimal,
+        amount: &BigDecimal,
+    ) -> AppResult<()> {
+        let Some(index) = self.orders.iter().position(|o| o.id == order_id) else {
+            return Err(AppError::Strategy(format!(
+                "Order '{}' not found",
+                order_id
+            )));
+        };
+        let order = self.orders[index].clone();
+
+        let market = self.market(&order.symbol)?;
+        let price = market.precision.round_price(price, RoundingMode::Down);
+        let amount = market.precision.round_amount(amount, RoundingMode::Down);
+
+        if amount <= BigDecimal::zero() {
+            return Err(AppError::Strategy("Amount must be positive".into()));
+        }
+        market.limits.check(&price, &amount)?;
+
+        // Like a new limit order, one moved across the market price is
+        // executed at once instead of resting.
+        let market_price = market.price()?;
+        let crosses = match order.order_type {
+            OrderType::LimitBuy => price >= market_price,
+            OrderType::LimitSell => price <= market_price,
+            OrderType::TrailingStopSell => false,
+        };
+        if crosses {
+            return self.execute_modified_order(index, &amount);
+        }
+
+        let value = &price * &amount;
+        let fee = market.precision.round_fee(&(&value * &market.fees.maker));
+
+        let cost_basis = match order.order_type {
+            OrderType::LimitBuy => {
+                let reserved = &order.price * &order.amount + &order.fee;
+                let total = &value + &fee;
+
+                if total > &self.balance + &reserved {
+                    return Err(AppError::Strategy("Insufficient funds".into()));
                 }
-                OrderType::LimitSell => {
-                    self.position += &order.amount;
-                    self.balance += &order.fee;
+
+                self.balance += &reserved - &total;
+                BigDecimal::zero()
+            }
+            OrderType::LimitSell => {
+                if amount > &market.position + &order.amount {
+                    return Err(AppError::Strategy(
+                        "Insufficient base asset amount to sell".into(),
+                    ));
+                }
+
+                if fee > &self.balance + &order.fee {
+                    return Err(AppError::Strategy("Insufficient funds to cover fee".into()));
                 }
+
+                let market = self.market_mut(&order.symbol)?;
+                market.position += &order.amount;
+                market.cost_basis += &order.cost_basis;
+                let cost_basis = market.release_cost_basis(&amount);
+                market.position -= &amount;
+                self.balance += &order.fee - &fee;
+                cost_basis
+            }
+            OrderType::TrailingStopSell => {
+                return Err(AppError::Strategy(
+                    "Trailing stop orders cannot be modified".into(),
+                ));
+            }
+        };
+
+        let order = &mut self.orders[index];
+        order.price = price;
+        order.amount = amount;
+        order.fee = fee;
+        order.cost_basis = cost_basis;
+
+        Ok(())
+    }
+
+    /// Replaces a pending limit order with a market order for `amount`. The
+    /// order is left as it was if the market order fails.
+    fn execute_modified_order(&mut self, index: usize, amount: &BigDecimal) -> AppResult<()> {
+        let order = self.orders[index].clone();
+        let market = self.market(&order.symbol)?;
+        let (position, cost_basis) = (market.position.clone(), market.cost_basis.clone());
+        let balance = self.balance.clone();
+
+        self.orders.remove(index);
+        self.refund_order(order.clone());
+        let result = if order.order_type.is_buy() {
+            self.market_buy_for(&order.symbol, amount)
+        } else {
+            self.market_sell_for(&order.symbol, amount)
+        };
+
+        if result.is_err() {
+            if let Some(market) = self.markets.get_mut(&order.symbol) {
+                market.position = position;
+                market.cost_basis = cost_basis;
             }
-            self.orders.remove(pos);
+            self.balance = balance;
+            self.orders.insert(index, order);
         }
+        result
     }
 
     pub fn market_buy(&mut self, amount: &BigDecimal) -> AppResult<()> {
-        let amount = self.precision.round_amount(amount, RoundingMode::Down);
+        let symbol = self.symbol.clone();
+        self.market_buy_for(&symbol, amount)
+    }
+
+    pub fn market_buy_for(&mut self, symbol: &str, amount: &BigDecimal) -> AppResult<()> {
+        let balance = self.balance.clone();
+        let market = self.market_mut(symbol)?;
+        let amount = market.precision.round_amount(amount, RoundingMode::Down);
 
         if amount <= BigDecimal::zero() {
             return Err(AppError::Strategy("Amount must be positive".into()));
         }
 
-        let candle = self.candle()?;
-        let price = candle.close;
+        let candle = market.candle()?;
+        let mut amount = amount;
+        let mut price = market.fill_price(true, &amount)?;
+        let (mut fee, mut total) = market.buy_total(&price, &amount);
 
-        let cost = &price * &amount;
-        let fee = &cost * &self.fees.taker;
-        let fee = self.precision.round_amount(&fee, RoundingMode::Up);
-        let total = &cost + &fee;
+        // Buying the whole balance overshoots by the rounded fee, so step
+        // the amount down by one precision increment before giving up.
+        if total > balance {
+            let reduced = &amount - &market.precision.amount_precision;
+            let reduced_price = market.fill_price(true, &reduced)?;
+            let (reduced_fee, reduced_total) = market.buy_total(&reduced_price, &reduced);
+            if reduced <= BigDecimal::zero() || reduced_total > balance {
+                return Err(AppError::Strategy(format!(
+                    "Insufficient funds, at most {} can be bought",
+                    market.affordable_amount(&balance)?
+                )));
+            }
 
-        if total > self.balance {
-            return Err(AppError::Strategy("Insufficient funds".into()));
+            amount = reduced;
+            price = reduced_price;
+            fee = reduced_fee;
+            total = reduced_total;
         }
+        market.check_volume(&amount)?;
+        market.limits.check(&price, &amount)?;
 
+        market.position += &amount;
+        market.cost_basis += &total;
         self.balance -= &total;
-        self.position += &amount;
 
         self.trades.push(Trade {
             timestamp: candle.timestamp,
+            symbol: symbol.to_string(),
             trade_type: TradeType::MarketBuy,
+            intent: Some(TradeIntent::OpenLong),
             price,
             amount,
             fee,
@@ -219,68 +1210,174 @@ impl StrategyContext<'_> {
     }
 
     pub fn market_sell(&mut self, amount: &BigDecimal) -> AppResult<()> {
-        let amount = self.precision.round_amount(amount, RoundingMode::Down);
+        let symbol = self.symbol.clone();
+        self.market_sell_for(&symbol, amount)
+    }
+
+    pub fn market_sell_for(&mut self, symbol: &str, amount: &BigDecimal) -> AppResult<()> {
+        let market = self.market_mut(symbol)?;
+        let amount = market.precision.round_amount(amount, RoundingMode::Down);
 
         if amount <= BigDecimal::zero() {
             return Err(AppError::Strategy("Amount must be positive".into()));
         }
 
-        if amount > self.position {
+        if amount > market.position {
             return Err(AppError::Strategy(
                 "Insufficient base asset amount to sell".into(),
             ));
         }
 
-        let candle = self.candle()?;
-        let price = candle.close;
+        let candle = market.candle()?;
+        let price = market.fill_price(false, &amount)?;
+        market.check_volume(&amount)?;
+        market.limits.check(&price, &amount)?;
 
         let proceeds = &price * &amount;
-        let fee = self
+        let fee = market
             .precision
-            .round_amount(&(&proceeds * &self.fees.taker), RoundingMode::Up);
+            .round_fee(&(&proceeds * &market.fees.taker));
         let revenue = &proceeds - &fee;
 
         if revenue < BigDecimal::zero() {
             return Err(AppError::Strategy("Revenue cannot be negative".into()));
         }
 
-        self.position -= &amount;
+        let cost_basis = market.release_cost_basis(&amount);
+        let profit = &revenue - &cost_basis;
+
+        market.position -= &amount;
         self.balance += &revenue;
 
         self.trades.push(Trade {
             timestamp: candle.timestamp,
+            symbol: symbol.to_string(),
             trade_type: TradeType::MarketSell,
+            intent: Some(TradeIntent::CloseLong),
             price,
             amount,
             fee,
-            profit: None,
+            profit: Some(profit),
         });
 
         Ok(())
     }
 
+    pub fn close_position(&mut self) -> AppResult<()> {
+        let symbol = self.symbol.clone();
+        self.close_position_for(&symbol)
+    }
+
+    pub fn close_position_for(&mut self, symbol: &str) -> AppResult<()> {
+        self.market(symbol)?;
+        self.cancel_orders_where(|o| o.symbol == symbol);
+
+        let market = self.market(symbol)?;
+        let amount = market
+            .precision
+            .round_amount(&market.position, RoundingMode::Down);
+        if amount <= BigDecimal::zero() {
+            return Ok(());
+        }
+
+        self.market_sell_for(symbol, &amount)
+    }
+
+    pub fn market_buy_pct(&mut self, fraction: &BigDecimal) -> AppResult<()> {
+        let symbol = self.symbol.clone();
+        self.market_buy_pct_for(&symbol, fraction)
+    }
+
+    pub fn market_buy_pct_for(&mut self, symbol: &str, fraction: &BigDecimal) -> AppResult<()> {
+        Self::check_fraction(fraction)?;
+
+        let budget = &self.balance * fraction;
+        let amount = self.market(symbol)?.affordable_amount(&budget)?;
+
+        self.market_buy_for(symbol, &amount)
+    }
+
+    pub fn market_sell_pct(&mut self, fraction: &BigDecimal) -> AppResult<()> {
+        let symbol = self.symbol.clone();
+        self.market_sell_pct_for(&symbol, fraction)
+    }
+
+    pub fn market_sell_pct_for(&mut self, symbol: &str, fraction: &BigDecimal) -> AppResult<()> {
+        Self::check_fraction(fraction)?;
+
+        let amount = &self.market(symbol)?.position * fraction;
+        self.market_sell_for(symbol, &amount)
+    }
+
+    fn check_fraction(fraction: &BigDecimal) -> AppResult<()> {
+        if fraction <= &BigDecimal::zero() || fraction > &BigDecimal::from(1) {
+            return Err(AppError::Strategy("Fraction must be in (0, 1]".into()));
+        }
+
+        Ok(())
+    }
+
     pub fn limit_buy(
         &mut self,
         price: &BigDecimal,
         amount: &BigDecimal,
     ) -> AppResult<Option<Uuid>> {
-        let price = self.precision.round_amount(price, RoundingMode::Down);
-        let amount = self.precision.round_amount(amount, RoundingMode::Down);
+        let symbol = self.symbol.clone();
+        self.limit_buy_for(&symbol, price, amount)
+    }
+
+    pub fn limit_buy_for(
+        &mut self,
+        symbol: &str,
+        price: &BigDecimal,
+        amount: &BigDecimal,
+    ) -> AppResult<Option<Uuid>> {
+        let market = self.market(symbol)?;
+        let price = market.precision.round_price(price, RoundingMode::Down);
+
+        if price >= market.price()? {
+            self.market_buy_for(symbol, amount)?;
+            return Ok(None);
+        };
+
+        self.limit_buy_post_only_for(symbol, &price, amount)
+            .map(Some)
+    }
+
+    pub fn limit_buy_post_only(
+        &mut self,
+        price: &BigDecimal,
+        amount: &BigDecimal,
+    ) -> AppResult<Uuid> {
+        let symbol = self.symbol.clone();
+        self.limit_buy_post_only_for(&symbol, price, amount)
+    }
+
+    pub fn limit_buy_post_only_for(
+        &mut self,
+        symbol: &str,
+        price: &BigDecimal,
+        amount: &BigDecimal,
+    ) -> AppResult<Uuid> {
+        let market = self.market(symbol)?;
+        let price = market.precision.round_price(price, RoundingMode::Down);
+        let amount = market.precision.round_amount(amount, RoundingMode::Down);
 
         if amount <= BigDecimal::zero() {
             return Err(AppError::Strategy("Amount must be positive".into()));
         }
 
-        let candle = self.candle()?;
-
-        if price >= candle.close {
-            self.market_buy(&amount)?;
-            return Ok(None);
-        };
+        let market_price = market.price()?;
+        if price >= market_price {
+            return Err(AppError::OrderRejected(format!(
+                "Post-only limit buy at {} would cross the market price {}",
+                price, market_price
+            )));
+        }
+        market.limits.check(&price, &amount)?;
 
         let cost = &amount * &price;
-        let fee = &cost * &self.fees.maker;
-        let fee = self.precision.round_amount(&fee, RoundingMode::Up);
+        let fee = market.precision.round_fee(&(&cost * &market.fees.maker));
         let total = &cost + &fee;
 
         if total > self.balance {
@@ -289,16 +1386,19 @@ impl StrategyContext<'_> {
 
         self.balance -= &total;
 
-        let order_id = Uuid::new_v4();
+        let order_id = self.order_ids.next();
         self.orders.push(Order {
             id: order_id,
+            symbol: symbol.to_string(),
             order_type: OrderType::LimitBuy,
             price,
             amount,
             fee,
+            cost_basis: BigDecimal::zero(),
+            trailing: None,
         });
 
-        Ok(Some(order_id))
+        Ok(order_id)
     }
 
     pub fn limit_sell(
@@ -306,89 +1406,418 @@ impl StrategyContext<'_> {
         price: &BigDecimal,
         amount: &BigDecimal,
     ) -> AppResult<Option<Uuid>> {
-        let price = self.precision.round_amount(price, RoundingMode::Down);
-        let amount = self.precision.round_amount(amount, RoundingMode::Down);
+        let symbol = self.symbol.clone();
+        self.limit_sell_for(&symbol, price, amount)
+    }
+
+    pub fn limit_sell_for(
+        &mut self,
+        symbol: &str,
+        price: &BigDecimal,
+        amount: &BigDecimal,
+    ) -> AppResult<Option<Uuid>> {
+        let market = self.market(symbol)?;
+        let price = market.precision.round_price(price, RoundingMode::Down);
+
+        if price <= market.price()? {
+            self.market_sell_for(symbol, amount)?;
+            return Ok(None);
+        };
+
+        self.limit_sell_post_only_for(symbol, &price, amount)
+            .map(Some)
+    }
+
+    pub fn limit_sell_post_only(
+        &mut self,
+        price: &BigDecimal,
+        amount: &BigDecimal,
+    ) -> AppResult<Uuid> {
+        let symbol = self.symbol.clone();
+        self.limit_sell_post_only_for(&symbol, price, amount)
+    }
+
+    pub fn limit_sell_post_only_for(
+        &mut self,
+        symbol: &str,
+        price: &BigDecimal,
+        amount: &BigDecimal,
+    ) -> AppResult<Uuid> {
+        let market = self.market(symbol)?;
+        let price = market.precision.round_price(price, RoundingMode::Down);
+        let amount = market.precision.round_amount(amount, RoundingMode::Down);
 
         if amount <= BigDecimal::zero() {
             return Err(AppError::Strategy("Amount must be positive".into()));
         }
 
-        if amount > self.position {
+        if amount > market.position {
             return Err(AppError::Strategy(
                 "Insufficient base asset amount to sell".into(),
             ));
         }
 
-        let candle = self.candle()?;
-        if price <= candle.close {
-            self.market_sell(&amount)?;
-            return Ok(None);
-        };
+        let market_price = market.price()?;
+        if price <= market_price {
+            return Err(AppError::OrderRejected(format!(
+                "Post-only limit sell at {} would cross the market price {}",
+                price, market_price
+            )));
+        }
+        market.limits.check(&price, &amount)?;
 
         let proceeds = &price * &amount;
-        let fee = self
+        let fee = market
             .precision
-            .round_amount(&(&proceeds * &self.fees.maker), RoundingMode::Up);
+            .round_fee(&(&proceeds * &market.fees.maker));
 
         if fee > self.balance {
             return Err(AppError::Strategy("Insufficient funds to cover fee".into()));
         }
 
-        self.position -= &amount;
+        let market = self.market_mut(symbol)?;
+        let cost_basis = market.release_cost_basis(&amount);
+        market.position -= &amount;
         self.balance -= &fee;
 
-        let order_id = Uuid::new_v4();
+        let order_id = self.order_ids.next();
         self.orders.push(Order {
             id: order_id,
+            symbol: symbol.to_string(),
             order_type: OrderType::LimitSell,
             price,
             amount,
             fee,
+            cost_basis,
+            trailing: None,
         });
 
-        Ok(Some(order_id))
+        Ok(order_id)
+    }
+
+    pub fn trailing_stop_sell(&mut self, trail: Trail, amount: &BigDecimal) -> AppResult<Uuid> {
+        let symbol = self.symbol.clone();
+        self.trailing_stop_sell_for(&symbol, trail, amount)
     }
 
-    fn execute_limit_buy(
+    pub fn trailing_stop_sell_for(
         &mut self,
-        candle: &Candle,
-        price: &BigDecimal,
+        symbol: &str,
+        trail: Trail,
         amount: &BigDecimal,
-        fee: &BigDecimal,
-    ) {
-        self.position += amount;
+    ) -> AppResult<Uuid> {
+        let valid = match &trail {
+            Trail::Absolute(distance) => distance > &BigDecimal::zero(),
+            Trail::Percent(percent) => {
+                percent > &BigDecimal::zero() && percent < &BigDecimal::from(100)
+            }
+        };
+        if !valid {
+            return Err(AppError::Strategy("Invalid trailing stop distance".into()));
+        }
+
+        let market = self.market_mut(symbol)?;
+        let amount = market.precision.round_amount(amount, RoundingMode::Down);
+
+        if amount <= BigDecimal::zero() {
+            return Err(AppError::Strategy("Amount must be positive".into()));
+        }
+
+        if amount > market.position {
+            return Err(AppError::Strategy(
+                "Insufficient base asset amount to sell".into(),
+            ));
+        }
+
+        let high_water = market.price()?;
+        let price = market
+            .precision
+            .round_price(&trail.stop_price(&high_water), RoundingMode::Down);
+        if price <= BigDecimal::zero() {
+            return Err(AppError::Strategy("Invalid trailing stop distance".into()));
+        }
+        market.limits.check(&price, &amount)?;
+
+        let cost_basis = market.release_cost_basis(&amount);
+        market.position -= &amount;
+
+        let order_id = self.order_ids.next();
+        self.orders.push(Order {
+            id: order_id,
+            symbol: symbol.to_string(),
+            order_type: OrderType::TrailingStopSell,
+            price,
+            amount,
+            fee: BigDecimal::zero(),
+            cost_basis,
+            trailing: Some(TrailingStop { trail, high_water }),
+        });
+
+        Ok(order_id)
+    }
+
+    // Limit orders pay the fee reserved for them, which was already deducted
+    // from the balance.
+    fn execute_limit_buy(&mut self, candle: &Candle, order: &Order) {
+        let fee = order.fee.clone();
+        if let Some(market) = self.markets.get_mut(&order.symbol) {
+            market.position += &order.amount;
+            market.cost_basis += &order.price * &order.amount + &fee;
+        }
 
         let trade = Trade {
             timestamp: candle.timestamp,
+            symbol: order.symbol.clone(),
             trade_type: TradeType::LimitBuy,
-            price: price.clone(),
-            amount: amount.clone(),
-            fee: fee.clone(),
+            intent: Some(TradeIntent::OpenLong),
+            price: order.price.clone(),
+            amount: order.amount.clone(),
+            fee,
             profit: None,
         };
 
         self.trades.push(trade);
     }
 
-    fn execute_limit_sell(
-        &mut self,
-        candle: &Candle,
-        price: &BigDecimal,
-        amount: &BigDecimal,
-        fee: &BigDecimal,
-    ) {
-        let proceeds = price * amount;
+    fn execute_limit_sell(&mut self, candle: &Candle, order: &Order) {
+        let fee = order.fee.clone();
+        let proceeds = &order.price * &order.amount;
         self.balance += &proceeds;
 
         let trade = Trade {
             timestamp: candle.timestamp,
+            symbol: order.symbol.clone(),
             trade_type: TradeType::LimitSell,
-            price: price.clone(),
-            amount: amount.clone(),
-            fee: fee.clone(),
-            profit: None,
+            intent: Some(TradeIntent::CloseLong),
+            price: order.price.clone(),
+            amount: order.amount.clone(),
+            profit: Some(&proceeds - &fee - &order.cost_basis),
+            fee,
+        };
+
+        self.trades.push(trade);
+    }
+
+    fn execute_trailing_stop_sell(&mut self, candle: &Candle, order: &Order) {
+        let price = if candle.open < order.price {
+            candle.open.clone()
+        } else {
+            order.price.clone()
+        };
+
+        let proceeds = &price * &order.amount;
+        let fee = match self.markets.get(&order.symbol) {
+            Some(market) => market
+                .precision
+                .round_fee(&(&proceeds * &market.fees.taker)),
+            None => BigDecimal::zero(),
+        };
+        self.balance += &proceeds - &fee;
+
+        let trade = Trade {
+            timestamp: candle.timestamp,
+            symbol: order.symbol.clone(),
+            trade_type: TradeType::TrailingStopSell,
+            intent: Some(TradeIntent::CloseLong),
+            price,
+            amount: order.amount.clone(),
+            profit: Some(&proceeds - &fee - &order.cost_basis),
+            fee,
         };
 
         self.trades.push(trade);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::FeeRounding;
+    use std::str::FromStr;
+
+    fn decimal(value: &str) -> BigDecimal {
+        BigDecimal::from_str(value).unwrap()
+    }
+
+    fn candle(minute: i64, open: i32, high: i32, low: i32, close: i32) -> Candle {
+        let start = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        Candle::test(start + chrono::TimeDelta::minutes(minute), close).with_range(open, high, low)
+    }
+
+    fn context<'a>() -> StrategyContext<'a> {
+        StrategyContext::new(
+            BigDecimal::from(10000),
+            "test",
+            "BTC/USDT",
+            Timeframe::M1,
+            TradingFees {
+                maker: decimal("0.001"),
+                taker: decimal("0.002"),
+            },
+            MarketPrecision {
+                price_precision: BigDecimal::zero(),
+                amount_precision: BigDecimal::zero(),
+                fee_precision: BigDecimal::zero(),
+                fee_rounding: FeeRounding::default(),
+            },
+            MarketLimits::default(),
+        )
+        .unwrap()
+    }
+
+    /// Moves the context onto the first `count` candles and matches orders.
+    fn step<'a>(context: &mut StrategyContext<'a>, candles: &'a [Candle], count: usize) {
+        context.set_candles("BTC/USDT", &candles[..count]).unwrap();
+        context.before().unwrap();
+    }
+
+    #[test]
+    fn far_limit_buy_filling_later_pays_maker_fee() {
+        let candles = [
+            candle(0, 100, 100, 100, 100),
+            candle(1, 99, 99, 97, 98),
+            candle(2, 95, 96, 89, 92),
+        ];
+        let mut context = context();
+        step(&mut context, &candles, 1);
+        context
+            .limit_buy(&BigDecimal::from(90), &BigDecimal::from(1))
+            .unwrap();
+        assert_eq!(context.balance(), decimal("9909.91"));
+
+        step(&mut context, &candles, 2);
+        assert!(context.trades().is_empty());
+
+        step(&mut context, &candles, 3);
+        let trade = &context.trades()[0];
+        assert_eq!(trade.price, BigDecimal::from(90));
+        assert_eq!(trade.fee, decimal("0.09"));
+        assert_eq!(context.balance(), decimal("9909.91"));
+        assert_eq!(context.position(), BigDecimal::from(1));
+    }
+
+    #[test]
+    fn resting_orders_gapped_through_fill_at_their_price_as_makers() {
+        let candles = [candle(0, 100, 100, 100, 100), candle(1, 85, 86, 84, 85)];
+        let mut context = context();
+        step(&mut context, &candles, 1);
+        context
+            .limit_buy(&BigDecimal::from(90), &BigDecimal::from(1))
+            .unwrap();
+
+        step(&mut context, &candles, 2);
+        let trade = &context.trades()[0];
+        assert_eq!(trade.price, BigDecimal::from(90));
+        assert_eq!(trade.fee, decimal("0.09"));
+        assert_eq!(context.balance(), decimal("9909.91"));
+    }
+
+    #[test]
+    fn far_limit_sell_filling_later_pays_maker_fee() {
+        let candles = [
+            candle(0, 100, 100, 100, 100),
+            candle(1, 101, 103, 100, 102),
+            candle(2, 105, 111, 104, 108),
+        ];
+        let mut context = context();
+        step(&mut context, &candles, 1);
+        context.market_buy(&BigDecimal::from(1)).unwrap();
+        context
+            .limit_sell(&BigDecimal::from(110), &BigDecimal::from(1))
+            .unwrap();
+        assert_eq!(context.balance(), decimal("9899.69"));
+
+        step(&mut context, &candles, 2);
+        step(&mut context, &candles, 3);
+        let trade = &context.trades()[1];
+        assert_eq!(trade.price, BigDecimal::from(110));
+        assert_eq!(trade.fee, decimal("0.11"));
+        assert_eq!(trade.profit, Some(decimal("9.69")));
+        assert_eq!(context.balance(), decimal("10009.69"));
+    }
+
+    #[test]
+    fn orders_modified_across_the_market_execute_at_once() {
+        let candles = [candle(0, 100, 100, 100, 100), candle(1, 100, 102, 99, 101)];
+        let mut context = context();
+        step(&mut context, &candles, 1);
+        let order_id = context
+            .limit_buy(&BigDecimal::from(90), &BigDecimal::from(1))
+            .unwrap()
+            .unwrap();
+        context
+            .modify_order(order_id, &BigDecimal::from(101), &BigDecimal::from(1))
+            .unwrap();
+        assert!(context.orders().is_empty());
+        assert!(matches!(
+            context.trades()[0].trade_type,
+            TradeType::MarketBuy
+        ));
+        assert_eq!(context.trades()[0].fee, decimal("0.2"));
+        assert_eq!(context.balance(), decimal("9899.8"));
+
+        step(&mut context, &candles, 2);
+        assert_eq!(context.trades().len(), 1);
+        assert_eq!(context.position(), BigDecimal::from(1));
+    }
+
+    #[test]
+    fn failed_crossing_modifications_keep_the_order() {
+        let candles = [candle(0, 100, 100, 100, 100)];
+        let mut context = context();
+        step(&mut context, &candles, 1);
+        let order_id = context
+            .limit_buy(&BigDecimal::from(90), &BigDecimal::from(1))
+            .unwrap()
+            .unwrap();
+        assert!(
+            context
+                .modify_order(order_id, &BigDecimal::from(100), &BigDecimal::from(1000))
+                .is_err()
+        );
+        assert_eq!(context.orders()[0].price, BigDecimal::from(90));
+        assert_eq!(context.balance(), decimal("9909.91"));
+        assert!(context.trades().is_empty());
+    }
+
+    #[test]
+    fn partial_fills_pay_their_share_of_the_reserved_fee() {
+        let candles = [
+            candle(0, 100, 100, 100, 100),
+            candle(1, 95, 96, 89, 92).with_volume(decimal("0.5")),
+            candle(2, 92, 93, 88, 90).with_volume(decimal("0.5")),
+        ];
+        let mut context = context();
+        context
+            .set_limit_fill_ratio("BTC/USDT", Some(BigDecimal::from(1)))
+            .unwrap();
+        step(&mut context, &candles, 1);
+        context
+            .limit_buy(&BigDecimal::from(90), &BigDecimal::from(1))
+            .unwrap();
+
+        step(&mut context, &candles, 2);
+        assert_eq!(context.trades()[0].amount, decimal("0.5"));
+        assert_eq!(context.trades()[0].fee, decimal("0.045"));
+        assert_eq!(context.orders()[0].fee, decimal("0.045"));
+
+        step(&mut context, &candles, 3);
+        assert!(context.orders().is_empty());
+        assert_eq!(context.trades()[1].fee, decimal("0.045"));
+        assert_eq!(context.position(), BigDecimal::from(1));
+        assert_eq!(context.balance(), decimal("9909.91"));
+    }
+
+    #[test]
+    fn cancelled_orders_refund_their_reserved_fee() {
+        let candles = [candle(0, 100, 100, 100, 100)];
+        let mut context = context();
+        step(&mut context, &candles, 1);
+        let order_id = context
+            .limit_buy(&BigDecimal::from(90), &BigDecimal::from(1))
+            .unwrap()
+            .unwrap();
+        context.cancel_order(order_id);
+        assert_eq!(context.balance(), BigDecimal::from(10000));
+    }
+}
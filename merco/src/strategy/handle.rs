@@ -1,31 +1,250 @@
-use crate::errors::AppResult;
-use crate::strategy::Strategy;
+use crate::errors::{AppError, AppResult};
+use crate::strategy::{
+    PLUGIN_ABI_VERSION, PluginMetadata, Strategy, StrategyContext, StrategyMetadata, panic_message,
+};
 use libloading::{Library, Symbol};
 use std::{
+    fs,
+    mem::ManuallyDrop,
     ops::{Deref, DerefMut},
-    path::PathBuf,
+    panic::{AssertUnwindSafe, catch_unwind},
+    path::{Path, PathBuf},
 };
+use uuid::Uuid;
 
-const PLUGIN_CREATE_FUNCTION_NAME: &'static str = "_plugin_create";
+const PLUGIN_CREATE_FUNCTION_NAME: &str = "_plugin_create";
+const PLUGIN_CREATE_WITH_CONFIG_FUNCTION_NAME: &str = "_plugin_create_with_config";
+const PLUGIN_ABI_VERSION_FUNCTION_NAME: &str = "_plugin_abi_version";
+const PLUGIN_METADATA_FUNCTION_NAME: &str = "_plugin_metadata";
+const LOADED_LIBRARY_DIR: &str = "merco-strategies";
 
 pub struct StrategyHandle {
-    strategy: Box<dyn Strategy>,
-    _lib: Library, // Keep the library loaded
+    strategy: ManuallyDrop<Box<dyn Strategy>>,
+    /// None for strategies linked into the current binary.
+    lib: ManuallyDrop<Option<Library>>,
+    lib_path: PathBuf,
+    metadata: StrategyMetadata,
 }
 
 impl StrategyHandle {
-    pub fn try_from_path(path: &PathBuf) -> AppResult<Self> {
+    pub fn try_from_path(path: &Path, params: Option<&serde_json::Value>) -> AppResult<Self> {
+        // Load a private copy so rebuilding the strategy cannot replace the
+        // file under a loaded library.
+        let lib_path = Self::copy_library(path)?;
+        Self::load(&lib_path, path, params).inspect_err(|_| {
+            let _ = fs::remove_file(&lib_path);
+        })
+    }
+
+    /// Wraps a strategy compiled into the current binary, so backtests can be
+    /// run without building a plugin.
+    #[cfg(any(test, feature = "test-util"))]
+    pub fn from_strategy(strategy: Box<dyn Strategy>) -> Self {
+        Self {
+            strategy: ManuallyDrop::new(strategy),
+            lib: ManuallyDrop::new(None),
+            lib_path: PathBuf::new(),
+            metadata: StrategyMetadata::default(),
+        }
+    }
+
+    /// Reads a plugin's metadata without creating the strategy.
+    pub fn read_metadata(path: &Path) -> AppResult<StrategyMetadata> {
+        let lib_path = Self::copy_library(path)?;
+        let metadata = unsafe {
+            Library::new(&lib_path)
+                .map_err(AppError::from)
+                .and_then(|lib| Self::check_abi(&lib).map(|_| Self::metadata_of(&lib, path)))
+        };
+        let _ = fs::remove_file(&lib_path);
+        metadata
+    }
+
+    fn copy_library(path: &Path) -> AppResult<PathBuf> {
+        let dir = std::env::temp_dir().join(LOADED_LIBRARY_DIR);
+        fs::create_dir_all(&dir)?;
+
+        let stem = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or("strategy");
+        let mut file_name = format!("{}-{}", stem, Uuid::new_v4());
+        if let Some(extension) = path.extension().and_then(|extension| extension.to_str()) {
+            file_name = format!("{}.{}", file_name, extension);
+        }
+
+        let lib_path = dir.join(file_name);
+        fs::copy(path, &lib_path)?;
+        Ok(lib_path)
+    }
+
+    fn load(
+        path: &PathBuf,
+        source_path: &Path,
+        params: Option<&serde_json::Value>,
+    ) -> AppResult<Self> {
         unsafe {
             let lib = Library::new(path)?;
-            let constructor: Symbol<fn() -> *mut dyn Strategy> =
-                lib.get(PLUGIN_CREATE_FUNCTION_NAME.as_bytes())?;
-            let strategy = Box::from_raw(constructor());
+
+            Self::check_abi(&lib)?;
+            let metadata = Self::metadata_of(&lib, source_path);
+            let strategy = match params {
+                Some(params) => {
+                    let json = params.to_string();
+                    let constructor: Symbol<
+                        unsafe extern "C" fn(*const u8, usize) -> *mut dyn Strategy,
+                    > = lib.get(PLUGIN_CREATE_WITH_CONFIG_FUNCTION_NAME.as_bytes())?;
+                    let strategy = constructor(json.as_ptr(), json.len());
+                    if strategy.is_null() {
+                        return Err(AppError::Strategy(format!(
+                            "Strategy rejected params: {}",
+                            json
+                        )));
+                    }
+                    Box::from_raw(strategy)
+                }
+                None => {
+                    let constructor: Symbol<unsafe extern "C" fn() -> *mut dyn Strategy> =
+                        lib.get(PLUGIN_CREATE_FUNCTION_NAME.as_bytes())?;
+                    let strategy = constructor();
+                    if strategy.is_null() {
+                        return Err(AppError::Strategy(
+                            "Strategy failed to initialize".to_string(),
+                        ));
+                    }
+                    Box::from_raw(strategy)
+                }
+            };
             Ok(Self {
-                strategy,
-                _lib: lib,
+                strategy: ManuallyDrop::new(strategy),
+                lib: ManuallyDrop::new(Some(lib)),
+                lib_path: path.clone(),
+                metadata,
             })
         }
     }
+
+    unsafe fn check_abi(lib: &Library) -> AppResult<()> {
+        let Ok(abi_version) = (unsafe {
+            lib.get::<extern "C" fn() -> u32>(PLUGIN_ABI_VERSION_FUNCTION_NAME.as_bytes())
+        }) else {
+            return Err(AppError::Strategy(
+                "Strategy was built against an older merco version, rebuild it".to_string(),
+            ));
+        };
+        let abi_version = abi_version();
+        if abi_version != PLUGIN_ABI_VERSION {
+            return Err(AppError::Strategy(format!(
+                "Strategy was built against merco ABI version {}, but the host uses {}, rebuild it",
+                abi_version, PLUGIN_ABI_VERSION
+            )));
+        }
+        Ok(())
+    }
+
+    /// Plugins built without metadata are named after their crate.
+    unsafe fn metadata_of(lib: &Library, path: &Path) -> StrategyMetadata {
+        let metadata = unsafe {
+            lib.get::<extern "C" fn() -> *const PluginMetadata>(
+                PLUGIN_METADATA_FUNCTION_NAME.as_bytes(),
+            )
+            .ok()
+            .and_then(|metadata| metadata().as_ref().map(StrategyMetadata::from))
+        };
+        metadata.unwrap_or_else(|| StrategyMetadata {
+            name: path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .map(|stem| stem.strip_prefix("lib").unwrap_or(stem))
+                .unwrap_or("strategy")
+                .to_string(),
+            ..Default::default()
+        })
+    }
+
+    pub fn metadata(&self) -> &StrategyMetadata {
+        &self.metadata
+    }
+
+    pub fn tick(&mut self, context: &mut StrategyContext) -> AppResult<()> {
+        catch_unwind(AssertUnwindSafe(|| self.strategy.tick(context))).unwrap_or_else(|payload| {
+            Err(AppError::Strategy(format!(
+                "Strategy panicked: {}",
+                panic_message(payload.as_ref())
+            )))
+        })
+    }
+
+    pub fn on_start(&mut self, context: &mut StrategyContext) -> AppResult<()> {
+        catch_unwind(AssertUnwindSafe(|| self.strategy.on_start(context))).unwrap_or_else(
+            |payload| {
+                Err(AppError::Strategy(format!(
+                    "Strategy panicked while starting: {}",
+                    panic_message(payload.as_ref())
+                )))
+            },
+        )
+    }
+
+    pub fn on_finish(&mut self, context: &mut StrategyContext) -> AppResult<()> {
+        catch_unwind(AssertUnwindSafe(|| self.strategy.on_finish(context))).unwrap_or_else(
+            |payload| {
+                Err(AppError::Strategy(format!(
+                    "Strategy panicked while finishing: {}",
+                    panic_message(payload.as_ref())
+                )))
+            },
+        )
+    }
+}
+
+impl Drop for StrategyHandle {
+    fn drop(&mut self) {
+        // The strategy's code lives in the library, so it must be dropped
+        // before the library is unloaded and its file removed.
+        unsafe {
+            ManuallyDrop::drop(&mut self.strategy);
+            ManuallyDrop::drop(&mut self.lib);
+        }
+        if !self.lib_path.as_os_str().is_empty() {
+            let _ = fs::remove_file(&self.lib_path);
+        }
+    }
+}
+
+/// A private copy of a built strategy library, removed when dropped. Tasks
+/// that load a strategy many times load it from one, so a rebuild while the
+/// task runs cannot change the code under its later runs.
+pub struct LibrarySnapshot {
+    dir: PathBuf,
+    path: PathBuf,
+}
+
+impl LibrarySnapshot {
+    pub fn new(path: &Path) -> AppResult<Self> {
+        // The copy keeps its file name, which names plugins without metadata.
+        let dir = std::env::temp_dir()
+            .join(LOADED_LIBRARY_DIR)
+            .join(Uuid::new_v4().to_string());
+        fs::create_dir_all(&dir)?;
+
+        let snapshot = Self {
+            path: dir.join(path.file_name().unwrap_or(path.as_os_str())),
+            dir,
+        };
+        fs::copy(path, &snapshot.path)?;
+        Ok(snapshot)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for LibrarySnapshot {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
 }
 
 impl Deref for StrategyHandle {
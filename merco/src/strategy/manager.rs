@@ -1,7 +1,18 @@
-use crate::errors::AppResult;
+use crate::errors::{AppError, AppResult};
 use crate::strategy::handle::StrategyHandle;
 use cargo_metadata::MetadataCommand;
-use std::{fs, path::PathBuf, process::Stdio};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::Arc;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Stdio,
+};
+use tokio::process::Command;
+use tokio::sync::Mutex;
 use toml_edit::{DocumentMut, array, table, value};
 
 const WORKSPACE_CARGO_TOML: &str = include_str!(concat!(
@@ -18,15 +29,99 @@ const MEMBER_LIB_RS: &str = include_str!(concat!(
 ));
 pub const STRATEGY_WORKDIR_NAME: &str = "strategies";
 
+/// Where the strategies cargo workspace lives. Relative paths are resolved
+/// against the working directory at startup.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct StrategyConfig {
+    pub workspace_dir: PathBuf,
+    pub sandbox: SandboxConfig,
+}
+
+impl Default for StrategyConfig {
+    fn default() -> Self {
+        Self {
+            workspace_dir: PathBuf::from(STRATEGY_WORKDIR_NAME),
+            sandbox: SandboxConfig::default(),
+        }
+    }
+}
+
+/// Isolates untrusted strategies. When enabled, builds and backtests run as
+/// child processes with only `inherit_env` passed through, prefixed by
+/// `wrapper` (e.g. a `bwrap`, `firejail` or `prlimit` invocation), and
+/// backtests no longer load strategy code into the server process.
+///
+/// Without a wrapper the children can still read the server's files,
+/// including the config files holding credentials, so enabling the sandbox
+/// with an empty `wrapper` requires `allow_unwrapped`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SandboxConfig {
+    pub enabled: bool,
+    pub wrapper: Vec<String>,
+    pub inherit_env: Vec<String>,
+    pub allow_unwrapped: bool,
+}
+
+impl Default for SandboxConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            wrapper: Vec::new(),
+            inherit_env: [
+                "PATH",
+                "HOME",
+                "CARGO_HOME",
+                "RUSTUP_HOME",
+                "LD_LIBRARY_PATH",
+            ]
+            .into_iter()
+            .map(str::to_string)
+            .collect(),
+            allow_unwrapped: false,
+        }
+    }
+}
+
+impl SandboxConfig {
+    pub fn is_unwrapped(&self) -> bool {
+        self.enabled && self.wrapper.is_empty()
+    }
+
+    pub fn command(&self, program: impl AsRef<OsStr>) -> Command {
+        let mut command = match self.wrapper.split_first() {
+            Some((wrapper, args)) => {
+                let mut command = Command::new(wrapper);
+                command.args(args).arg(program);
+                command
+            }
+            None => Command::new(program),
+        };
+
+        command.env_clear();
+        for name in &self.inherit_env {
+            if let Some(value) = std::env::var_os(name) {
+                command.env(name, value);
+            }
+        }
+
+        command
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct StrategyManager {
     workspace_dir: PathBuf,
+    sandbox: SandboxConfig,
+    build_hashes: Arc<Mutex<HashMap<String, u64>>>,
+    build_lock: Arc<Mutex<()>>,
 }
 
 impl StrategyManager {
-    pub fn new() -> AppResult<Self> {
+    pub fn new(config: &StrategyConfig) -> AppResult<Self> {
         let current_dir = std::env::current_dir()?;
-        let workspace_dir = current_dir.join(STRATEGY_WORKDIR_NAME);
+        let workspace_dir = current_dir.join(&config.workspace_dir);
 
         let mut initial = false;
         if !workspace_dir.is_dir() {
@@ -41,12 +136,24 @@ impl StrategyManager {
             initial = true;
         }
 
+        let workspace_dir = workspace_dir.canonicalize()?;
         let workspace_toml = workspace_dir.join("Cargo.toml");
         if !workspace_toml.exists() {
             fs::write(workspace_toml, WORKSPACE_CARGO_TOML)?;
         }
 
-        let manager = Self { workspace_dir };
+        if config.sandbox.is_unwrapped() {
+            tracing::warn!(
+                "Strategy sandbox has no wrapper, so strategies can still read the server's files, including config/secrets.toml"
+            );
+        }
+
+        let manager = Self {
+            workspace_dir,
+            sandbox: config.sandbox.clone(),
+            build_hashes: Arc::new(Mutex::new(HashMap::new())),
+            build_lock: Arc::new(Mutex::new(())),
+        };
 
         if initial {
             manager.add_strategy("my-strategy")?;
@@ -55,6 +162,14 @@ impl StrategyManager {
         Ok(manager)
     }
 
+    pub fn workspace_dir(&self) -> &Path {
+        &self.workspace_dir
+    }
+
+    pub fn sandbox(&self) -> Option<&SandboxConfig> {
+        self.sandbox.enabled.then_some(&self.sandbox)
+    }
+
     pub fn add_strategy(&self, strategy_name: &str) -> AppResult<()> {
         let workspace_toml_path = self.workspace_dir.join("Cargo.toml");
         let mut workspace_toml: DocumentMut = fs::read_to_string(&workspace_toml_path)?.parse()?;
@@ -98,7 +213,74 @@ impl StrategyManager {
         Ok(())
     }
 
-    pub async fn load_strategy(&self, strategy_name: &str) -> AppResult<StrategyHandle> {
+    pub fn remove_strategy(&self, strategy_name: &str) -> AppResult<()> {
+        let is_valid_name = !strategy_name.is_empty()
+            && strategy_name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !is_valid_name {
+            return Err(AppError::BadRequest(format!(
+                "Invalid strategy name: {}",
+                strategy_name
+            )));
+        }
+
+        let workspace_toml_path = self.workspace_dir.join("Cargo.toml");
+        let mut workspace_toml: DocumentMut = fs::read_to_string(&workspace_toml_path)?.parse()?;
+
+        let Some(members) = workspace_toml
+            .get_mut("workspace")
+            .and_then(|w| w.get_mut("members"))
+            .and_then(|m| m.as_array_mut())
+        else {
+            return Err(AppError::NotFound(format!(
+                "Strategy '{}' not found",
+                strategy_name
+            )));
+        };
+
+        let Some(index) = members
+            .iter()
+            .position(|m| m.as_str() == Some(strategy_name))
+        else {
+            return Err(AppError::NotFound(format!(
+                "Strategy '{}' not found",
+                strategy_name
+            )));
+        };
+
+        // Drop the workspace member first so a failed directory removal leaves
+        // an orphaned directory rather than a workspace pointing at nothing.
+        members.remove(index);
+        fs::write(workspace_toml_path, workspace_toml.to_string())?;
+
+        let strategy_dir = self.workspace_dir.join(strategy_name);
+        if strategy_dir.is_dir() {
+            fs::remove_dir_all(&strategy_dir)?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn load_strategy(
+        &self,
+        strategy_name: &str,
+        force_rebuild: bool,
+        params: Option<&serde_json::Value>,
+    ) -> AppResult<StrategyHandle> {
+        let lib_path = self.build_strategy(strategy_name, force_rebuild).await?;
+        StrategyHandle::try_from_path(&lib_path, params)
+    }
+
+    pub async fn build_strategy(
+        &self,
+        strategy_name: &str,
+        force_rebuild: bool,
+    ) -> AppResult<PathBuf> {
+        // Every member builds into the shared workspace target directory, so
+        // builds are serialized. Loading an already built library is not.
+        let _build_guard = self.build_lock.lock().await;
+
         let metadata = MetadataCommand::new()
             .current_dir(&self.workspace_dir)
             .exec()?;
@@ -109,19 +291,6 @@ impl StrategyManager {
             .find(|p| p.name == strategy_name)
             .ok_or(format!("Package '{}' not found", strategy_name))?;
 
-        let output = tokio::process::Command::new("cargo")
-            .args(["build", "--release", "--package", strategy_name])
-            .current_dir(&self.workspace_dir)
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .output()
-            .await?;
-
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(format!("Build failed: {}", stderr).into());
-        }
-
         let target_dir = metadata.target_directory.as_std_path();
 
         #[cfg(target_os = "linux")]
@@ -135,10 +304,75 @@ impl StrategyManager {
 
         let lib_path = target_dir.join("release").join(&lib_name);
 
+        let source_hash = self.source_hash(strategy_name)?;
+        let is_cached = !force_rebuild
+            && lib_path.exists()
+            && self.build_hashes.lock().await.get(strategy_name) == Some(&source_hash);
+
+        if is_cached {
+            tracing::info!("Strategy {} unchanged, skipping build", strategy_name);
+        } else {
+            let mut command = match self.sandbox() {
+                Some(sandbox) => sandbox.command("cargo"),
+                None => Command::new("cargo"),
+            };
+            let output = command
+                .args(["build", "--release", "--package", strategy_name])
+                .current_dir(&self.workspace_dir)
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .output()
+                .await?;
+
+            if !output.status.success() {
+                let stdout = String::from_utf8_lossy(&output.stdout);
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                return Err(AppError::Build(
+                    format!("{}{}", stdout, stderr).trim().to_string(),
+                ));
+            }
+
+            self.build_hashes
+                .lock()
+                .await
+                .insert(strategy_name.to_string(), source_hash);
+        }
+
         if !lib_path.exists() {
             return Err(format!("Library not found: {:?}", lib_path).into());
         }
 
-        StrategyHandle::try_from_path(&lib_path)
+        Ok(lib_path)
+    }
+
+    fn source_hash(&self, strategy_name: &str) -> AppResult<u64> {
+        let mut files = Vec::new();
+        Self::collect_source_files(&self.workspace_dir.join(strategy_name), &mut files)?;
+        files.sort();
+
+        let mut hasher = DefaultHasher::new();
+        fs::read(self.workspace_dir.join("Cargo.toml"))?.hash(&mut hasher);
+        for file in files {
+            file.hash(&mut hasher);
+            fs::read(&file)?.hash(&mut hasher);
+        }
+
+        Ok(hasher.finish())
+    }
+
+    fn collect_source_files(dir: &Path, files: &mut Vec<PathBuf>) -> AppResult<()> {
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                if entry.file_name() != "target" {
+                    Self::collect_source_files(&entry.path(), files)?;
+                }
+            } else if file_type.is_file() {
+                files.push(entry.path());
+            }
+        }
+
+        Ok(())
     }
 }
@@ -1,5 +1,19 @@
 pub mod backtest;
+pub mod fetch_all_candles;
 pub mod fetch_candles;
+pub mod optimize;
+mod progress;
+pub mod sandbox;
+pub mod summary;
+pub mod walk_forward;
 
-pub use backtest::{BacktestStatistic, BacktestStatus, BacktestTask};
+pub use backtest::{
+    BacktestConfig, BacktestMarket, BacktestProgress, BacktestResult, BacktestStatistic,
+    BacktestStatus, BacktestTask, MarketType, RunLimits, SlippageModel,
+};
+pub use fetch_all_candles::{FetchAllCandlesFailure, FetchAllCandlesTask};
 pub use fetch_candles::{FetchCandlesResult, FetchCandlesStatus, FetchCandlesTask};
+pub use optimize::{OptimizeMetric, OptimizeResult, OptimizeStatus, OptimizeTask, ParameterRange};
+pub use sandbox::{SANDBOX_WORKER_ARG, StrategyRunner, run_sandbox_worker};
+pub use summary::{TaskStatus, TaskSummary, TaskType};
+pub use walk_forward::{WalkForwardFold, WalkForwardStatus, WalkForwardSummary, WalkForwardTask};
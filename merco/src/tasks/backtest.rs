@@ -1,30 +1,139 @@
-use crate::errors::AppResult;
+use crate::errors::{AppError, AppResult};
 use crate::exchange::ccxt::CCXT;
-use crate::models::{Candle, MarketPrecision, Timeframe};
+use crate::models::{
+    Candle, FeeRounding, FundingRate, MarketLimits, MarketPrecision, Timeframe, TradingFees,
+};
+use crate::services::backtests::save_backtest_result;
 use crate::services::candles::get_candles;
+use crate::services::funding::{get_funding_rate_range, get_funding_rates, insert_funding_rates};
 use crate::services::tasks::save_backtest_task;
-use crate::strategy::{StrategyContext, StrategyHandle, StrategyManager, Trade, TradeType};
-use bigdecimal::{BigDecimal, RoundingMode, ToPrimitive, Zero};
-use chrono::{DateTime, Utc, serde::ts_milliseconds, serde::ts_milliseconds_option};
+use crate::strategy::{
+    ExecutionTiming, FundingPayment, OrderIds, PlotMarker, PlotPoint, Slippage, StrategyContext,
+    StrategyHandle, StrategyLog, StrategyManager, Trade,
+};
+use crate::tasks::StrategyRunner;
+use crate::tasks::progress::Throughput;
+use crate::tasks::summary::{TaskSummary, TaskType};
+use bigdecimal::{BigDecimal, FromPrimitive, RoundingMode, ToPrimitive, Zero};
+use chrono::{DateTime, TimeDelta, Utc, serde::ts_milliseconds, serde::ts_milliseconds_option};
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
-use tokio::sync::broadcast;
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Range;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc, oneshot};
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
 use ts_rs::TS;
 use uuid::Uuid;
 
 const BACKTEST_BROADCAST_INTERVAL: usize = 100;
+const FUNDING_RATE_FETCH_LIMIT: i64 = 1000;
+/// Decimal places float metrics are rounded to, so that last-bit differences
+/// between platforms' math libraries do not change serialized results.
+const METRIC_DECIMALS: i32 = 6;
+
+/// A backtest that reports no progress for `stall_timeout_secs`, or has run
+/// for `max_run_secs`, is failed, so a strategy stuck in a loop cannot hold
+/// its task forever. 0 disables either limit.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct BacktestConfig {
+    pub stall_timeout_secs: u64,
+    pub max_run_secs: u64,
+}
+
+impl Default for BacktestConfig {
+    fn default() -> Self {
+        Self {
+            stall_timeout_secs: 300,
+            max_run_secs: 3600,
+        }
+    }
+}
+
+impl BacktestConfig {
+    pub fn run_limits(&self) -> RunLimits {
+        let limit = |secs: u64| (secs > 0).then(|| Duration::from_secs(secs));
+        RunLimits {
+            stall_timeout: limit(self.stall_timeout_secs),
+            max_duration: limit(self.max_run_secs),
+        }
+    }
+}
+
+/// Time limits on a single strategy run, of a backtest or of one parameter
+/// combination of an optimization.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RunLimits {
+    pub stall_timeout: Option<Duration>,
+    pub max_duration: Option<Duration>,
+}
+
+impl RunLimits {
+    pub(crate) fn stall_deadline(&self) -> Option<Instant> {
+        self.stall_timeout.map(|timeout| Instant::now() + timeout)
+    }
+
+    pub(crate) fn run_deadline(&self) -> Option<Instant> {
+        self.max_duration.map(|duration| Instant::now() + duration)
+    }
+
+    pub(crate) fn stalled_error(&self) -> AppError {
+        format!(
+            "Backtest made no progress for {} seconds",
+            self.stall_timeout.map_or(0, |timeout| timeout.as_secs())
+        )
+        .into()
+    }
+
+    pub(crate) fn overran_error(&self) -> AppError {
+        format!(
+            "Backtest did not finish within {} seconds",
+            self.max_duration.map_or(0, |duration| duration.as_secs())
+        )
+        .into()
+    }
+}
+
+/// Sets the flag when dropped, telling a backtest thread to stop.
+struct StopOnDrop(Arc<AtomicBool>);
+
+impl Drop for StopOnDrop {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[ts(export)]
 pub struct BacktestStatistic {
     pub trades: Vec<Trade>,
+    #[serde(default)]
+    pub logs: Vec<StrategyLog>,
+    #[serde(default)]
+    pub plots: BTreeMap<String, Vec<PlotPoint>>,
+    #[serde(default)]
+    pub markers: Vec<PlotMarker>,
+    #[serde(default)]
+    pub funding_payments: Vec<FundingPayment>,
     #[ts(type = "string")]
     pub initial_capital: BigDecimal,
     #[ts(type = "string")]
     pub total_cost: BigDecimal,
+    #[serde(default)]
+    #[ts(type = "string")]
+    pub funding_cost: BigDecimal,
+    #[serde(default)]
+    #[ts(type = "string")]
+    pub total_fees: BigDecimal,
     #[ts(type = "string")]
     pub net_profit: BigDecimal,
     pub return_percent: f32,
+    #[serde(default)]
+    pub gross_return_percent: f32,
     #[ts(type = "string")]
     pub max_equity: BigDecimal,
     #[ts(type = "string")]
@@ -36,6 +145,27 @@ pub struct BacktestStatistic {
     pub gross_loss: BigDecimal,
     pub profit_factor: f32,
     pub sharpe_ratio: f32,
+    #[serde(default)]
+    #[ts(optional)]
+    pub sortino_ratio: Option<f32>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub calmar_ratio: Option<f32>,
+    #[serde(default)]
+    pub benchmark_return_percent: f32,
+    #[serde(default)]
+    pub benchmark_curve: Vec<PlotPoint>,
+    #[serde(default)]
+    pub candles_processed: usize,
+    #[serde(default)]
+    pub time_in_market_percent: f32,
+    #[serde(default)]
+    pub round_trips: usize,
+    #[serde(default)]
+    pub avg_holding_candles: f32,
+    #[serde(default)]
+    #[ts(type = "number")]
+    pub avg_holding_ms: i64,
     pub total_trades: usize,
     pub buy_trades: usize,
     pub sell_trades: usize,
@@ -63,18 +193,302 @@ pub enum BacktestStatus {
     Failed,
 }
 
+/// Swap markets are charged funding on held positions during the backtest.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum MarketType {
+    #[default]
+    Spot,
+    Swap,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[ts(export, tag = "type")]
+pub enum SlippageModel {
+    VolumeImpact {
+        factor: f64,
+    },
+    OrderBook {
+        #[ts(optional)]
+        depth: Option<usize>,
+    },
+}
+
+impl SlippageModel {
+    pub fn validate(&self) -> AppResult<()> {
+        match self {
+            SlippageModel::VolumeImpact { factor } if !factor.is_finite() || *factor < 0.0 => Err(
+                AppError::BadRequest(format!("Invalid volume impact factor: {}", factor)),
+            ),
+            SlippageModel::OrderBook { depth: Some(0) } => Err(AppError::BadRequest(
+                "Order book depth must be positive".into(),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    async fn resolve(&self, ccxt: &CCXT, symbol: &str) -> AppResult<Slippage> {
+        match self {
+            SlippageModel::VolumeImpact { factor } => {
+                let Some(factor) = BigDecimal::from_f64(*factor) else {
+                    return Err(format!("Invalid volume impact factor: {}", factor).into());
+                };
+                Ok(Slippage::VolumeImpact { factor })
+            }
+            SlippageModel::OrderBook { depth } => {
+                let book = ccxt
+                    .fetch_order_book_async(symbol, depth.map(|depth| depth as i64))
+                    .await?;
+                if book.mid_price().is_none() {
+                    return Err(format!("Order book for {} is empty", symbol).into());
+                }
+                Ok(Slippage::OrderBook(book))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestMarket {
+    pub exchange: String,
+    pub symbol: String,
+    pub timeframe: Timeframe,
+    pub candles: Vec<Candle>,
+    pub fees: TradingFees,
+    pub precision: MarketPrecision,
+    pub limits: MarketLimits,
+    pub slippage: Slippage,
+    pub limit_fill_ratio: Option<BigDecimal>,
+    pub market_volume_ratio: Option<BigDecimal>,
+    pub funding_rates: Vec<FundingRate>,
+    /// Candles of additional timeframes, keyed by timeframe.
+    pub timeframe_candles: HashMap<Timeframe, Vec<Candle>>,
+}
+
+impl BacktestMarket {
+    pub async fn load(
+        db_pool: &PgPool,
+        exchange: &str,
+        symbol: &str,
+        timeframe: Timeframe,
+        start: Option<DateTime<Utc>>,
+        end: Option<DateTime<Utc>>,
+    ) -> AppResult<Self> {
+        let candles = get_candles(db_pool, exchange, symbol, timeframe, start, end).await?;
+        if candles.is_empty() {
+            if start.is_some() || end.is_some() {
+                return Err(
+                    format!("No candles available for {} in the requested range", symbol).into(),
+                );
+            }
+            return Err(format!("No candles available for {}", symbol).into());
+        }
+
+        let ccxt = CCXT::with_exchange_async(exchange).await?;
+        let fees = ccxt.fees_async(symbol).await?;
+        let precision = ccxt.precision_async(symbol).await?;
+        let limits = ccxt.limits_async(symbol).await?;
+
+        Ok(Self {
+            exchange: exchange.to_string(),
+            symbol: symbol.to_string(),
+            timeframe,
+            candles,
+            fees,
+            precision,
+            limits,
+            slippage: Slippage::None,
+            limit_fill_ratio: None,
+            market_volume_ratio: None,
+            funding_rates: Vec::new(),
+            timeframe_candles: HashMap::new(),
+        })
+    }
+
+    pub async fn load_timeframe(
+        &mut self,
+        db_pool: &PgPool,
+        timeframe: Timeframe,
+        start: Option<DateTime<Utc>>,
+        end: Option<DateTime<Utc>>,
+    ) -> AppResult<()> {
+        let candles =
+            get_candles(db_pool, &self.exchange, &self.symbol, timeframe, start, end).await?;
+        if candles.is_empty() {
+            return Err(format!("No {} candles available for {}", timeframe, self.symbol).into());
+        }
+
+        self.timeframe_candles.insert(timeframe, candles);
+        Ok(())
+    }
+
+    /// A copy of the market limited to the given range of its candles.
+    pub fn slice(&self, range: Range<usize>) -> Self {
+        Self {
+            exchange: self.exchange.clone(),
+            symbol: self.symbol.clone(),
+            timeframe: self.timeframe,
+            candles: self.candles[range].to_vec(),
+            fees: self.fees.clone(),
+            precision: self.precision.clone(),
+            limits: self.limits.clone(),
+            slippage: self.slippage.clone(),
+            limit_fill_ratio: self.limit_fill_ratio.clone(),
+            market_volume_ratio: self.market_volume_ratio.clone(),
+            funding_rates: self.funding_rates.clone(),
+            timeframe_candles: self.timeframe_candles.clone(),
+        }
+    }
+
+    /// Fetches any funding rates missing from the database over the candle
+    /// range, then loads them into the market.
+    pub async fn load_funding_rates(&mut self, db_pool: &PgPool, ccxt: &CCXT) -> AppResult<()> {
+        let (Some(start), Some(end)) = (self.candles.first(), self.candles.last()) else {
+            return Ok(());
+        };
+        let (start, end) = (start.timestamp, end.timestamp);
+
+        let mut since = match get_funding_rate_range(db_pool, &self.exchange, &self.symbol).await? {
+            Some((first, last)) if first <= start => last + TimeDelta::milliseconds(1),
+            _ => start,
+        };
+        let delay_ms = ccxt.rate_limit_ms_async().await?;
+        while since <= end {
+            let rates = ccxt
+                .fetch_funding_rates_async(
+                    &self.symbol,
+                    Some(since.timestamp_millis()),
+                    Some(FUNDING_RATE_FETCH_LIMIT),
+                )
+                .await?;
+            let Some(last) = rates.last() else {
+                break;
+            };
+            if last.timestamp < since {
+                break;
+            }
+
+            since = last.timestamp + TimeDelta::milliseconds(1);
+            insert_funding_rates(db_pool, &rates).await?;
+            tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+        }
+
+        self.funding_rates =
+            get_funding_rates(db_pool, &self.exchange, &self.symbol, start, end).await?;
+        Ok(())
+    }
+}
+
+/// A fee-free market over the given candles for test fixtures, available to
+/// dependent crates through the `test-util` feature.
+#[cfg(any(test, feature = "test-util"))]
+impl BacktestMarket {
+    pub fn test(candles: Vec<Candle>) -> Self {
+        let first = candles.first();
+        Self {
+            exchange: first.map_or("test", |candle| &candle.exchange).to_string(),
+            symbol: first
+                .map_or("BTC/USDT", |candle| &candle.symbol)
+                .to_string(),
+            timeframe: first.map_or(Timeframe::M1, |candle| candle.timeframe),
+            candles,
+            fees: TradingFees {
+                maker: BigDecimal::zero(),
+                taker: BigDecimal::zero(),
+            },
+            precision: MarketPrecision {
+                price_precision: BigDecimal::zero(),
+                amount_precision: BigDecimal::zero(),
+                fee_precision: BigDecimal::zero(),
+                fee_rounding: FeeRounding::default(),
+            },
+            limits: MarketLimits::default(),
+            slippage: Slippage::None,
+            limit_fill_ratio: None,
+            market_volume_ratio: None,
+            funding_rates: Vec::new(),
+            timeframe_candles: HashMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct BacktestProgress {
+    pub task_id: Uuid,
+    pub progress: f32,
+    pub trades: Vec<Trade>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct BacktestResult {
+    pub id: Uuid,
+    pub name: String,
+    pub exchange: String,
+    pub symbol: String,
+    pub timeframe: Timeframe,
+    #[ts(optional, type = "Record<string, unknown>")]
+    pub params: Option<serde_json::Value>,
+    pub statistic: BacktestStatistic,
+    #[serde(with = "ts_milliseconds")]
+    #[ts(type = "number")]
+    pub completed_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[ts(export)]
 pub struct BacktestTask {
     pub id: Uuid,
     pub status: BacktestStatus,
     pub progress: f32,
+    /// Candles processed per second.
+    #[serde(default)]
+    #[ts(optional)]
+    pub throughput: Option<f64>,
+    /// Estimated seconds until the task completes at the current throughput.
+    #[serde(default)]
+    #[ts(optional)]
+    pub eta_secs: Option<f64>,
     pub name: String,
     pub exchange: String,
     pub symbol: String,
     pub timeframe: Timeframe,
     pub precision: MarketPrecision,
     #[ts(optional)]
+    pub extra_symbols: Option<Vec<String>>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub extra_timeframes: Option<Vec<Timeframe>>,
+    #[ts(optional, type = "Record<string, unknown>")]
+    pub params: Option<serde_json::Value>,
+    /// First candle of the backtest, including warmup candles.
+    #[serde(default, with = "ts_milliseconds_option")]
+    #[ts(optional, type = "number")]
+    pub start: Option<DateTime<Utc>>,
+    /// Last candle of the backtest.
+    #[serde(default, with = "ts_milliseconds_option")]
+    #[ts(optional, type = "number")]
+    pub end: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub warmup: usize,
+    #[serde(default)]
+    pub execution_timing: ExecutionTiming,
+    #[ts(optional)]
+    pub slippage: Option<SlippageModel>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub limit_fill_ratio: Option<f64>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub market_volume_ratio: Option<f64>,
+    #[serde(default)]
+    pub market_type: MarketType,
+    #[serde(default)]
+    pub fee_rounding: FeeRounding,
+    #[ts(optional)]
     pub statistic: Option<BacktestStatistic>,
     #[ts(optional)]
     pub error_message: Option<String>,
@@ -93,6 +507,9 @@ pub struct BacktestTask {
     #[serde(skip)]
     #[ts(skip)]
     pub event_tx: Option<broadcast::Sender<BacktestTask>>,
+    #[serde(skip)]
+    #[ts(skip)]
+    pub progress_tx: Option<broadcast::Sender<BacktestProgress>>,
 }
 
 impl BacktestTask {
@@ -102,11 +519,50 @@ impl BacktestTask {
         }
     }
 
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self.status,
+            BacktestStatus::Completed | BacktestStatus::Failed
+        )
+    }
+
+    pub fn finished_at(&self) -> Option<DateTime<Utc>> {
+        self.is_finished()
+            .then(|| self.completed_at.unwrap_or(self.updated_at))
+    }
+
+    pub fn summary(&self) -> TaskSummary {
+        TaskSummary {
+            id: self.id,
+            task_type: TaskType::Backtest,
+            status: (&self.status).into(),
+            progress: self.progress,
+            error_message: self.error_message.clone(),
+            created_at: self.created_at,
+            started_at: self.started_at,
+            completed_at: self.completed_at,
+            updated_at: self.updated_at,
+        }
+    }
+
+    pub fn broadcast_progress(&self, trades: &[Trade]) {
+        if let Some(tx) = &self.progress_tx {
+            let _ = tx.send(BacktestProgress {
+                task_id: self.id,
+                progress: self.progress,
+                trades: trades.to_vec(),
+            });
+        }
+    }
+
     pub async fn execute(
         &mut self,
         strategy_manager: &StrategyManager,
         strategy_name: &str,
+        force_rebuild: bool,
+        limits: RunLimits,
         db_pool: PgPool,
+        cancel: &CancellationToken,
     ) {
         let now = Utc::now();
         self.status = BacktestStatus::Compiling;
@@ -114,12 +570,25 @@ impl BacktestTask {
         self.updated_at = now;
         self.broadcast();
 
-        let mut strategy_handle = match strategy_manager.load_strategy(strategy_name).await {
-            Ok(handle) => handle,
+        let strategy_runner = tokio::select! {
+            biased;
+            _ = cancel.cancelled() => Err(AppError::Cancelled),
+            result = StrategyRunner::load(
+                strategy_manager,
+                strategy_name,
+                force_rebuild,
+                self.params.as_ref(),
+            ) => result,
+        };
+        let strategy_runner = match strategy_runner {
+            Ok(runner) => runner,
             Err(e) => {
                 let now = Utc::now();
                 self.status = BacktestStatus::Failed;
-                self.error_message = Some(format!("Failed to load strategy: {}", e));
+                self.error_message = Some(match e {
+                    AppError::Cancelled => e.to_string(),
+                    e => format!("Failed to load strategy: {}", e),
+                });
                 self.completed_at = Some(now);
                 self.updated_at = now;
                 self.broadcast();
@@ -133,18 +602,24 @@ impl BacktestTask {
         self.updated_at = now;
         self.broadcast();
 
-        let result = self.execute_backtest(&db_pool, &mut strategy_handle).await;
+        let result = tokio::select! {
+            biased;
+            _ = cancel.cancelled() => Err(AppError::Cancelled),
+            result = self.execute_backtest(&db_pool, strategy_runner, limits) => result,
+        };
         let now = Utc::now();
         match result {
             Ok(statistic) => {
                 self.status = BacktestStatus::Completed;
                 self.progress = 100.0;
+                self.eta_secs = None;
                 self.statistic = Some(statistic);
                 self.completed_at = Some(now);
                 self.updated_at = now;
             }
             Err(e) => {
                 self.status = BacktestStatus::Failed;
+                self.eta_secs = None;
                 self.error_message = Some(e.to_string());
                 self.completed_at = Some(now);
                 self.updated_at = now;
@@ -156,76 +631,361 @@ impl BacktestTask {
         save_backtest_task(&db_pool, self)
             .await
             .expect("Failed to save backtest task");
+
+        if let (Some(statistic), Some(completed_at)) = (&self.statistic, self.completed_at) {
+            let result = BacktestResult {
+                id: self.id,
+                name: self.name.clone(),
+                exchange: self.exchange.clone(),
+                symbol: self.symbol.clone(),
+                timeframe: self.timeframe,
+                params: self.params.clone(),
+                statistic: statistic.clone(),
+                completed_at,
+            };
+            if let Err(e) = save_backtest_result(&db_pool, &result).await {
+                tracing::error!("Failed to save backtest result {}: {}", self.id, e);
+            }
+        }
     }
 
     async fn execute_backtest(
         &mut self,
         db_pool: &PgPool,
-        strategy_handle: &mut StrategyHandle,
+        strategy_runner: StrategyRunner,
+        limits: RunLimits,
     ) -> AppResult<BacktestStatistic> {
         let exchange = self.exchange.clone();
-        let symbol = self.symbol.clone();
         let timeframe = self.timeframe;
 
+        let mut symbols = vec![self.symbol.clone()];
+        for symbol in self.extra_symbols.iter().flatten() {
+            if !symbols.contains(symbol) {
+                symbols.push(symbol.clone());
+            }
+        }
+
         tracing::info!(
             "Running backtest on {}/{} with timeframe {}",
             exchange,
-            symbol,
+            symbols.join(","),
             timeframe
         );
 
-        let all_candles = get_candles(db_pool, &exchange, &symbol, timeframe, None, None).await?;
-        let total_candles = all_candles.len();
-        if total_candles == 0 {
-            return Err("No candles available for backtest".into());
+        let mut markets = Vec::with_capacity(symbols.len());
+        for symbol in &symbols {
+            markets.push(
+                BacktestMarket::load(db_pool, &exchange, symbol, timeframe, self.start, self.end)
+                    .await?,
+            );
+        }
+
+        for extra_timeframe in self.extra_timeframes.iter().flatten() {
+            if *extra_timeframe == timeframe {
+                continue;
+            }
+            for market in &mut markets {
+                market
+                    .load_timeframe(db_pool, *extra_timeframe, self.start, self.end)
+                    .await?;
+            }
+        }
+
+        if self.market_type == MarketType::Swap {
+            let ccxt = CCXT::with_exchange_async(&exchange).await?;
+            for market in &mut markets {
+                market.load_funding_rates(db_pool, &ccxt).await?;
+            }
         }
 
+        if let Some(slippage) = &self.slippage {
+            let ccxt = CCXT::with_exchange_async(&exchange).await?;
+            for market in &mut markets {
+                market.slippage = slippage.resolve(&ccxt, &market.symbol).await?;
+            }
+        }
+
+        if let Some(ratio) = self.limit_fill_ratio {
+            let Some(ratio) = BigDecimal::from_f64(ratio) else {
+                return Err(format!("Invalid limit fill ratio: {}", ratio).into());
+            };
+            for market in &mut markets {
+                market.limit_fill_ratio = Some(ratio.clone());
+            }
+        }
+
+        if let Some(ratio) = self.market_volume_ratio {
+            let Some(ratio) = BigDecimal::from_f64(ratio) else {
+                return Err(format!("Invalid market volume ratio: {}", ratio).into());
+            };
+            for market in &mut markets {
+                market.market_volume_ratio = Some(ratio.clone());
+            }
+        }
+
+        for market in &mut markets {
+            market.precision.fee_rounding = self.fee_rounding;
+        }
+
+        let warmup = self.warmup;
+        let timing = self.execution_timing;
+        let total_candles = markets.first().map_or(0, |market| market.candles.len()) as u64;
+        let throughput = Throughput::start();
+        let backtest_stat = strategy_runner
+            .run(
+                markets.into(),
+                warmup,
+                timing,
+                limits,
+                |progress, trades| {
+                    self.progress = progress;
+                    let processed = (progress as f64 / 100.0 * total_candles as f64).round() as u64;
+                    if let Some((rate, eta)) = throughput.measure(processed, total_candles) {
+                        self.throughput = Some(rate);
+                        self.eta_secs = Some(eta);
+                    }
+                    self.updated_at = Utc::now();
+                    self.broadcast();
+                    self.broadcast_progress(trades);
+                },
+            )
+            .await?;
+
+        Ok(backtest_stat)
+    }
+
+    /// Runs the backtest on a dedicated thread so that strategy code cannot
+    /// block the async runtime, failing once it stalls or overruns `limits`.
+    /// The thread is told to stop whenever this returns or is dropped, e.g.
+    /// when its task is cancelled, and does so before its next candle. Strategy
+    /// code that never returns cannot be interrupted and keeps its thread; only
+    /// the sandbox worker process, which is killed on drop, reclaims such code.
+    pub async fn run_backtest_watched(
+        mut strategy_handle: StrategyHandle,
+        markets: Arc<[BacktestMarket]>,
+        warmup: usize,
+        timing: ExecutionTiming,
+        limits: RunLimits,
+        mut on_progress: impl FnMut(f32, &[Trade]),
+    ) -> AppResult<BacktestStatistic> {
+        let (progress_tx, mut progress_rx) = mpsc::unbounded_channel();
+        let (result_tx, mut result_rx) = oneshot::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let _stop_on_drop = StopOnDrop(stop.clone());
+        std::thread::Builder::new()
+            .name("backtest".to_string())
+            .spawn(move || {
+                let result = Self::run_backtest(
+                    &mut strategy_handle,
+                    &markets,
+                    warmup,
+                    timing,
+                    OrderIds::Random,
+                    &stop,
+                    |progress, trades| {
+                        let _ = progress_tx.send((progress, trades.to_vec()));
+                    },
+                );
+                let _ = result_tx.send(result);
+            })?;
+
+        let mut stall_deadline = limits.stall_deadline();
+        let run_deadline = limits.run_deadline();
+        loop {
+            tokio::select! {
+                result = &mut result_rx => {
+                    while let Ok((progress, trades)) = progress_rx.try_recv() {
+                        on_progress(progress, &trades);
+                    }
+                    return result.unwrap_or_else(|_| Err("Backtest thread exited unexpectedly".into()));
+                }
+                Some((progress, trades)) = progress_rx.recv() => {
+                    on_progress(progress, &trades);
+                    stall_deadline = limits.stall_deadline();
+                }
+                _ = tokio::time::sleep_until(stall_deadline.unwrap_or_else(Instant::now)), if stall_deadline.is_some() => {
+                    return Err(limits.stalled_error());
+                }
+                _ = tokio::time::sleep_until(run_deadline.unwrap_or_else(Instant::now)), if run_deadline.is_some() => {
+                    return Err(limits.overran_error());
+                }
+            }
+        }
+    }
+
+    /// Runs a backtest on the current thread. Tasks draw random order IDs;
+    /// tests can pass `OrderIds::Sequential` to assert exact IDs.
+    pub fn run_backtest(
+        strategy_handle: &mut StrategyHandle,
+        markets: &[BacktestMarket],
+        warmup: usize,
+        timing: ExecutionTiming,
+        order_ids: OrderIds,
+        stop: &AtomicBool,
+        mut on_progress: impl FnMut(f32, &[Trade]),
+    ) -> AppResult<BacktestStatistic> {
+        let check_stop = || {
+            if stop.load(Ordering::Relaxed) {
+                Err(AppError::Cancelled)
+            } else {
+                Ok(())
+            }
+        };
+
+        let Some((primary, others)) = markets.split_first() else {
+            return Err("No markets available for backtest".into());
+        };
+
+        let total_candles = primary.candles.len();
+        if warmup >= total_candles {
+            return Err(format!(
+                "Warmup of {} candles leaves no candles to backtest, only {} available",
+                warmup, total_candles
+            )
+            .into());
+        }
         let initial_capital = BigDecimal::from(10000);
-        let ccxt = CCXT::with_exchange(&exchange)?;
-        let fees = ccxt.fees(&symbol)?;
-        let precision = ccxt.precision(&symbol)?;
-        let mut context = StrategyContext::new(initial_capital.clone(), fees, precision)?;
+        let mut context = StrategyContext::new(
+            initial_capital.clone(),
+            &primary.exchange,
+            &primary.symbol,
+            primary.timeframe,
+            primary.fees.clone(),
+            primary.precision.clone(),
+            primary.limits.clone(),
+        )?;
+        for market in others {
+            context.add_market(
+                &market.symbol,
+                market.fees.clone(),
+                market.precision.clone(),
+                market.limits.clone(),
+            );
+        }
+        for market in markets {
+            context.set_slippage(&market.symbol, market.slippage.clone())?;
+            context.set_limit_fill_ratio(&market.symbol, market.limit_fill_ratio.clone())?;
+            context.set_market_volume_ratio(&market.symbol, market.market_volume_ratio.clone())?;
+            context.set_funding_rates(&market.symbol, &market.funding_rates)?;
+        }
+        context.set_execution_timing(timing);
+        context.set_order_ids(order_ids);
+
+        // Under NextOpen the strategy only sees earlier candles, so it needs at
+        // least one before its first tick.
+        let first_tick = match timing {
+            ExecutionTiming::Close => warmup,
+            ExecutionTiming::NextOpen => warmup.max(1),
+        };
 
-        for i in 0..all_candles.len() {
-            context.candles = &all_candles[0..=i];
+        let mut offsets = vec![0usize; others.len()];
+        let mut timeframe_offsets: Vec<HashMap<Timeframe, usize>> =
+            vec![HashMap::new(); markets.len()];
+        let mut reported_trades = 0;
 
+        for i in 0..total_candles {
+            let candle = &primary.candles[i];
+            context.set_candles(&primary.symbol, &primary.candles[0..=i])?;
+            for (market, offset) in others.iter().zip(offsets.iter_mut()) {
+                while let Some(market_candle) = market.candles.get(*offset)
+                    && market_candle.timestamp <= candle.timestamp
+                {
+                    *offset += 1;
+                }
+                context.set_candles(&market.symbol, &market.candles[0..*offset])?;
+            }
+
+            // Other timeframes only expose candles that have closed by the time
+            // the strategy acts, so strategies cannot look ahead.
+            let close_time = match timing {
+                ExecutionTiming::Close => primary.timeframe.advance(candle.timestamp),
+                ExecutionTiming::NextOpen => candle.timestamp,
+            };
+            for (market, offsets) in markets.iter().zip(timeframe_offsets.iter_mut()) {
+                for (timeframe, candles) in &market.timeframe_candles {
+                    let offset = offsets.entry(*timeframe).or_default();
+                    while let Some(timeframe_candle) = candles.get(*offset)
+                        && timeframe.advance(timeframe_candle.timestamp) <= close_time
+                    {
+                        *offset += 1;
+                    }
+                    context.set_timeframe_candles(
+                        &market.symbol,
+                        *timeframe,
+                        &candles[0..*offset],
+                    )?;
+                }
+            }
+
+            if i < first_tick {
+                continue;
+            }
+
+            check_stop()?;
             context.before()?;
+            if i == first_tick {
+                strategy_handle.on_start(&mut context)?;
+                check_stop()?;
+            }
             strategy_handle.tick(&mut context)?;
             context.after()?;
 
             if i % BACKTEST_BROADCAST_INTERVAL == 0 {
-                let progress = 100.0 * ((i + 1) as f32) / (total_candles as f32);
-                self.progress = progress;
-                self.updated_at = Utc::now();
-                self.broadcast();
+                on_progress(
+                    100.0 * ((i + 1) as f32) / (total_candles as f32),
+                    &context.trades()[reported_trades..],
+                );
+                reported_trades = context.trades().len();
             }
         }
 
+        check_stop()?;
         context.end()?;
-        self.progress = 100.0;
-        self.updated_at = Utc::now();
-        self.broadcast();
+        strategy_handle.on_finish(&mut context)?;
+        on_progress(100.0, &context.trades()[reported_trades..]);
 
-        let backtest_stat = Self::calculate_backtest_statistic(
+        let mut backtest_stat = Self::calculate_backtest_statistic(
             initial_capital,
-            context.candles(),
+            markets,
+            warmup,
             context.trades(),
+            context.funding_payments(),
         );
+        backtest_stat.funding_payments = context.funding_payments().to_vec();
+        backtest_stat.logs = context.logs().to_vec();
+        backtest_stat.plots = context.plots().clone();
+        backtest_stat.markers = context.markers().to_vec();
 
         Ok(backtest_stat)
     }
 
     fn calculate_backtest_statistic(
         initial_capital: BigDecimal,
-        candles: &[Candle],
+        markets: &[BacktestMarket],
+        warmup: usize,
         trades: &[Trade],
+        funding_payments: &[FundingPayment],
     ) -> BacktestStatistic {
+        let (primary, others) = markets
+            .split_first()
+            .expect("Backtest requires at least one market");
+
         let mut balance = initial_capital.clone();
-        let mut position = BigDecimal::zero();
-        let mut total_cost = BigDecimal::zero();
+        let mut positions: HashMap<&str, BigDecimal> = HashMap::new();
+        let mut total_costs: HashMap<&str, BigDecimal> = HashMap::new();
+        let mut prices: HashMap<&str, BigDecimal> = HashMap::new();
+        let mut offsets = vec![0usize; others.len()];
         let mut max_equity = initial_capital.clone();
         let mut max_drawdown = BigDecimal::zero();
         let mut max_drawdown_percent = 0.0f32;
+        let mut equity_curve = Vec::with_capacity(primary.candles.len());
+        let mut benchmark_curve = Vec::with_capacity(primary.candles.len());
+        let benchmark_amount = primary
+            .candles
+            .get(warmup)
+            .filter(|candle| !candle.close.is_zero())
+            .map(|candle| &initial_capital / &candle.close)
+            .unwrap_or_else(BigDecimal::zero);
 
         let mut buy_trades = 0usize;
         let mut sell_trades = 0usize;
@@ -235,25 +995,50 @@ impl BacktestTask {
         let mut gross_loss = BigDecimal::zero();
         let mut largest_win = BigDecimal::zero();
         let mut largest_loss = BigDecimal::zero();
+        let mut opened_positions: HashMap<&str, (usize, DateTime<Utc>)> = HashMap::new();
+        let mut round_trips = 0usize;
+        let mut holding_candles = 0usize;
+        let mut holding_ms = 0i64;
+        let mut candles_in_market = 0usize;
 
         let mut trades_iter = trades.iter().peekable();
         let mut trades_with_profit = Vec::with_capacity(trades.len());
+        let mut funding_iter = funding_payments.iter().peekable();
+        let mut funding_cost = BigDecimal::zero();
+        let mut total_fees = BigDecimal::zero();
+        // Buy fees still in the cost basis of open positions, and the fees
+        // already deducted from realized profit.
+        let mut open_fees: HashMap<&str, BigDecimal> = HashMap::new();
+        let mut realized_fees = BigDecimal::zero();
 
-        for candle in candles.iter() {
+        for (i, candle) in primary.candles.iter().enumerate() {
             while let Some(trade) = trades_iter.peek() {
                 if trade.timestamp > candle.timestamp {
                     break;
                 }
 
                 let trade = trades_iter.next().unwrap();
-                let is_buy = matches!(trade.trade_type, TradeType::MarketBuy | TradeType::LimitBuy);
+                let is_buy = trade.trade_type.is_buy();
+                total_fees += &trade.fee;
+                let position = positions
+                    .entry(trade.symbol.as_str())
+                    .or_insert_with(BigDecimal::zero);
+                let total_cost = total_costs
+                    .entry(trade.symbol.as_str())
+                    .or_insert_with(BigDecimal::zero);
 
                 if is_buy {
                     buy_trades += 1;
+                    *open_fees
+                        .entry(trade.symbol.as_str())
+                        .or_insert_with(BigDecimal::zero) += &trade.fee;
                     let cost = &trade.price * &trade.amount + &trade.fee;
-                    total_cost += &cost;
+                    *total_cost += &cost;
                     balance -= &cost;
-                    position += &trade.amount;
+                    if position.is_zero() {
+                        opened_positions.insert(trade.symbol.as_str(), (i, trade.timestamp));
+                    }
+                    *position += &trade.amount;
                     trades_with_profit.push(trade.clone());
                 } else {
                     sell_trades += 1;
@@ -262,17 +1047,39 @@ impl BacktestTask {
                     let average_cost = if position.is_zero() {
                         BigDecimal::zero()
                     } else {
-                        &total_cost / &position
+                        &*total_cost / &*position
+                    };
+                    let open_fee = open_fees
+                        .entry(trade.symbol.as_str())
+                        .or_insert_with(BigDecimal::zero);
+                    let released_fee = if position.is_zero() || trade.amount >= *position {
+                        open_fee.clone()
+                    } else {
+                        &*open_fee * &trade.amount / &*position
                     };
-                    let profit = &revenue - (&average_cost * &trade.amount);
+                    *open_fee -= &released_fee;
+                    realized_fees += &released_fee + &trade.fee;
+                    let profit = trade
+                        .profit
+                        .clone()
+                        .unwrap_or_else(|| &revenue - (&average_cost * &trade.amount));
 
-                    position -= &trade.amount;
+                    *position -= &trade.amount;
                     balance += &revenue;
 
+                    if position.is_zero()
+                        && let Some((opened_index, opened_at)) =
+                            opened_positions.remove(trade.symbol.as_str())
+                    {
+                        round_trips += 1;
+                        holding_candles += i - opened_index;
+                        holding_ms += (trade.timestamp - opened_at).num_milliseconds();
+                    }
+
                     if position.is_zero() {
-                        total_cost = BigDecimal::zero();
+                        *total_cost = BigDecimal::zero();
                     } else {
-                        total_cost -= &average_cost * &trade.amount;
+                        *total_cost -= &average_cost * &trade.amount;
                     }
 
                     if profit > BigDecimal::zero() {
@@ -291,7 +1098,9 @@ impl BacktestTask {
 
                     trades_with_profit.push(Trade {
                         timestamp: trade.timestamp,
+                        symbol: trade.symbol.clone(),
                         trade_type: trade.trade_type.clone(),
+                        intent: trade.intent,
                         price: trade.price.clone(),
                         amount: trade.amount.clone(),
                         fee: trade.fee.clone(),
@@ -300,12 +1109,53 @@ impl BacktestTask {
                 }
             }
 
-            let high_value = &position * &candle.high + &balance;
+            while let Some(payment) = funding_iter.next_if(|p| p.timestamp <= candle.timestamp) {
+                balance -= &payment.amount;
+                funding_cost += &payment.amount;
+            }
+
+            for (market, offset) in others.iter().zip(offsets.iter_mut()) {
+                while let Some(market_candle) = market.candles.get(*offset)
+                    && market_candle.timestamp <= candle.timestamp
+                {
+                    prices.insert(market.symbol.as_str(), market_candle.close.clone());
+                    *offset += 1;
+                }
+            }
+
+            if i < warmup {
+                continue;
+            }
+
+            if positions.values().any(|position| !position.is_zero()) {
+                candles_in_market += 1;
+            }
+
+            let mut high_value = balance.clone();
+            let mut low_value = balance.clone();
+            let mut close_value = balance.clone();
+            for (symbol, position) in &positions {
+                if *symbol == primary.symbol {
+                    high_value += position * &candle.high;
+                    low_value += position * &candle.low;
+                    close_value += position * &candle.close;
+                } else if let Some(price) = prices.get(symbol) {
+                    high_value += position * price;
+                    low_value += position * price;
+                    close_value += position * price;
+                }
+            }
+            equity_curve.push(close_value.to_f64().unwrap_or(0.0));
+            benchmark_curve.push(PlotPoint {
+                timestamp: candle.timestamp,
+                value: (&benchmark_amount * &candle.close)
+                    .with_scale_round(2, RoundingMode::HalfUp),
+            });
+
             if high_value > max_equity {
                 max_equity = high_value;
             }
 
-            let low_value = &position * &candle.low + &balance;
             let drawdown = &max_equity - &low_value;
             if drawdown > max_drawdown {
                 max_drawdown = drawdown.clone();
@@ -316,15 +1166,31 @@ impl BacktestTask {
             }
         }
 
-        while let Some(trade) = trades_iter.next() {
-            let is_buy = matches!(trade.trade_type, TradeType::MarketBuy | TradeType::LimitBuy);
+        for trade in trades_iter {
+            let is_buy = trade.trade_type.is_buy();
+            total_fees += &trade.fee;
+            let position = positions
+                .entry(trade.symbol.as_str())
+                .or_insert_with(BigDecimal::zero);
+            let total_cost = total_costs
+                .entry(trade.symbol.as_str())
+                .or_insert_with(BigDecimal::zero);
 
             if is_buy {
                 buy_trades += 1;
+                *open_fees
+                    .entry(trade.symbol.as_str())
+                    .or_insert_with(BigDecimal::zero) += &trade.fee;
                 let cost = &trade.price * &trade.amount + &trade.fee;
-                total_cost += &cost;
+                *total_cost += &cost;
                 balance -= &cost;
-                position += &trade.amount;
+                if position.is_zero() {
+                    opened_positions.insert(
+                        trade.symbol.as_str(),
+                        (primary.candles.len(), trade.timestamp),
+                    );
+                }
+                *position += &trade.amount;
                 trades_with_profit.push(trade.clone());
             } else {
                 sell_trades += 1;
@@ -333,17 +1199,39 @@ impl BacktestTask {
                 let average_cost = if position.is_zero() {
                     BigDecimal::zero()
                 } else {
-                    &total_cost / &position
+                    &*total_cost / &*position
+                };
+                let open_fee = open_fees
+                    .entry(trade.symbol.as_str())
+                    .or_insert_with(BigDecimal::zero);
+                let released_fee = if position.is_zero() || trade.amount >= *position {
+                    open_fee.clone()
+                } else {
+                    &*open_fee * &trade.amount / &*position
                 };
-                let profit = &revenue - (&average_cost * &trade.amount);
+                *open_fee -= &released_fee;
+                realized_fees += &released_fee + &trade.fee;
+                let profit = trade
+                    .profit
+                    .clone()
+                    .unwrap_or_else(|| &revenue - (&average_cost * &trade.amount));
 
-                position -= &trade.amount;
+                *position -= &trade.amount;
                 balance += &revenue;
 
+                if position.is_zero()
+                    && let Some((opened_index, opened_at)) =
+                        opened_positions.remove(trade.symbol.as_str())
+                {
+                    round_trips += 1;
+                    holding_candles += primary.candles.len() - opened_index;
+                    holding_ms += (trade.timestamp - opened_at).num_milliseconds();
+                }
+
                 if position.is_zero() {
-                    total_cost = BigDecimal::zero();
+                    *total_cost = BigDecimal::zero();
                 } else {
-                    total_cost -= &average_cost * &trade.amount;
+                    *total_cost -= &average_cost * &trade.amount;
                 }
 
                 if profit > BigDecimal::zero() {
@@ -362,7 +1250,9 @@ impl BacktestTask {
 
                 trades_with_profit.push(Trade {
                     timestamp: trade.timestamp,
+                    symbol: trade.symbol.clone(),
                     trade_type: trade.trade_type.clone(),
+                    intent: trade.intent,
                     price: trade.price.clone(),
                     amount: trade.amount.clone(),
                     fee: trade.fee.clone(),
@@ -371,6 +1261,10 @@ impl BacktestTask {
             }
         }
 
+        let total_cost = total_costs
+            .into_values()
+            .fold(BigDecimal::zero(), |total, cost| total + cost);
+
         let total_trades = buy_trades + sell_trades;
         let win_rate = if sell_trades > 0 {
             (winning_trades as f32 / sell_trades as f32) * 100.0
@@ -402,7 +1296,8 @@ impl BacktestTask {
             (&gross_profit / &gross_loss.abs()).to_f32().unwrap_or(0.0)
         };
 
-        let net_profit = (&gross_profit + &gross_loss).with_scale_round(2, RoundingMode::HalfUp);
+        let net_profit =
+            (&gross_profit + &gross_loss - &funding_cost).with_scale_round(2, RoundingMode::HalfUp);
 
         let return_percent = if !initial_capital.is_zero() {
             (&net_profit / &initial_capital).to_f32().unwrap_or(0.0) * 100.0
@@ -410,27 +1305,91 @@ impl BacktestTask {
             0.0
         };
 
+        let gross_return_percent = if !initial_capital.is_zero() {
+            ((&net_profit + &realized_fees) / &initial_capital)
+                .to_f32()
+                .unwrap_or(0.0)
+                * 100.0
+        } else {
+            0.0
+        };
+
+        let benchmark_return_percent = match benchmark_curve.last() {
+            Some(point) if !initial_capital.is_zero() => {
+                ((&point.value - &initial_capital) / &initial_capital)
+                    .to_f32()
+                    .unwrap_or(0.0)
+                    * 100.0
+            }
+            _ => 0.0,
+        };
+
+        let candles_processed = primary.candles.len().saturating_sub(warmup);
+        let time_in_market_percent = if candles_processed > 0 {
+            (candles_in_market as f32 / candles_processed as f32) * 100.0
+        } else {
+            0.0
+        };
+
+        let (avg_holding_candles, avg_holding_ms) = if round_trips > 0 {
+            (
+                holding_candles as f32 / round_trips as f32,
+                holding_ms / round_trips as i64,
+            )
+        } else {
+            (0.0, 0)
+        };
+
         let sharpe_ratio = Self::calculate_sharpe_ratio(&trades_with_profit, &initial_capital);
 
+        let periods_per_year = primary
+            .candles
+            .first()
+            .map(|candle| Self::periods_per_year(candle.timeframe))
+            .unwrap_or(0.0);
+        let sortino_ratio = Self::calculate_sortino_ratio(&equity_curve, periods_per_year);
+        let calmar_ratio = Self::calculate_calmar_ratio(
+            &equity_curve,
+            initial_capital.to_f64().unwrap_or(0.0),
+            periods_per_year,
+            max_drawdown_percent,
+        );
+
         BacktestStatistic {
             trades: trades_with_profit,
+            logs: Vec::new(),
+            plots: BTreeMap::new(),
+            markers: Vec::new(),
+            funding_payments: Vec::new(),
             initial_capital,
             total_cost,
+            funding_cost,
+            total_fees,
             net_profit,
-            return_percent,
+            return_percent: round_metric(return_percent),
+            gross_return_percent: round_metric(gross_return_percent),
             max_equity,
             max_drawdown,
-            max_drawdown_percent,
+            max_drawdown_percent: round_metric(max_drawdown_percent),
             gross_profit,
             gross_loss,
-            profit_factor,
-            sharpe_ratio,
+            profit_factor: round_metric(profit_factor),
+            sharpe_ratio: round_metric(sharpe_ratio),
+            sortino_ratio: sortino_ratio.map(round_metric),
+            calmar_ratio: calmar_ratio.map(round_metric),
+            benchmark_return_percent: round_metric(benchmark_return_percent),
+            benchmark_curve,
+            candles_processed,
+            time_in_market_percent: round_metric(time_in_market_percent),
+            round_trips,
+            avg_holding_candles: round_metric(avg_holding_candles),
+            avg_holding_ms,
             total_trades,
             buy_trades,
             sell_trades,
             winning_trades,
             losing_trades,
-            win_rate,
+            win_rate: round_metric(win_rate),
             avg_win,
             avg_loss,
             largest_win,
@@ -489,4 +1448,253 @@ impl BacktestTask {
 
         (mean_return / std_dev) as f32
     }
+
+    fn periods_per_year(timeframe: Timeframe) -> f64 {
+        TimeDelta::days(365).num_milliseconds() as f64 / timeframe.to_ms() as f64
+    }
+
+    fn calculate_sortino_ratio(equity_curve: &[f64], periods_per_year: f64) -> Option<f32> {
+        let returns: Vec<f64> = equity_curve
+            .windows(2)
+            .filter(|window| window[0] > 0.0)
+            .map(|window| window[1] / window[0] - 1.0)
+            .collect();
+
+        if returns.len() < 2 {
+            return None;
+        }
+
+        let mean_return = returns.iter().sum::<f64>() / returns.len() as f64;
+        let downside_variance =
+            returns.iter().map(|r| r.min(0.0).powi(2)).sum::<f64>() / returns.len() as f64;
+        let downside_deviation = downside_variance.sqrt();
+
+        if downside_deviation == 0.0 {
+            return None;
+        }
+
+        let sortino_ratio = mean_return / downside_deviation * periods_per_year.sqrt();
+        sortino_ratio.is_finite().then_some(sortino_ratio as f32)
+    }
+
+    fn calculate_calmar_ratio(
+        equity_curve: &[f64],
+        initial_capital: f64,
+        periods_per_year: f64,
+        max_drawdown_percent: f32,
+    ) -> Option<f32> {
+        let final_equity = *equity_curve.last()?;
+        if max_drawdown_percent <= 0.0 || initial_capital <= 0.0 || periods_per_year <= 0.0 {
+            return None;
+        }
+
+        let years = equity_curve.len() as f64 / periods_per_year;
+        let growth = final_equity / initial_capital;
+        let annual_return = if growth > 0.0 {
+            growth.powf(1.0 / years) - 1.0
+        } else {
+            -1.0
+        };
+
+        let calmar_ratio = annual_return * 100.0 / max_drawdown_percent as f64;
+        calmar_ratio.is_finite().then_some(calmar_ratio as f32)
+    }
+}
+
+fn round_metric(value: f32) -> f32 {
+    let scale = 10f64.powi(METRIC_DECIMALS);
+    ((value as f64 * scale).round() / scale) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::Strategy;
+    use std::str::FromStr;
+    use std::sync::Mutex;
+    use std::sync::atomic::AtomicUsize;
+
+    fn candles(count: usize) -> Vec<Candle> {
+        let start = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        (0..count)
+            .map(|i| Candle::test(start + TimeDelta::minutes(i as i64), 100))
+            .collect()
+    }
+
+    /// Counts its ticks, sleeping through each one.
+    struct Slow(Arc<AtomicUsize>);
+
+    impl Strategy for Slow {
+        fn tick(&mut self, _context: &mut StrategyContext) -> AppResult<()> {
+            self.0.fetch_add(1, Ordering::Relaxed);
+            std::thread::sleep(Duration::from_millis(20));
+            Ok(())
+        }
+    }
+
+    /// Places a far-away limit buy on every tick, recording the IDs it gets.
+    struct Bidder(Arc<Mutex<Vec<Uuid>>>);
+
+    impl Strategy for Bidder {
+        fn tick(&mut self, context: &mut StrategyContext) -> AppResult<()> {
+            let id = context.limit_buy(&BigDecimal::from(50), &BigDecimal::from(1))?;
+            self.0.lock().unwrap().extend(id);
+            Ok(())
+        }
+    }
+
+    fn order_ids_of(order_ids: OrderIds) -> Vec<Uuid> {
+        let ids = Arc::new(Mutex::new(Vec::new()));
+        let mut handle = StrategyHandle::from_strategy(Box::new(Bidder(ids.clone())));
+        BacktestTask::run_backtest(
+            &mut handle,
+            &[BacktestMarket::test(candles(3))],
+            0,
+            ExecutionTiming::Close,
+            order_ids,
+            &AtomicBool::new(false),
+            |_, _| {},
+        )
+        .unwrap();
+        ids.lock().unwrap().clone()
+    }
+
+    #[test]
+    fn sequential_order_ids_count_up() {
+        assert_eq!(
+            order_ids_of(OrderIds::Sequential(7)),
+            vec![Uuid::from_u128(7), Uuid::from_u128(8), Uuid::from_u128(9)]
+        );
+    }
+
+    #[test]
+    fn random_order_ids_are_v4() {
+        let ids = order_ids_of(OrderIds::Random);
+        assert_eq!(ids.len(), 3);
+        assert!(ids.iter().all(|id| id.get_version_num() == 4));
+        assert_ne!(ids[0], ids[1]);
+    }
+
+    /// Alternates market buys and sells, and keeps a limit buy resting below
+    /// the price, so a run has fees, profits and order fills to compare.
+    struct Trader;
+
+    impl Strategy for Trader {
+        fn tick(&mut self, context: &mut StrategyContext) -> AppResult<()> {
+            if context.position().is_zero() {
+                context.market_buy(&BigDecimal::from(2))?;
+            } else {
+                context.market_sell(&context.position())?;
+            }
+            if context.orders().is_empty() {
+                let price = context.candle()?.close - BigDecimal::from(3);
+                context.limit_buy(&price, &BigDecimal::from(1))?;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn identical_runs_give_identical_statistics() {
+        let start = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let closes = [100, 104, 97, 101, 95, 103, 108, 99, 102, 110];
+        let mut market = BacktestMarket::test(
+            closes
+                .iter()
+                .enumerate()
+                .map(|(i, close)| Candle::test(start + TimeDelta::minutes(i as i64), *close))
+                .collect(),
+        );
+        market.fees = TradingFees {
+            maker: BigDecimal::from_str("0.001").unwrap(),
+            taker: BigDecimal::from_str("0.002").unwrap(),
+        };
+        let markets = [market];
+
+        let run = || {
+            let mut handle = StrategyHandle::from_strategy(Box::new(Trader));
+            let statistic = BacktestTask::run_backtest(
+                &mut handle,
+                &markets,
+                0,
+                ExecutionTiming::Close,
+                OrderIds::Sequential(0),
+                &AtomicBool::new(false),
+                |_, _| {},
+            )
+            .unwrap();
+            serde_json::to_string(&statistic).unwrap()
+        };
+
+        let first = run();
+        assert!(first.contains("limit_buy"));
+        assert_eq!(first, run());
+    }
+
+    /// Records how many candles of the second market each tick sees.
+    struct Watcher(Arc<Mutex<Vec<usize>>>);
+
+    impl Strategy for Watcher {
+        fn tick(&mut self, context: &mut StrategyContext) -> AppResult<()> {
+            let seen = context.candles_for("ETH/USDT").len();
+            self.0.lock().unwrap().push(seen);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn other_markets_only_show_candles_up_to_the_primary_candle() {
+        let start = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let other = [1, 2, 4]
+            .into_iter()
+            .map(|minute| {
+                Candle::test(start + TimeDelta::minutes(minute), 2000).with_symbol("ETH/USDT")
+            })
+            .collect();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let mut handle = StrategyHandle::from_strategy(Box::new(Watcher(seen.clone())));
+
+        BacktestTask::run_backtest(
+            &mut handle,
+            &[
+                BacktestMarket::test(candles(5)),
+                BacktestMarket::test(other),
+            ],
+            0,
+            ExecutionTiming::Close,
+            OrderIds::Sequential(0),
+            &AtomicBool::new(false),
+            |_, _| {},
+        )
+        .unwrap();
+        assert_eq!(*seen.lock().unwrap(), vec![0, 1, 2, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn overrunning_backtest_stops_its_thread() {
+        let ticks = Arc::new(AtomicUsize::new(0));
+        let handle = StrategyHandle::from_strategy(Box::new(Slow(ticks.clone())));
+        let limits = RunLimits {
+            stall_timeout: None,
+            max_duration: Some(Duration::from_millis(100)),
+        };
+
+        let result = BacktestTask::run_backtest_watched(
+            handle,
+            Arc::from([BacktestMarket::test(candles(1000))]),
+            0,
+            ExecutionTiming::Close,
+            limits,
+            |_, _| {},
+        )
+        .await;
+        assert!(result.is_err());
+
+        // The thread notices the stop flag before its next tick.
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let stopped_at = ticks.load(Ordering::Relaxed);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(ticks.load(Ordering::Relaxed), stopped_at);
+        assert!(stopped_at < 1000);
+    }
 }
@@ -0,0 +1,192 @@
+use crate::errors::AppError;
+use crate::models::Timeframe;
+use crate::services::tasks::save_fetch_all_candles_task;
+use crate::tasks::{
+    FetchCandlesResult, FetchCandlesStatus, FetchCandlesTask, TaskSummary, TaskType,
+};
+use chrono::{DateTime, Utc, serde::ts_milliseconds, serde::ts_milliseconds_option};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct FetchAllCandlesFailure {
+    pub symbol: String,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct FetchAllCandlesTask {
+    pub id: Uuid,
+    pub status: FetchCandlesStatus,
+    pub progress: f32,
+    pub exchange: String,
+    pub timeframe: Timeframe,
+    pub symbols: Vec<String>,
+    /// Milliseconds to wait between consecutive exchange requests, defaulting
+    /// to the exchange's advertised rate limit.
+    #[serde(default)]
+    #[ts(type = "number")]
+    pub delay_ms: u64,
+    /// Candles requested per exchange call, capped at the exchange's maximum.
+    /// The exchange picks its own page size when unset.
+    #[serde(default)]
+    #[ts(optional, type = "number")]
+    pub batch_limit: Option<u64>,
+    pub completed_symbols: usize,
+    #[ts(optional)]
+    pub current_symbol: Option<String>,
+    pub results: Vec<FetchCandlesResult>,
+    pub failures: Vec<FetchAllCandlesFailure>,
+    #[ts(optional)]
+    pub error_message: Option<String>,
+    #[serde(with = "ts_milliseconds")]
+    #[ts(type = "number")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "ts_milliseconds_option")]
+    #[ts(optional, type = "number")]
+    pub started_at: Option<DateTime<Utc>>,
+    #[serde(with = "ts_milliseconds_option")]
+    #[ts(optional, type = "number")]
+    pub completed_at: Option<DateTime<Utc>>,
+    #[serde(with = "ts_milliseconds")]
+    #[ts(type = "number")]
+    pub updated_at: DateTime<Utc>,
+    #[serde(skip)]
+    #[ts(skip)]
+    pub event_tx: Option<broadcast::Sender<FetchAllCandlesTask>>,
+}
+
+impl FetchAllCandlesTask {
+    pub fn broadcast(&self) {
+        if let Some(tx) = &self.event_tx {
+            let _ = tx.send(self.clone());
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self.status,
+            FetchCandlesStatus::Completed | FetchCandlesStatus::Failed
+        )
+    }
+
+    pub fn finished_at(&self) -> Option<DateTime<Utc>> {
+        self.is_finished()
+            .then(|| self.completed_at.unwrap_or(self.updated_at))
+    }
+
+    pub fn summary(&self) -> TaskSummary {
+        TaskSummary {
+            id: self.id,
+            task_type: TaskType::FetchAllCandles,
+            status: (&self.status).into(),
+            progress: self.progress,
+            error_message: self.error_message.clone(),
+            created_at: self.created_at,
+            started_at: self.started_at,
+            completed_at: self.completed_at,
+            updated_at: self.updated_at,
+        }
+    }
+
+    pub async fn execute(&mut self, db_pool: PgPool, cancel: &CancellationToken) {
+        let now = Utc::now();
+        self.status = FetchCandlesStatus::Running;
+        self.started_at = Some(now);
+        self.updated_at = now;
+        self.broadcast();
+
+        tracing::info!(
+            "Fetching candles data for {} symbols on {} with timeframe {}",
+            self.symbols.len(),
+            self.exchange,
+            self.timeframe
+        );
+
+        for symbol in self.symbols.clone() {
+            self.current_symbol = Some(symbol.clone());
+            self.updated_at = Utc::now();
+            self.broadcast();
+
+            let now = Utc::now();
+            let mut task = FetchCandlesTask {
+                id: Uuid::new_v4(),
+                status: FetchCandlesStatus::Running,
+                progress: 0.0,
+                throughput: None,
+                eta_secs: None,
+                symbol: symbol.clone(),
+                exchange: self.exchange.clone(),
+                timeframe: self.timeframe,
+                start: None,
+                end: None,
+                delay_ms: self.delay_ms,
+                batch_limit: self.batch_limit,
+                result: None,
+                error_message: None,
+                created_at: now,
+                started_at: Some(now),
+                completed_at: None,
+                updated_at: now,
+                event_tx: None,
+            };
+
+            let result = tokio::select! {
+                biased;
+                _ = cancel.cancelled() => break,
+                result = task.execute_fetch(&db_pool) => result,
+            };
+            match result {
+                Ok(result) => self.results.push(result),
+                Err(e) => {
+                    tracing::warn!(
+                        "Fetching candles data for {} on {} failed: {}",
+                        symbol,
+                        self.exchange,
+                        e
+                    );
+                    self.failures.push(FetchAllCandlesFailure {
+                        symbol,
+                        error: e.to_string(),
+                    });
+                }
+            }
+
+            self.completed_symbols += 1;
+            self.progress = 100.0 * (self.completed_symbols as f32) / (self.symbols.len() as f32);
+            self.updated_at = Utc::now();
+            self.broadcast();
+
+            if self.completed_symbols < self.symbols.len() {
+                tokio::select! {
+                    _ = cancel.cancelled() => break,
+                    _ = tokio::time::sleep(Duration::from_millis(self.delay_ms)) => {}
+                }
+            }
+        }
+
+        let now = Utc::now();
+        if cancel.is_cancelled() {
+            self.status = FetchCandlesStatus::Failed;
+            self.error_message = Some(AppError::Cancelled.to_string());
+        } else {
+            self.status = FetchCandlesStatus::Completed;
+            self.progress = 100.0;
+        }
+        self.current_symbol = None;
+        self.completed_at = Some(now);
+        self.updated_at = now;
+        self.broadcast();
+
+        save_fetch_all_candles_task(&db_pool, self)
+            .await
+            .expect("Failed to save fetch all candles task");
+    }
+}
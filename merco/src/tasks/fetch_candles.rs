@@ -1,12 +1,18 @@
 use crate::exchange::ccxt::CCXT;
 use crate::models::Timeframe;
 use crate::services::candles;
-use crate::{errors::AppResult, services::tasks::save_fetch_candles_task};
-use bigdecimal::ToPrimitive;
+use crate::tasks::progress::Throughput;
+use crate::tasks::summary::{TaskSummary, TaskType};
+use crate::{
+    errors::{AppError, AppResult},
+    services::tasks::save_fetch_candles_task,
+};
 use chrono::{DateTime, Utc, serde::ts_milliseconds, serde::ts_milliseconds_option};
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
+use std::time::Duration;
 use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
 use ts_rs::TS;
 use uuid::Uuid;
 
@@ -17,6 +23,14 @@ pub struct FetchCandlesResult {
     pub exchange: String,
     pub timeframe: Timeframe,
     pub records: u64,
+    /// Candles that were already stored.
+    #[serde(default)]
+    #[ts(type = "number")]
+    pub skipped: u64,
+    /// Candles dropped by validation, e.g. with a high below the low.
+    #[serde(default)]
+    #[ts(type = "number")]
+    pub rejected: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, TS)]
@@ -35,9 +49,33 @@ pub struct FetchCandlesTask {
     pub id: Uuid,
     pub status: FetchCandlesStatus,
     pub progress: f32,
+    /// Candles fetched per second.
+    #[serde(default)]
+    #[ts(optional)]
+    pub throughput: Option<f64>,
+    /// Estimated seconds until the task completes at the current throughput.
+    #[serde(default)]
+    #[ts(optional)]
+    pub eta_secs: Option<f64>,
     pub symbol: String,
     pub exchange: String,
     pub timeframe: Timeframe,
+    #[serde(default, with = "ts_milliseconds_option")]
+    #[ts(optional, type = "number")]
+    pub start: Option<DateTime<Utc>>,
+    #[serde(default, with = "ts_milliseconds_option")]
+    #[ts(optional, type = "number")]
+    pub end: Option<DateTime<Utc>>,
+    /// Milliseconds to wait between consecutive exchange requests, defaulting
+    /// to the exchange's advertised rate limit.
+    #[serde(default)]
+    #[ts(type = "number")]
+    pub delay_ms: u64,
+    /// Candles requested per exchange call, capped at the exchange's maximum.
+    /// The exchange picks its own page size when unset.
+    #[serde(default)]
+    #[ts(optional, type = "number")]
+    pub batch_limit: Option<u64>,
     #[ts(optional)]
     pub result: Option<FetchCandlesResult>,
     #[ts(optional)]
@@ -66,25 +104,57 @@ impl FetchCandlesTask {
         }
     }
 
-    pub async fn execute(&mut self, db_pool: PgPool) {
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self.status,
+            FetchCandlesStatus::Completed | FetchCandlesStatus::Failed
+        )
+    }
+
+    pub fn finished_at(&self) -> Option<DateTime<Utc>> {
+        self.is_finished()
+            .then(|| self.completed_at.unwrap_or(self.updated_at))
+    }
+
+    pub fn summary(&self) -> TaskSummary {
+        TaskSummary {
+            id: self.id,
+            task_type: TaskType::FetchCandles,
+            status: (&self.status).into(),
+            progress: self.progress,
+            error_message: self.error_message.clone(),
+            created_at: self.created_at,
+            started_at: self.started_at,
+            completed_at: self.completed_at,
+            updated_at: self.updated_at,
+        }
+    }
+
+    pub async fn execute(&mut self, db_pool: PgPool, cancel: &CancellationToken) {
         let now = Utc::now();
         self.status = FetchCandlesStatus::Running;
         self.started_at = Some(now);
         self.updated_at = now;
         self.broadcast();
 
-        let result = self.execute_fetch(&db_pool).await;
+        let result = tokio::select! {
+            biased;
+            _ = cancel.cancelled() => Err(AppError::Cancelled),
+            result = self.execute_fetch(&db_pool) => result,
+        };
         let now = Utc::now();
         match result {
             Ok(fetch_result) => {
                 self.status = FetchCandlesStatus::Completed;
                 self.progress = 100.0;
+                self.eta_secs = None;
                 self.result = Some(fetch_result);
                 self.completed_at = Some(now);
                 self.updated_at = now;
             }
             Err(e) => {
                 self.status = FetchCandlesStatus::Failed;
+                self.eta_secs = None;
                 self.error_message = Some(e.to_string());
                 self.completed_at = Some(now);
                 self.updated_at = now;
@@ -97,7 +167,10 @@ impl FetchCandlesTask {
             .expect("Failed to save fetch candles task");
     }
 
-    async fn execute_fetch(&mut self, db_pool: &PgPool) -> AppResult<FetchCandlesResult> {
+    pub(crate) async fn execute_fetch(
+        &mut self,
+        db_pool: &PgPool,
+    ) -> AppResult<FetchCandlesResult> {
         let exchange = self.exchange.clone();
         let symbol = self.symbol.clone();
         let timeframe = self.timeframe;
@@ -109,69 +182,109 @@ impl FetchCandlesTask {
             timeframe
         );
 
-        let ccxt = CCXT::with_exchange(&exchange)?;
+        let ccxt = CCXT::with_exchange_async(&exchange).await?;
 
-        let timeframe_ms = timeframe.to_ms();
-        let timeframe_delta = timeframe.to_delta();
         let mut next_since =
             match candles::get_latest_candle(db_pool, &exchange, &symbol, timeframe).await? {
-                Some(latest_candle) => latest_candle.timestamp + timeframe_delta,
-                None => {
-                    let first_candle = ccxt.first_candle(&symbol, timeframe)?;
-                    let Some(first_candle) = first_candle else {
-                        return Err(format!(
-                            "No candles data available for {} on {}",
-                            symbol, exchange
-                        )
-                        .into());
-                    };
-
-                    first_candle.timestamp
+                Some(latest_candle) => {
+                    let since = timeframe.advance(latest_candle.timestamp);
+                    match self.start {
+                        Some(start) if start > since => start,
+                        _ => since,
+                    }
                 }
+                None => match self.start {
+                    Some(start) => start,
+                    None => {
+                        let first_candle = ccxt.first_candle_async(&symbol, timeframe).await?;
+                        let Some(first_candle) = first_candle else {
+                            return Err(format!(
+                                "No candles data available for {} on {}",
+                                symbol, exchange
+                            )
+                            .into());
+                        };
+
+                        first_candle.timestamp
+                    }
+                },
             };
 
-        let now = Utc::now();
-        let duration = now.signed_duration_since(next_since);
-        let Some(time_diff_ms) = duration.num_milliseconds().to_u64() else {
+        if let Some(batch_limit) = self.batch_limit
+            && let Some(max_limit) = ccxt.max_candles_limit_async(&symbol).await?
+        {
+            self.batch_limit = Some(batch_limit.min(max_limit));
+        }
+        let limit = self.batch_limit.map(|limit| limit as i64);
+
+        let end = self.end.unwrap_or_else(Utc::now);
+        if next_since >= end {
             return Ok(FetchCandlesResult {
                 symbol: symbol.to_string(),
                 exchange: exchange.to_string(),
                 timeframe,
                 records: 0,
+                skipped: 0,
+                rejected: 0,
             });
         };
 
-        let mut count: u64 = 0;
-        let total = (time_diff_ms + timeframe_ms - 1) / timeframe_ms;
-        let mut progress = 0.0;
+        // Progress is measured against the span still missing from storage, so
+        // re-running an interrupted fetch only counts the candles it adds.
+        let total = timeframe.periods_between(next_since, end).max(1);
+        let mut fetched: u64 = 0;
+        let mut inserted: u64 = 0;
+        let mut skipped: u64 = 0;
+        let mut rejected: u64 = 0;
 
-        self.progress = progress;
+        self.progress = 0.0;
         self.updated_at = Utc::now();
         self.broadcast();
 
+        let throughput = Throughput::start();
         loop {
             let next_since_ms = next_since.timestamp_millis();
-            let epoch = ccxt.fetch_candles(&symbol, timeframe, Some(next_since_ms), None)?;
+            let (mut epoch, epoch_rejected) = ccxt
+                .fetch_candles_async(&symbol, timeframe, Some(next_since_ms), limit)
+                .await?;
+            rejected += epoch_rejected;
+            if let Some(end) = self.end {
+                epoch.retain(|candle| candle.timestamp < end);
+            }
+
             let Some(latest) = epoch.last() else {
                 break;
             };
 
-            candles::insert_candles(db_pool, &epoch).await?;
+            let epoch_inserted = candles::insert_candles(db_pool, &epoch).await?;
+            inserted += epoch_inserted;
+            skipped += epoch.len() as u64 - epoch_inserted;
 
-            next_since = latest.timestamp + timeframe_delta;
-            count += epoch.len() as u64;
-            progress = 100.0 * (count as f32) / (total as f32);
+            next_since = timeframe.advance(latest.timestamp);
+            fetched += epoch.len() as u64;
 
-            self.progress = progress;
+            self.progress = (100.0 * (fetched as f32) / (total as f32)).min(100.0);
+            if let Some((rate, eta)) = throughput.measure(fetched, total) {
+                self.throughput = Some(rate);
+                self.eta_secs = Some(eta);
+            }
             self.updated_at = Utc::now();
             self.broadcast();
+
+            if next_since >= end {
+                break;
+            }
+
+            tokio::time::sleep(Duration::from_millis(self.delay_ms)).await;
         }
 
         Ok(FetchCandlesResult {
             symbol,
             exchange,
             timeframe,
-            records: total,
+            records: inserted,
+            skipped,
+            rejected,
         })
     }
 }
@@ -0,0 +1,427 @@
+use crate::errors::{AppError, AppResult};
+use crate::models::Timeframe;
+use crate::services::tasks::save_optimize_task;
+use crate::strategy::{ExecutionTiming, LibrarySnapshot, StrategyManager};
+use crate::tasks::{
+    BacktestMarket, BacktestStatistic, RunLimits, StrategyRunner, TaskSummary, TaskType,
+};
+use bigdecimal::{BigDecimal, ToPrimitive};
+use chrono::{DateTime, Utc, serde::ts_milliseconds, serde::ts_milliseconds_option};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sqlx::PgPool;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
+use ts_rs::TS;
+use uuid::Uuid;
+
+const MAX_OPTIMIZE_COMBINATIONS: usize = 10_000;
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[ts(export, tag = "type")]
+pub enum ParameterRange {
+    List {
+        #[ts(type = "unknown[]")]
+        values: Vec<Value>,
+    },
+    Range {
+        start: f64,
+        end: f64,
+        step: f64,
+    },
+}
+
+impl ParameterRange {
+    fn values(&self) -> AppResult<Vec<Value>> {
+        match self {
+            ParameterRange::List { values } => Ok(values.clone()),
+            ParameterRange::Range { start, end, step } => {
+                if !start.is_finite()
+                    || !end.is_finite()
+                    || !step.is_finite()
+                    || *step <= 0.0
+                    || start > end
+                {
+                    return Err(AppError::BadRequest(format!(
+                        "Invalid parameter range: start {}, end {}, step {}",
+                        start, end, step
+                    )));
+                }
+
+                // Counted in f64 first, as the span may not fit in a usize.
+                let steps = ((end - start) / step).floor();
+                let count = if steps.is_finite() && steps < MAX_OPTIMIZE_COMBINATIONS as f64 {
+                    (steps as usize).checked_add(1)
+                } else {
+                    None
+                };
+                let Some(count) = count.filter(|count| *count <= MAX_OPTIMIZE_COMBINATIONS) else {
+                    return Err(AppError::BadRequest(format!(
+                        "Parameter range produces more than {} values",
+                        MAX_OPTIMIZE_COMBINATIONS
+                    )));
+                };
+
+                let integral = start.fract() == 0.0 && step.fract() == 0.0;
+
+                Ok((0..count)
+                    .map(|i| {
+                        let value = start + step * i as f64;
+                        if integral {
+                            Value::from(value as i64)
+                        } else {
+                            Value::from(value)
+                        }
+                    })
+                    .collect())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum OptimizeMetric {
+    NetProfit,
+    ReturnPercent,
+    SharpeRatio,
+    ProfitFactor,
+    WinRate,
+}
+
+impl OptimizeMetric {
+    pub(crate) fn score(&self, statistic: &BacktestStatistic) -> f32 {
+        match self {
+            OptimizeMetric::NetProfit => statistic.net_profit.to_f32().unwrap_or(0.0),
+            OptimizeMetric::ReturnPercent => statistic.return_percent,
+            OptimizeMetric::SharpeRatio => statistic.sharpe_ratio,
+            OptimizeMetric::ProfitFactor => statistic.profit_factor,
+            OptimizeMetric::WinRate => statistic.win_rate,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct OptimizeResult {
+    #[ts(type = "Record<string, unknown>")]
+    pub params: Value,
+    pub score: f32,
+    #[ts(type = "string")]
+    pub net_profit: BigDecimal,
+    pub return_percent: f32,
+    pub sharpe_ratio: f32,
+    pub max_drawdown_percent: f32,
+    pub total_trades: usize,
+    pub win_rate: f32,
+}
+
+impl OptimizeResult {
+    pub(crate) fn new(
+        params: Value,
+        metric: OptimizeMetric,
+        statistic: &BacktestStatistic,
+    ) -> Self {
+        Self {
+            score: metric.score(statistic),
+            params,
+            net_profit: statistic.net_profit.clone(),
+            return_percent: statistic.return_percent,
+            sharpe_ratio: statistic.sharpe_ratio,
+            max_drawdown_percent: statistic.max_drawdown_percent,
+            total_trades: statistic.total_trades,
+            win_rate: statistic.win_rate,
+        }
+    }
+}
+
+/// Runs one backtest of a built strategy library with the given params.
+pub(crate) async fn run_with_params(
+    strategy_manager: &StrategyManager,
+    lib_path: &Path,
+    params: &Value,
+    markets: Arc<[BacktestMarket]>,
+    warmup: usize,
+    limits: RunLimits,
+) -> AppResult<BacktestStatistic> {
+    StrategyRunner::from_path(strategy_manager, lib_path, Some(params))?
+        .run(
+            markets,
+            warmup,
+            ExecutionTiming::default(),
+            limits,
+            |_, _| {},
+        )
+        .await
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum OptimizeStatus {
+    Pending,
+    Compiling,
+    Running,
+    Completed,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct OptimizeTask {
+    pub id: Uuid,
+    pub status: OptimizeStatus,
+    pub progress: f32,
+    pub name: String,
+    pub exchange: String,
+    pub symbol: String,
+    pub timeframe: Timeframe,
+    pub grid: BTreeMap<String, ParameterRange>,
+    pub metric: OptimizeMetric,
+    pub total_combinations: usize,
+    pub completed_combinations: usize,
+    pub failed_combinations: usize,
+    pub results: Vec<OptimizeResult>,
+    #[ts(optional)]
+    pub error_message: Option<String>,
+    #[serde(with = "ts_milliseconds")]
+    #[ts(type = "number")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "ts_milliseconds_option")]
+    #[ts(optional, type = "number")]
+    pub started_at: Option<DateTime<Utc>>,
+    #[serde(with = "ts_milliseconds_option")]
+    #[ts(optional, type = "number")]
+    pub completed_at: Option<DateTime<Utc>>,
+    #[serde(with = "ts_milliseconds")]
+    #[ts(type = "number")]
+    pub updated_at: DateTime<Utc>,
+    #[serde(skip)]
+    #[ts(skip)]
+    pub event_tx: Option<broadcast::Sender<OptimizeTask>>,
+}
+
+impl OptimizeTask {
+    pub fn broadcast(&self) {
+        if let Some(tx) = &self.event_tx {
+            let _ = tx.send(self.clone());
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self.status,
+            OptimizeStatus::Completed | OptimizeStatus::Failed
+        )
+    }
+
+    pub fn finished_at(&self) -> Option<DateTime<Utc>> {
+        self.is_finished()
+            .then(|| self.completed_at.unwrap_or(self.updated_at))
+    }
+
+    pub fn summary(&self) -> TaskSummary {
+        TaskSummary {
+            id: self.id,
+            task_type: TaskType::Optimize,
+            status: (&self.status).into(),
+            progress: self.progress,
+            error_message: self.error_message.clone(),
+            created_at: self.created_at,
+            started_at: self.started_at,
+            completed_at: self.completed_at,
+            updated_at: self.updated_at,
+        }
+    }
+
+    pub fn combinations(grid: &BTreeMap<String, ParameterRange>) -> AppResult<Vec<Value>> {
+        let mut combinations = vec![Map::new()];
+        for (name, range) in grid {
+            let values = range.values()?;
+            if values.is_empty() {
+                return Err(AppError::BadRequest(format!(
+                    "Parameter '{}' has no values",
+                    name
+                )));
+            }
+
+            let count = combinations.len().saturating_mul(values.len());
+            if count > MAX_OPTIMIZE_COMBINATIONS {
+                return Err(AppError::BadRequest(format!(
+                    "Parameter grid exceeds {} combinations",
+                    MAX_OPTIMIZE_COMBINATIONS
+                )));
+            }
+
+            let mut next = Vec::with_capacity(count);
+            for combination in &combinations {
+                for value in &values {
+                    let mut combination = combination.clone();
+                    combination.insert(name.clone(), value.clone());
+                    next.push(combination);
+                }
+            }
+            combinations = next;
+        }
+
+        Ok(combinations.into_iter().map(Value::Object).collect())
+    }
+
+    pub async fn execute(
+        &mut self,
+        strategy_manager: &StrategyManager,
+        limits: RunLimits,
+        db_pool: PgPool,
+        cancel: &CancellationToken,
+    ) {
+        let now = Utc::now();
+        self.status = OptimizeStatus::Compiling;
+        self.started_at = Some(now);
+        self.updated_at = now;
+        self.broadcast();
+
+        let result = tokio::select! {
+            biased;
+            _ = cancel.cancelled() => Err(AppError::Cancelled),
+            result = self.execute_optimize(strategy_manager, limits, &db_pool) => result,
+        };
+        let now = Utc::now();
+        match result {
+            Ok(()) => {
+                self.status = OptimizeStatus::Completed;
+                self.progress = 100.0;
+                self.completed_at = Some(now);
+                self.updated_at = now;
+            }
+            Err(e) => {
+                self.status = OptimizeStatus::Failed;
+                self.error_message = Some(e.to_string());
+                self.completed_at = Some(now);
+                self.updated_at = now;
+            }
+        }
+
+        self.broadcast();
+
+        save_optimize_task(&db_pool, self)
+            .await
+            .expect("Failed to save optimize task");
+    }
+
+    async fn execute_optimize(
+        &mut self,
+        strategy_manager: &StrategyManager,
+        limits: RunLimits,
+        db_pool: &PgPool,
+    ) -> AppResult<()> {
+        let combinations = Self::combinations(&self.grid)?;
+        self.total_combinations = combinations.len();
+
+        // Every combination runs the same build, even if the strategy is rebuilt
+        // during the task.
+        let library =
+            LibrarySnapshot::new(&strategy_manager.build_strategy(&self.name, false).await?)?;
+
+        let now = Utc::now();
+        self.status = OptimizeStatus::Running;
+        self.updated_at = now;
+        self.broadcast();
+
+        tracing::info!(
+            "Optimizing {} over {} combinations on {}/{} with timeframe {}",
+            self.name,
+            self.total_combinations,
+            self.exchange,
+            self.symbol,
+            self.timeframe
+        );
+
+        let market = BacktestMarket::load(
+            db_pool,
+            &self.exchange,
+            &self.symbol,
+            self.timeframe,
+            None,
+            None,
+        )
+        .await?;
+        let markets: Arc<[BacktestMarket]> = Arc::new([market]);
+
+        for params in combinations {
+            let result = run_with_params(
+                strategy_manager,
+                library.path(),
+                &params,
+                markets.clone(),
+                0,
+                limits,
+            )
+            .await;
+
+            match result {
+                Ok(statistic) => {
+                    self.results
+                        .push(OptimizeResult::new(params, self.metric, &statistic));
+                }
+                Err(e) => {
+                    tracing::warn!("Optimization run with params {} failed: {}", params, e);
+                    self.failed_combinations += 1;
+                }
+            }
+
+            self.completed_combinations += 1;
+            self.results.sort_by(|a, b| b.score.total_cmp(&a.score));
+            self.progress =
+                100.0 * (self.completed_combinations as f32) / (self.total_combinations as f32);
+            self.updated_at = Utc::now();
+            self.broadcast();
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(start: f64, end: f64, step: f64) -> ParameterRange {
+        ParameterRange::Range { start, end, step }
+    }
+
+    #[test]
+    fn range_values_include_both_ends() {
+        let values = range(1.0, 5.0, 2.0).values().unwrap();
+        assert_eq!(values, vec![Value::from(1), Value::from(3), Value::from(5)]);
+    }
+
+    #[test]
+    fn range_rejects_non_finite_bounds() {
+        assert!(range(0.0, f64::INFINITY, 1.0).values().is_err());
+        assert!(range(f64::NAN, 1.0, 1.0).values().is_err());
+        assert!(range(0.0, 1.0, f64::NAN).values().is_err());
+    }
+
+    #[test]
+    fn range_rejects_too_many_values_without_overflow() {
+        assert!(range(-f64::MAX, f64::MAX, 1.0).values().is_err());
+        assert!(range(0.0, 1e300, f64::MIN_POSITIVE).values().is_err());
+        assert!(
+            range(0.0, MAX_OPTIMIZE_COMBINATIONS as f64, 1.0)
+                .values()
+                .is_err()
+        );
+        assert_eq!(
+            range(1.0, MAX_OPTIMIZE_COMBINATIONS as f64, 1.0)
+                .values()
+                .unwrap()
+                .len(),
+            MAX_OPTIMIZE_COMBINATIONS
+        );
+    }
+}
@@ -0,0 +1,27 @@
+use std::time::Instant;
+
+/// Tracks how fast a task loop works through its items.
+pub(crate) struct Throughput {
+    started_at: Instant,
+}
+
+impl Throughput {
+    pub(crate) fn start() -> Self {
+        Self {
+            started_at: Instant::now(),
+        }
+    }
+
+    /// Items processed per second so far and the estimated seconds until all
+    /// `total` items are processed at that rate.
+    pub(crate) fn measure(&self, processed: u64, total: u64) -> Option<(f64, f64)> {
+        let elapsed = self.started_at.elapsed().as_secs_f64();
+        if processed == 0 || elapsed <= 0.0 {
+            return None;
+        }
+
+        let rate = processed as f64 / elapsed;
+        let remaining = total.saturating_sub(processed) as f64 / rate;
+        Some((rate, remaining))
+    }
+}
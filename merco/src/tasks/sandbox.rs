@@ -0,0 +1,288 @@
+use crate::errors::{AppError, AppResult};
+use crate::strategy::{
+    ExecutionTiming, OrderIds, SandboxConfig, StrategyHandle, StrategyManager, StrategyMetadata,
+    Trade,
+};
+use crate::tasks::{BacktestMarket, BacktestStatistic, BacktestTask, RunLimits};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::time::Instant;
+
+/// First argument that starts the server binary as a sandboxed backtest worker.
+pub const SANDBOX_WORKER_ARG: &str = "backtest-worker";
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum WorkerRequest<'a> {
+    Backtest {
+        lib_path: PathBuf,
+        params: Option<serde_json::Value>,
+        markets: Cow<'a, [BacktestMarket]>,
+        warmup: usize,
+        timing: ExecutionTiming,
+    },
+    /// Loads the strategy with its default params, without running it.
+    Validate { lib_path: PathBuf },
+    /// Reads the plugin's metadata without creating the strategy.
+    Metadata { lib_path: PathBuf },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum WorkerMessage {
+    Progress { progress: f32, trades: Vec<Trade> },
+    Completed { statistic: Box<BacktestStatistic> },
+    Validated,
+    Metadata { metadata: StrategyMetadata },
+    Failed { error: String },
+}
+
+/// Runs a strategy either loaded into the server process or, when the
+/// strategy sandbox is enabled, in a worker subprocess.
+pub enum StrategyRunner {
+    InProcess(StrategyHandle),
+    Sandboxed {
+        lib_path: PathBuf,
+        params: Option<serde_json::Value>,
+        sandbox: SandboxConfig,
+    },
+}
+
+impl StrategyRunner {
+    pub async fn load(
+        strategy_manager: &StrategyManager,
+        strategy_name: &str,
+        force_rebuild: bool,
+        params: Option<&serde_json::Value>,
+    ) -> AppResult<Self> {
+        match strategy_manager.sandbox() {
+            Some(_) => {
+                let lib_path = strategy_manager
+                    .build_strategy(strategy_name, force_rebuild)
+                    .await?;
+                Self::from_path(strategy_manager, &lib_path, params)
+            }
+            None => strategy_manager
+                .load_strategy(strategy_name, force_rebuild, params)
+                .await
+                .map(Self::InProcess),
+        }
+    }
+
+    pub fn from_path(
+        strategy_manager: &StrategyManager,
+        lib_path: &Path,
+        params: Option<&serde_json::Value>,
+    ) -> AppResult<Self> {
+        match strategy_manager.sandbox() {
+            Some(sandbox) => Ok(Self::Sandboxed {
+                lib_path: lib_path.to_path_buf(),
+                params: params.cloned(),
+                sandbox: sandbox.clone(),
+            }),
+            None => StrategyHandle::try_from_path(lib_path, params).map(Self::InProcess),
+        }
+    }
+
+    /// Checks that a built library loads and creates its strategy, in the
+    /// worker subprocess when the strategy sandbox is enabled.
+    pub async fn validate(
+        strategy_manager: &StrategyManager,
+        lib_path: &Path,
+        limits: RunLimits,
+    ) -> AppResult<()> {
+        let Some(sandbox) = strategy_manager.sandbox() else {
+            return StrategyHandle::try_from_path(lib_path, None).map(|_| ());
+        };
+
+        let request = WorkerRequest::Validate {
+            lib_path: lib_path.to_path_buf(),
+        };
+        match Self::run_worker(sandbox, &request, limits, |_, _| {}).await? {
+            WorkerMessage::Validated => Ok(()),
+            _ => Err("Unexpected reply from the strategy worker".into()),
+        }
+    }
+
+    /// Reads a built library's metadata, in the worker subprocess when the
+    /// strategy sandbox is enabled.
+    pub async fn metadata(
+        strategy_manager: &StrategyManager,
+        lib_path: &Path,
+        limits: RunLimits,
+    ) -> AppResult<StrategyMetadata> {
+        let Some(sandbox) = strategy_manager.sandbox() else {
+            return StrategyHandle::read_metadata(lib_path);
+        };
+
+        let request = WorkerRequest::Metadata {
+            lib_path: lib_path.to_path_buf(),
+        };
+        match Self::run_worker(sandbox, &request, limits, |_, _| {}).await? {
+            WorkerMessage::Metadata { metadata } => Ok(metadata),
+            _ => Err("Unexpected reply from the strategy worker".into()),
+        }
+    }
+
+    pub async fn run(
+        self,
+        markets: Arc<[BacktestMarket]>,
+        warmup: usize,
+        timing: ExecutionTiming,
+        limits: RunLimits,
+        on_progress: impl FnMut(f32, &[Trade]),
+    ) -> AppResult<BacktestStatistic> {
+        match self {
+            Self::InProcess(strategy_handle) => {
+                BacktestTask::run_backtest_watched(
+                    strategy_handle,
+                    markets,
+                    warmup,
+                    timing,
+                    limits,
+                    on_progress,
+                )
+                .await
+            }
+            Self::Sandboxed {
+                lib_path,
+                params,
+                sandbox,
+            } => {
+                let request = WorkerRequest::Backtest {
+                    lib_path,
+                    params,
+                    markets: Cow::Borrowed(&markets),
+                    warmup,
+                    timing,
+                };
+                match Self::run_worker(&sandbox, &request, limits, on_progress).await? {
+                    WorkerMessage::Completed { statistic } => Ok(*statistic),
+                    _ => Err("Unexpected reply from the backtest worker".into()),
+                }
+            }
+        }
+    }
+
+    /// Runs a request in a worker subprocess, reporting its progress, and
+    /// returns the message that ended it.
+    async fn run_worker(
+        sandbox: &SandboxConfig,
+        request: &WorkerRequest<'_>,
+        limits: RunLimits,
+        mut on_progress: impl FnMut(f32, &[Trade]),
+    ) -> AppResult<WorkerMessage> {
+        let request = serde_json::to_vec(request)?;
+        let mut child = sandbox
+            .command(std::env::current_exe()?)
+            .arg(SANDBOX_WORKER_ARG)
+            .current_dir(std::env::temp_dir())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .kill_on_drop(true)
+            .spawn()?;
+
+        let (Some(mut stdin), Some(stdout)) = (child.stdin.take(), child.stdout.take()) else {
+            return Err("Failed to open backtest worker pipes".into());
+        };
+        tokio::spawn(async move {
+            let _ = stdin.write_all(&request).await;
+        });
+
+        let mut lines = BufReader::new(stdout).lines();
+        let mut stall_deadline = limits.stall_deadline();
+        let run_deadline = limits.run_deadline();
+        loop {
+            tokio::select! {
+                line = lines.next_line() => {
+                    let Some(line) = line? else {
+                        let status = child.wait().await?;
+                        return Err(format!("Backtest worker exited with {}", status).into());
+                    };
+                    // Anything else the strategy prints is not a message.
+                    match serde_json::from_str::<WorkerMessage>(&line) {
+                        Ok(WorkerMessage::Progress { progress, trades }) => {
+                            on_progress(progress, &trades);
+                            stall_deadline = limits.stall_deadline();
+                        }
+                        Ok(WorkerMessage::Failed { error }) => return Err(AppError::Strategy(error)),
+                        Ok(message) => return Ok(message),
+                        Err(_) => {}
+                    }
+                }
+                _ = tokio::time::sleep_until(stall_deadline.unwrap_or_else(Instant::now)), if stall_deadline.is_some() => {
+                    return Err(limits.stalled_error());
+                }
+                _ = tokio::time::sleep_until(run_deadline.unwrap_or_else(Instant::now)), if run_deadline.is_some() => {
+                    return Err(limits.overran_error());
+                }
+            }
+        }
+    }
+}
+
+/// Entry point of the worker process: reads a request from stdin, runs it and
+/// reports progress and the result as JSON lines on stdout.
+pub fn run_sandbox_worker() -> AppResult<()> {
+    let request: WorkerRequest = serde_json::from_reader(std::io::stdin().lock())?;
+
+    let send = |message: &WorkerMessage| -> AppResult<()> {
+        let mut stdout = std::io::stdout().lock();
+        // Start on a fresh line in case the strategy printed without one.
+        writeln!(stdout)?;
+        serde_json::to_writer(&mut stdout, message)?;
+        writeln!(stdout)?;
+        stdout.flush()?;
+        Ok(())
+    };
+
+    let result = match request {
+        WorkerRequest::Backtest {
+            lib_path,
+            params,
+            markets,
+            warmup,
+            timing,
+        } => StrategyHandle::try_from_path(&lib_path, params.as_ref()).and_then(
+            |mut strategy_handle| {
+                BacktestTask::run_backtest(
+                    &mut strategy_handle,
+                    &markets,
+                    warmup,
+                    timing,
+                    OrderIds::Random,
+                    &AtomicBool::new(false),
+                    |progress, trades| {
+                        let _ = send(&WorkerMessage::Progress {
+                            progress,
+                            trades: trades.to_vec(),
+                        });
+                    },
+                )
+                .map(|statistic| WorkerMessage::Completed {
+                    statistic: Box::new(statistic),
+                })
+            },
+        ),
+        WorkerRequest::Validate { lib_path } => {
+            StrategyHandle::try_from_path(&lib_path, None).map(|_| WorkerMessage::Validated)
+        }
+        WorkerRequest::Metadata { lib_path } => StrategyHandle::read_metadata(&lib_path)
+            .map(|metadata| WorkerMessage::Metadata { metadata }),
+    };
+
+    send(&match result {
+        Ok(message) => message,
+        Err(AppError::Strategy(error)) => WorkerMessage::Failed { error },
+        Err(e) => WorkerMessage::Failed {
+            error: e.to_string(),
+        },
+    })
+}
@@ -0,0 +1,99 @@
+use crate::tasks::{BacktestStatus, FetchCandlesStatus, OptimizeStatus, WalkForwardStatus};
+use chrono::{DateTime, Utc, serde::ts_milliseconds, serde::ts_milliseconds_option};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum TaskType {
+    FetchCandles,
+    FetchAllCandles,
+    Backtest,
+    Optimize,
+    WalkForward,
+}
+
+/// Status shared by every task kind; fetch tasks never report `Compiling`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum TaskStatus {
+    Pending,
+    Compiling,
+    Running,
+    Completed,
+    Failed,
+}
+
+impl From<&FetchCandlesStatus> for TaskStatus {
+    fn from(status: &FetchCandlesStatus) -> Self {
+        match status {
+            FetchCandlesStatus::Pending => Self::Pending,
+            FetchCandlesStatus::Running => Self::Running,
+            FetchCandlesStatus::Completed => Self::Completed,
+            FetchCandlesStatus::Failed => Self::Failed,
+        }
+    }
+}
+
+impl From<&BacktestStatus> for TaskStatus {
+    fn from(status: &BacktestStatus) -> Self {
+        match status {
+            BacktestStatus::Pending => Self::Pending,
+            BacktestStatus::Compiling => Self::Compiling,
+            BacktestStatus::Running => Self::Running,
+            BacktestStatus::Completed => Self::Completed,
+            BacktestStatus::Failed => Self::Failed,
+        }
+    }
+}
+
+impl From<&OptimizeStatus> for TaskStatus {
+    fn from(status: &OptimizeStatus) -> Self {
+        match status {
+            OptimizeStatus::Pending => Self::Pending,
+            OptimizeStatus::Compiling => Self::Compiling,
+            OptimizeStatus::Running => Self::Running,
+            OptimizeStatus::Completed => Self::Completed,
+            OptimizeStatus::Failed => Self::Failed,
+        }
+    }
+}
+
+impl From<&WalkForwardStatus> for TaskStatus {
+    fn from(status: &WalkForwardStatus) -> Self {
+        match status {
+            WalkForwardStatus::Pending => Self::Pending,
+            WalkForwardStatus::Compiling => Self::Compiling,
+            WalkForwardStatus::Running => Self::Running,
+            WalkForwardStatus::Completed => Self::Completed,
+            WalkForwardStatus::Failed => Self::Failed,
+        }
+    }
+}
+
+/// The fields every task kind has, for listing tasks without their results.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct TaskSummary {
+    pub id: Uuid,
+    pub task_type: TaskType,
+    pub status: TaskStatus,
+    pub progress: f32,
+    #[ts(optional)]
+    pub error_message: Option<String>,
+    #[serde(with = "ts_milliseconds")]
+    #[ts(type = "number")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "ts_milliseconds_option")]
+    #[ts(optional, type = "number")]
+    pub started_at: Option<DateTime<Utc>>,
+    #[serde(with = "ts_milliseconds_option")]
+    #[ts(optional, type = "number")]
+    pub completed_at: Option<DateTime<Utc>>,
+    #[serde(with = "ts_milliseconds")]
+    #[ts(type = "number")]
+    pub updated_at: DateTime<Utc>,
+}
@@ -0,0 +1,434 @@
+use crate::errors::{AppError, AppResult};
+use crate::models::Timeframe;
+use crate::services::tasks::save_walk_forward_task;
+use crate::strategy::{LibrarySnapshot, StrategyManager};
+use crate::tasks::optimize::run_with_params;
+use crate::tasks::{
+    BacktestMarket, OptimizeMetric, OptimizeResult, OptimizeTask, ParameterRange, RunLimits,
+    TaskSummary, TaskType,
+};
+use bigdecimal::{BigDecimal, Zero};
+use chrono::{DateTime, Utc, serde::ts_milliseconds, serde::ts_milliseconds_option};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::collections::BTreeMap;
+use std::ops::Range;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
+use ts_rs::TS;
+use uuid::Uuid;
+
+const MAX_WALK_FORWARD_FOLDS: usize = 100;
+
+/// One in-sample optimization and the out-of-sample backtest that follows it.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct WalkForwardFold {
+    pub index: usize,
+    #[serde(with = "ts_milliseconds")]
+    #[ts(type = "number")]
+    pub in_sample_start: DateTime<Utc>,
+    #[serde(with = "ts_milliseconds")]
+    #[ts(type = "number")]
+    pub in_sample_end: DateTime<Utc>,
+    #[serde(with = "ts_milliseconds")]
+    #[ts(type = "number")]
+    pub out_of_sample_start: DateTime<Utc>,
+    #[serde(with = "ts_milliseconds")]
+    #[ts(type = "number")]
+    pub out_of_sample_end: DateTime<Utc>,
+    /// Best scoring params on the in-sample window.
+    #[ts(optional)]
+    pub in_sample: Option<OptimizeResult>,
+    /// Those params backtested on the out-of-sample window.
+    #[ts(optional)]
+    pub out_of_sample: Option<OptimizeResult>,
+    pub failed_combinations: usize,
+    #[ts(optional)]
+    pub error_message: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct WalkForwardSummary {
+    /// Folds with an out-of-sample result; the averages cover only these.
+    pub completed_folds: usize,
+    pub profitable_folds: usize,
+    #[ts(type = "string")]
+    pub total_net_profit: BigDecimal,
+    pub average_return_percent: f32,
+    pub average_in_sample_score: f32,
+    pub average_out_of_sample_score: f32,
+    /// Average out-of-sample score over average in-sample score. Values far
+    /// below 1 suggest the params were fitted to noise.
+    #[ts(optional)]
+    pub efficiency: Option<f32>,
+}
+
+impl WalkForwardSummary {
+    fn new(folds: &[WalkForwardFold]) -> Self {
+        let mut completed_folds = 0;
+        let mut profitable_folds = 0;
+        let mut total_net_profit = BigDecimal::zero();
+        let mut total_return_percent = 0.0;
+        let mut total_in_sample_score = 0.0;
+        let mut total_out_of_sample_score = 0.0;
+
+        for fold in folds {
+            let (Some(in_sample), Some(out_of_sample)) = (&fold.in_sample, &fold.out_of_sample)
+            else {
+                continue;
+            };
+
+            completed_folds += 1;
+            if out_of_sample.net_profit > BigDecimal::zero() {
+                profitable_folds += 1;
+            }
+            total_net_profit += &out_of_sample.net_profit;
+            total_return_percent += out_of_sample.return_percent;
+            total_in_sample_score += in_sample.score;
+            total_out_of_sample_score += out_of_sample.score;
+        }
+
+        let average = |total: f32| {
+            if completed_folds > 0 {
+                total / completed_folds as f32
+            } else {
+                0.0
+            }
+        };
+        let average_in_sample_score = average(total_in_sample_score);
+        let average_out_of_sample_score = average(total_out_of_sample_score);
+
+        Self {
+            completed_folds,
+            profitable_folds,
+            total_net_profit,
+            average_return_percent: average(total_return_percent),
+            average_in_sample_score,
+            average_out_of_sample_score,
+            efficiency: (average_in_sample_score != 0.0)
+                .then(|| average_out_of_sample_score / average_in_sample_score),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum WalkForwardStatus {
+    Pending,
+    Compiling,
+    Running,
+    Completed,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct WalkForwardTask {
+    pub id: Uuid,
+    pub status: WalkForwardStatus,
+    pub progress: f32,
+    pub name: String,
+    pub exchange: String,
+    pub symbol: String,
+    pub timeframe: Timeframe,
+    pub grid: BTreeMap<String, ParameterRange>,
+    pub metric: OptimizeMetric,
+    /// Candles in each in-sample window.
+    pub in_sample: usize,
+    /// Candles in each out-of-sample window.
+    pub out_of_sample: usize,
+    /// Candles the windows move forward between folds.
+    pub step: usize,
+    pub total_folds: usize,
+    pub folds: Vec<WalkForwardFold>,
+    #[ts(optional)]
+    pub summary: Option<WalkForwardSummary>,
+    #[ts(optional)]
+    pub error_message: Option<String>,
+    #[serde(with = "ts_milliseconds")]
+    #[ts(type = "number")]
+    pub created_at: DateTime<Utc>,
+    #[serde(with = "ts_milliseconds_option")]
+    #[ts(optional, type = "number")]
+    pub started_at: Option<DateTime<Utc>>,
+    #[serde(with = "ts_milliseconds_option")]
+    #[ts(optional, type = "number")]
+    pub completed_at: Option<DateTime<Utc>>,
+    #[serde(with = "ts_milliseconds")]
+    #[ts(type = "number")]
+    pub updated_at: DateTime<Utc>,
+    #[serde(skip)]
+    #[ts(skip)]
+    pub event_tx: Option<broadcast::Sender<WalkForwardTask>>,
+}
+
+impl WalkForwardTask {
+    pub fn broadcast(&self) {
+        if let Some(tx) = &self.event_tx {
+            let _ = tx.send(self.clone());
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self.status,
+            WalkForwardStatus::Completed | WalkForwardStatus::Failed
+        )
+    }
+
+    pub fn finished_at(&self) -> Option<DateTime<Utc>> {
+        self.is_finished()
+            .then(|| self.completed_at.unwrap_or(self.updated_at))
+    }
+
+    pub fn summary(&self) -> TaskSummary {
+        TaskSummary {
+            id: self.id,
+            task_type: TaskType::WalkForward,
+            status: (&self.status).into(),
+            progress: self.progress,
+            error_message: self.error_message.clone(),
+            created_at: self.created_at,
+            started_at: self.started_at,
+            completed_at: self.completed_at,
+            updated_at: self.updated_at,
+        }
+    }
+
+    /// Splits `total` candles into folds of in-sample and out-of-sample
+    /// candle ranges.
+    fn fold_ranges(&self, total: usize) -> AppResult<Vec<(Range<usize>, Range<usize>)>> {
+        let window = self.in_sample + self.out_of_sample;
+        if total < window {
+            return Err(format!(
+                "Walk-forward needs at least {} candles for one fold, but {} are available",
+                window, total
+            )
+            .into());
+        }
+
+        let count = (total - window) / self.step + 1;
+        if count > MAX_WALK_FORWARD_FOLDS {
+            return Err(format!(
+                "Walk-forward produces {} folds, more than {}; increase the step",
+                count, MAX_WALK_FORWARD_FOLDS
+            )
+            .into());
+        }
+
+        Ok((0..count)
+            .map(|index| {
+                let start = index * self.step;
+                let split = start + self.in_sample;
+                (start..split, split..split + self.out_of_sample)
+            })
+            .collect())
+    }
+
+    pub async fn execute(
+        &mut self,
+        strategy_manager: &StrategyManager,
+        limits: RunLimits,
+        db_pool: PgPool,
+        cancel: &CancellationToken,
+    ) {
+        let now = Utc::now();
+        self.status = WalkForwardStatus::Compiling;
+        self.started_at = Some(now);
+        self.updated_at = now;
+        self.broadcast();
+
+        let result = tokio::select! {
+            biased;
+            _ = cancel.cancelled() => Err(AppError::Cancelled),
+            result = self.execute_walk_forward(strategy_manager, limits, &db_pool) => result,
+        };
+        let now = Utc::now();
+        match result {
+            Ok(()) => {
+                self.status = WalkForwardStatus::Completed;
+                self.progress = 100.0;
+                self.summary = Some(WalkForwardSummary::new(&self.folds));
+                self.completed_at = Some(now);
+                self.updated_at = now;
+            }
+            Err(e) => {
+                self.status = WalkForwardStatus::Failed;
+                self.error_message = Some(e.to_string());
+                self.completed_at = Some(now);
+                self.updated_at = now;
+            }
+        }
+
+        self.broadcast();
+
+        save_walk_forward_task(&db_pool, self)
+            .await
+            .expect("Failed to save walk-forward task");
+    }
+
+    async fn execute_walk_forward(
+        &mut self,
+        strategy_manager: &StrategyManager,
+        limits: RunLimits,
+        db_pool: &PgPool,
+    ) -> AppResult<()> {
+        let combinations = OptimizeTask::combinations(&self.grid)?;
+        // Runs of all folds load this copy, so folds are compared on one build.
+        let library =
+            LibrarySnapshot::new(&strategy_manager.build_strategy(&self.name, false).await?)?;
+
+        let market = BacktestMarket::load(
+            db_pool,
+            &self.exchange,
+            &self.symbol,
+            self.timeframe,
+            None,
+            None,
+        )
+        .await?;
+        let fold_ranges = self.fold_ranges(market.candles.len())?;
+        self.total_folds = fold_ranges.len();
+
+        let now = Utc::now();
+        self.status = WalkForwardStatus::Running;
+        self.updated_at = now;
+        self.broadcast();
+
+        tracing::info!(
+            "Walking forward {} over {} folds of {} combinations on {}/{} with timeframe {}",
+            self.name,
+            self.total_folds,
+            combinations.len(),
+            self.exchange,
+            self.symbol,
+            self.timeframe
+        );
+
+        // Each fold runs every combination in sample and one backtest out of
+        // sample.
+        let total_runs = self.total_folds * (combinations.len() + 1);
+        let mut completed_runs = 0;
+
+        for (index, (in_sample, out_of_sample)) in fold_ranges.into_iter().enumerate() {
+            let candles = &market.candles;
+            self.folds.push(WalkForwardFold {
+                index,
+                in_sample_start: candles[in_sample.start].timestamp,
+                in_sample_end: candles[in_sample.end - 1].timestamp,
+                out_of_sample_start: candles[out_of_sample.start].timestamp,
+                out_of_sample_end: candles[out_of_sample.end - 1].timestamp,
+                in_sample: None,
+                out_of_sample: None,
+                failed_combinations: 0,
+                error_message: None,
+            });
+
+            let in_sample_markets: Arc<[BacktestMarket]> =
+                Arc::new([market.slice(in_sample.clone())]);
+            let mut best: Option<OptimizeResult> = None;
+            for params in &combinations {
+                let result = run_with_params(
+                    strategy_manager,
+                    library.path(),
+                    params,
+                    in_sample_markets.clone(),
+                    0,
+                    limits,
+                )
+                .await;
+
+                match result {
+                    Ok(statistic) => {
+                        let result = OptimizeResult::new(params.clone(), self.metric, &statistic);
+                        if best.as_ref().is_none_or(|best| result.score > best.score) {
+                            best = Some(result);
+                        }
+                    }
+                    Err(e) => {
+                        tracing::warn!(
+                            "Walk-forward fold {} run with params {} failed: {}",
+                            index,
+                            params,
+                            e
+                        );
+                        if let Some(fold) = self.folds.last_mut() {
+                            fold.failed_combinations += 1;
+                        }
+                    }
+                }
+
+                completed_runs += 1;
+                self.report_progress(completed_runs, total_runs);
+            }
+
+            let out_of_sample = match &best {
+                Some(best) => {
+                    self.run_out_of_sample(
+                        strategy_manager,
+                        library.path(),
+                        &market,
+                        in_sample.start..out_of_sample.end,
+                        best,
+                        limits,
+                    )
+                    .await
+                }
+                None => Err(format!("All {} combinations failed", combinations.len()).into()),
+            };
+            if let Some(fold) = self.folds.last_mut() {
+                fold.in_sample = best;
+                match out_of_sample {
+                    Ok(result) => fold.out_of_sample = Some(result),
+                    Err(e) => fold.error_message = Some(e.to_string()),
+                }
+            }
+
+            completed_runs += 1;
+            self.report_progress(completed_runs, total_runs);
+        }
+
+        Ok(())
+    }
+
+    /// Backtests the best in-sample params over `range`, with the in-sample
+    /// candles at its start as warmup so that only out-of-sample candles are
+    /// traded.
+    async fn run_out_of_sample(
+        &self,
+        strategy_manager: &StrategyManager,
+        lib_path: &Path,
+        market: &BacktestMarket,
+        range: Range<usize>,
+        best: &OptimizeResult,
+        limits: RunLimits,
+    ) -> AppResult<OptimizeResult> {
+        let markets: Arc<[BacktestMarket]> = Arc::new([market.slice(range)]);
+        let statistic = run_with_params(
+            strategy_manager,
+            lib_path,
+            &best.params,
+            markets,
+            self.in_sample,
+            limits,
+        )
+        .await?;
+
+        Ok(OptimizeResult::new(
+            best.params.clone(),
+            self.metric,
+            &statistic,
+        ))
+    }
+
+    fn report_progress(&mut self, completed_runs: usize, total_runs: usize) {
+        self.progress = 100.0 * (completed_runs as f32) / (total_runs as f32);
+        self.updated_at = Utc::now();
+        self.broadcast();
+    }
+}
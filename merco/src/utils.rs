@@ -1,35 +1,43 @@
 use crate::errors::{AppError, AppResult};
 use bigdecimal::BigDecimal;
 use std::{
-    path::{Path, PathBuf},
+    path::{Component, Path, PathBuf},
     str::FromStr,
 };
 
 pub fn safe_join(base_dir: &Path, path: &str) -> AppResult<PathBuf> {
-    let path = path.trim().trim_start_matches('/');
-
-    if path.contains("..") {
+    let relative = Path::new(path.trim().trim_start_matches('/'));
+    if relative
+        .components()
+        .any(|component| !matches!(component, Component::Normal(_) | Component::CurDir))
+    {
         return Err(AppError::BadRequest(
             "Path traversal attempt detected".to_string(),
         ));
     }
 
-    let candidate = base_dir.join(path);
     let base_canonical = base_dir.canonicalize()?;
+    let candidate = base_canonical.join(relative);
 
-    let canonical = if candidate.exists() {
-        candidate.canonicalize()?
-    } else {
-        if let Some(parent) = candidate.parent() {
-            let canonical_parent = parent.canonicalize()?;
-            let file_name = candidate
-                .file_name()
-                .ok_or(AppError::BadRequest("Invalid path".to_string()))?;
-            canonical_parent.join(file_name)
-        } else {
-            candidate
-        }
-    };
+    // Canonicalize the deepest entry that exists, so symlinks anywhere along
+    // the path are resolved before checking it stays inside the base directory.
+    let mut existing = candidate.as_path();
+    let mut missing = Vec::new();
+    while existing.symlink_metadata().is_err() {
+        let (Some(parent), Some(file_name)) = (existing.parent(), existing.file_name()) else {
+            return Err(AppError::BadRequest("Invalid path".to_string()));
+        };
+        missing.push(file_name);
+        existing = parent;
+    }
+
+    // A dangling symlink has metadata but no canonical form.
+    let mut canonical = existing
+        .canonicalize()
+        .map_err(|_| AppError::BadRequest("Invalid path".to_string()))?;
+    for file_name in missing.iter().rev() {
+        canonical.push(file_name);
+    }
 
     if !canonical.starts_with(&base_canonical) {
         return Err(AppError::BadRequest(
@@ -43,3 +51,107 @@ pub fn safe_join(base_dir: &Path, path: &str) -> AppResult<PathBuf> {
 pub fn str_to_bigdecimal(value: &str, field_name: &str) -> AppResult<BigDecimal> {
     BigDecimal::from_str(value).map_err(|_| format!("Invalid {}: {}", field_name, value).into())
 }
+
+/// Levenshtein distance between two strings, counted in characters.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+// The symlink cases need Unix symlinks.
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::os::unix::fs::symlink;
+    use tempfile::TempDir;
+
+    fn workspace() -> TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("src/lib.rs"), "").unwrap();
+        dir
+    }
+
+    #[test]
+    fn joins_paths_inside_the_base() {
+        let dir = workspace();
+        let base = dir.path().canonicalize().unwrap();
+        assert_eq!(
+            safe_join(dir.path(), "src/lib.rs").unwrap(),
+            base.join("src/lib.rs")
+        );
+        assert_eq!(
+            safe_join(dir.path(), "src/new.rs").unwrap(),
+            base.join("src/new.rs")
+        );
+        assert_eq!(
+            safe_join(dir.path(), "./src/a/b.rs").unwrap(),
+            base.join("src/a/b.rs")
+        );
+    }
+
+    #[test]
+    fn rejects_parent_components() {
+        let dir = workspace();
+        assert!(safe_join(dir.path(), "../outside.rs").is_err());
+        assert!(safe_join(dir.path(), "src/../../outside.rs").is_err());
+        assert!(safe_join(dir.path(), "src/..").is_err());
+    }
+
+    #[test]
+    fn treats_absolute_paths_as_relative_to_the_base() {
+        let dir = workspace();
+        let base = dir.path().canonicalize().unwrap();
+        assert_eq!(
+            safe_join(dir.path(), "/src/lib.rs").unwrap(),
+            base.join("src/lib.rs")
+        );
+        assert_eq!(
+            safe_join(dir.path(), "/etc/passwd").unwrap(),
+            base.join("etc/passwd")
+        );
+    }
+
+    #[test]
+    fn rejects_symlinks_escaping_the_base() {
+        let dir = workspace();
+        let outside = tempfile::tempdir().unwrap();
+        fs::write(outside.path().join("secret"), "").unwrap();
+        symlink(outside.path(), dir.path().join("src/escape")).unwrap();
+        symlink(outside.path().join("secret"), dir.path().join("secret")).unwrap();
+
+        assert!(safe_join(dir.path(), "src/escape").is_err());
+        assert!(safe_join(dir.path(), "src/escape/secret").is_err());
+        assert!(safe_join(dir.path(), "src/escape/new.rs").is_err());
+        assert!(safe_join(dir.path(), "secret").is_err());
+    }
+
+    #[test]
+    fn follows_symlinks_within_the_base() {
+        let dir = workspace();
+        let base = dir.path().canonicalize().unwrap();
+        symlink(dir.path().join("src"), dir.path().join("link")).unwrap();
+        assert_eq!(
+            safe_join(dir.path(), "link/lib.rs").unwrap(),
+            base.join("src/lib.rs")
+        );
+    }
+
+    #[test]
+    fn rejects_dangling_symlinks() {
+        let dir = workspace();
+        symlink(dir.path().join("missing"), dir.path().join("src/dangling")).unwrap();
+        assert!(safe_join(dir.path(), "src/dangling").is_err());
+        assert!(safe_join(dir.path(), "src/dangling/file.rs").is_err());
+    }
+}
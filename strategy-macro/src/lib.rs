@@ -1,21 +1,123 @@
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{DeriveInput, parse_macro_input};
+use syn::{DeriveInput, Expr, ExprLit, Lit, LitStr, Meta, parse_macro_input};
 
+/// Exports a `Strategy` implementation from a plugin library.
+///
+/// Optional `name`, `description`, `author` and `version` arguments describe
+/// the strategy, e.g. `#[strategy(name = "Moving Average Cross")]`. They
+/// default to the struct name, its doc comment and the crate's package
+/// authors and version.
 #[proc_macro_attribute]
-pub fn strategy(_attr: TokenStream, item: TokenStream) -> TokenStream {
+pub fn strategy(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut meta_name: Option<LitStr> = None;
+    let mut meta_description: Option<LitStr> = None;
+    let mut meta_author: Option<LitStr> = None;
+    let mut meta_version: Option<LitStr> = None;
+    let parser = syn::meta::parser(|meta| {
+        let field = if meta.path.is_ident("name") {
+            &mut meta_name
+        } else if meta.path.is_ident("description") {
+            &mut meta_description
+        } else if meta.path.is_ident("author") {
+            &mut meta_author
+        } else if meta.path.is_ident("version") {
+            &mut meta_version
+        } else {
+            return Err(meta.error("unsupported strategy argument"));
+        };
+        *field = Some(meta.value()?.parse()?);
+        Ok(())
+    });
+    parse_macro_input!(attr with parser);
+
     let input: DeriveInput = parse_macro_input!(item as DeriveInput);
     let name = &input.ident;
 
-    const PLUGIN_CREATE_FUNCTION_NAME: &'static str = "_plugin_create";
+    let meta_name = meta_name.unwrap_or_else(|| LitStr::new(&name.to_string(), name.span()));
+    let meta_description = meta_description.unwrap_or_else(|| {
+        let docs: Vec<String> = input
+            .attrs
+            .iter()
+            .filter_map(|attr| match &attr.meta {
+                Meta::NameValue(meta) if meta.path.is_ident("doc") => match &meta.value {
+                    Expr::Lit(ExprLit {
+                        lit: Lit::Str(doc), ..
+                    }) => Some(doc.value().trim().to_string()),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect();
+        LitStr::new(docs.join("\n").trim(), name.span())
+    });
+    let meta_author = match meta_author {
+        Some(author) => quote! { #author },
+        None => quote! { env!("CARGO_PKG_AUTHORS") },
+    };
+    let meta_version = match meta_version {
+        Some(version) => quote! { #version },
+        None => quote! { env!("CARGO_PKG_VERSION") },
+    };
+
+    const PLUGIN_CREATE_FUNCTION_NAME: &str = "_plugin_create";
+    const PLUGIN_CREATE_WITH_CONFIG_FUNCTION_NAME: &str = "_plugin_create_with_config";
+    const PLUGIN_ABI_VERSION_FUNCTION_NAME: &str = "_plugin_abi_version";
+    const PLUGIN_METADATA_FUNCTION_NAME: &str = "_plugin_metadata";
     let func_name = syn::Ident::new(PLUGIN_CREATE_FUNCTION_NAME, name.span());
+    let config_func_name = syn::Ident::new(PLUGIN_CREATE_WITH_CONFIG_FUNCTION_NAME, name.span());
+    let abi_func_name = syn::Ident::new(PLUGIN_ABI_VERSION_FUNCTION_NAME, name.span());
+    let metadata_func_name = syn::Ident::new(PLUGIN_METADATA_FUNCTION_NAME, name.span());
 
     let expanded = quote! {
         #input
 
         #[unsafe(no_mangle)]
-        pub fn #func_name() -> *mut dyn ::merco::Strategy {
-            let strategy = <#name as ::std::default::Default>::default();
+        pub extern "C" fn #abi_func_name() -> u32 {
+            ::merco::PLUGIN_ABI_VERSION
+        }
+
+        #[unsafe(no_mangle)]
+        #[allow(improper_ctypes_definitions)]
+        pub extern "C" fn #metadata_func_name() -> *const ::merco::PluginMetadata {
+            static METADATA: ::merco::PluginMetadata = ::merco::PluginMetadata {
+                name: #meta_name,
+                description: #meta_description,
+                author: #meta_author,
+                version: #meta_version,
+            };
+            &METADATA
+        }
+
+        #[unsafe(no_mangle)]
+        #[allow(improper_ctypes_definitions)]
+        pub extern "C" fn #func_name() -> *mut dyn ::merco::Strategy {
+            let create = <#name as ::std::default::Default>::default;
+            let Ok(strategy) = ::std::panic::catch_unwind(create) else {
+                return ::std::ptr::null_mut::<::merco::GuardedStrategy<#name>>();
+            };
+            Box::into_raw(Box::new(::merco::GuardedStrategy(strategy)))
+        }
+
+        #[unsafe(no_mangle)]
+        #[allow(improper_ctypes_definitions)]
+        pub unsafe extern "C" fn #config_func_name(
+            ptr: *const u8,
+            len: usize,
+        ) -> *mut dyn ::merco::Strategy {
+            let bytes = unsafe { ::std::slice::from_raw_parts(ptr, len) };
+            let Ok(params) = ::std::str::from_utf8(bytes) else {
+                return ::std::ptr::null_mut::<::merco::GuardedStrategy<#name>>();
+            };
+
+            let create = <#name as ::std::default::Default>::default;
+            let mut strategy = match ::std::panic::catch_unwind(create) {
+                Ok(strategy) => ::merco::GuardedStrategy(strategy),
+                Err(_) => return ::std::ptr::null_mut::<::merco::GuardedStrategy<#name>>(),
+            };
+            if ::merco::Strategy::configure(&mut strategy, params).is_err() {
+                return ::std::ptr::null_mut::<::merco::GuardedStrategy<#name>>();
+            }
             Box::into_raw(Box::new(strategy))
         }
     };